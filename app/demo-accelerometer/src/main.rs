@@ -1,23 +1,25 @@
 #![no_std]
 #![no_main]
 
-use core::fmt::Write;
+use core::fmt::{self, Write};
 use arrayvec::ArrayString;
 use embedded_graphics::Drawable;
 use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::*;
-use embedded_graphics::mono_font::{ascii::FONT_5X7, MonoTextStyle};
+use embedded_graphics::mono_font::{ascii::FONT_4X6, ascii::FONT_5X7, MonoTextStyle};
 use embedded_graphics::text::Text;
 use embedded_hal::spi::{Mode as SpiMode, Phase, Polarity};
 use embedded_hal::{blocking::spi, digital::v2::OutputPin};
 use embedded_hal::blocking::delay::DelayUs;
 use hx1230::command::{init_sequence};
 use hx1230::{ArrayDisplayBuffer, DisplayBuffer, SpiDriver, command, DisplayDriver};
+use lib_i2c_bus::RecoveringI2c;
+use lib_imu::{detect, Complementary, Config, Fusion, Madgwick, Mahony, Measurement, Mpu6050, Orientation};
 
 use cortex_m_rt::entry;
+use nb::block;
 use stm32f1xx_hal::i2c::{BlockingI2c, Mode as I2CMode, DutyCycle};
 use stm32f1xx_hal::{pac, prelude::*, spi::{NoMiso, Spi}};
-use mpu6050::*;
 
 use lib_panic_led as _;
 
@@ -26,9 +28,59 @@ pub const SPI_MODE: SpiMode = SpiMode {
     polarity: Polarity::IdleLow,
 };
 
+/// Display is refreshed once in this many samples
+const REFRESH_SAMPLES: u32 = 10;
+
+/// Samples each page is shown for
+const PAGE_SAMPLES: u32 = 500;
+
+/// Pages of the display, switched every `PAGE_SAMPLES`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Page {
+    Measurement,
+    Orientation,
+}
+
+impl Page {
+    fn next(self) -> Self {
+        match self {
+            Page::Measurement => Page::Orientation,
+            Page::Orientation => Page::Measurement,
+        }
+    }
+}
+
+/// Orientation computed by all the filters from the same samples
+struct Filters {
+    complementary: Complementary,
+    madgwick: Madgwick,
+    mahony: Mahony,
+    /// From the last acceleration only, for comparison
+    tilt: Orientation,
+}
+
+impl Filters {
+    fn new(sample_rate_hz: u32) -> Self {
+        Self {
+            complementary: Complementary::new(sample_rate_hz, 1000),
+            madgwick: Madgwick::new(sample_rate_hz, 60),
+            mahony: Mahony::new(sample_rate_hz, 1000, 50),
+            tilt: Orientation::default(),
+        }
+    }
+
+    fn update(&mut self, measurement: &Measurement) {
+        let (acceleration, angular_rate) = (&measurement.acceleration, &measurement.angular_rate);
+        self.complementary.update(acceleration, angular_rate);
+        self.madgwick.update(acceleration, angular_rate);
+        self.mahony.update(acceleration, angular_rate);
+        self.tilt = Orientation::from_acceleration(acceleration);
+    }
+}
+
 #[entry]
 fn main() -> ! {
-    let cp = cortex_m::Peripherals::take().unwrap();
+    let mut cp = cortex_m::Peripherals::take().unwrap();
     let dp = pac::Peripherals::take().unwrap();
 
     let mut flash = dp.FLASH.constrain();
@@ -44,6 +96,10 @@ fn main() -> ! {
         .hclk(8.MHz())     // clock used for timers
         .freeze(&mut flash.acr);
 
+    // I2C timeouts are measured by the cycle counter
+    cp.DCB.enable_trace();
+    cp.DWT.enable_cycle_counter();
+
     let mut gpiob = dp.GPIOB.split();
     let mut gpioc = dp.GPIOC.split();
 
@@ -80,6 +136,9 @@ fn main() -> ! {
         1000,
     );
 
+    // Failed transactions recover the bus and are retried
+    let mut i2c = RecoveringI2c::new(i2c, clocks);
+
     let mut delay = cp.SYST.delay(&clocks);
     delay.delay_ms(100_u16);
 
@@ -87,63 +146,167 @@ fn main() -> ! {
 
     init_display(&mut spi, &mut display_cs, &mut delay).unwrap();
 
-    let mut diameter = 1;
     let text_style = MonoTextStyle::new(&FONT_5X7, BinaryColor::On);
 
-    let mut mpu = Mpu6050::new_with_addr(i2c, 0x72);
     let mut text = ArrayString::<32>::new();
 
-    let _ = write!(&mut text, "Start");
-    Text::new(&text, Point::new(0, 5), text_style).draw(&mut frame_buffer).unwrap();
-    let mut driver = SpiDriver::new(&mut spi, &mut display_cs);
-    driver.send_buffer(&frame_buffer).unwrap();
+    // MPU6050 answers at 0x68 or 0x69 depending on the AD0 pin
+    let config = Config::orientation();
+
+    let mpu = match detect(&mut i2c).and_then(|address| Mpu6050::new(&mut i2c, address, config)) {
+        Ok(mpu) => mpu,
+        Err(err) => {
+            let _ = write!(&mut text, "ERROR:\n{:?}", err);
+            Text::new(&text, Point::new(0, 5), text_style).draw(&mut frame_buffer).unwrap();
+            let mut driver = SpiDriver::new(&mut spi, &mut display_cs);
+            driver.send_buffer(&frame_buffer).unwrap();
+            panic!("no MPU6050 found");
+        }
+    };
+
+    // Filters need samples at a fixed rate, the sensor takes them at
+    // the same rate, so every read gets a new one
+    let sample_rate_hz = config.sample_rate_hz();
+    let mut filters = Filters::new(sample_rate_hz);
+    let mut timer = dp.TIM2.counter_hz(&clocks);
+    timer.start(sample_rate_hz.Hz()).unwrap();
+
+    let mut page = Page::Measurement;
+    let mut samples = 0;
+
+    loop {
+        block!(timer.wait()).unwrap();
+
+        let result = mpu.read(&mut i2c);
+
+        if let Ok(measurement) = &result {
+            filters.update(measurement);
+        }
+
+        samples += 1;
 
-    if let Err(err) = mpu.init(&mut delay) {
+        if samples % REFRESH_SAMPLES != 0 {
+            continue;
+        }
+
+        led.set_low();
         clear(&mut frame_buffer);
-        text.clear();
-        let _ = write!(&mut text, "ERROR:\n{:?}", err);
-        Text::new(&text, Point::new(0, 5), text_style).draw(&mut frame_buffer).unwrap();
+
+        match (result, page) {
+            (Ok(measurement), Page::Measurement) => print_measurement(&mut frame_buffer, &mpu, &measurement).unwrap(),
+            (Ok(_), Page::Orientation) => print_orientation(&mut frame_buffer, &filters).unwrap(),
+            (Err(err), _) => {
+                text.clear();
+                let _ = write!(&mut text, "ERROR:\n{:?}", err);
+                Text::new(&text, Point::new(0, 5), text_style).draw(&mut frame_buffer).unwrap();
+            }
+        }
+
         let mut driver = SpiDriver::new(&mut spi, &mut display_cs);
         driver.send_buffer(&frame_buffer).unwrap();
-        loop {}
+
+        led.set_high();
+
+        if samples >= PAGE_SAMPLES {
+            samples = 0;
+            page = page.next();
+        }
     }
+}
 
-    loop {
-        led.set_low();
-        clear(&mut frame_buffer);
+/// Roll, pitch and yaw in degrees by every filter
+fn print_orientation(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    filters: &Filters,
+) -> Result<(), ()> {
+    let mut text = ArrayString::<32>::new();
+    let text_style = MonoTextStyle::new(&FONT_4X6, BinaryColor::On);
 
-        clear_line(&mut frame_buffer, 0);
-        clear_line(&mut frame_buffer, 1);
+    let mut print_line = |line: i32, text: &str| {
+        Text::new(text, Point::new(0, 5 + 8*line), text_style).draw(frame_buffer).map_err(|_| ())
+    };
 
-        let angles = mpu.get_acc_angles().unwrap();
-        let temp = mpu.get_temp().unwrap();
-        let gyro = mpu.get_gyro().unwrap();
-        let acc = mpu.get_acc().unwrap();
+    print_line(0, "      roll pitch    yaw")?;
 
-        text.clear();
-        let _ = write!(&mut text, "ANG {:?}", angles);
-        Text::new(&text, Point::new(0, 5), text_style).draw(&mut frame_buffer).unwrap();
+    let rows = [
+        ("Cmp", filters.complementary.orientation()),
+        ("Mdg", filters.madgwick.orientation()),
+        ("Mhn", filters.mahony.orientation()),
+        ("Acc", filters.tilt),
+    ];
 
+    for (line, (name, orientation)) in rows.iter().enumerate() {
         text.clear();
-        let _ = write!(&mut text, "T {:?}", temp);
-        Text::new(&text, Point::new(0, 12), text_style).draw(&mut frame_buffer).unwrap();
+        write!(
+            &mut text, "{} {:>6} {:>5} {:>6}",
+            name,
+            Tenths::from_thousandths(orientation.roll),
+            Tenths::from_thousandths(orientation.pitch),
+            Tenths::from_thousandths(orientation.yaw),
+        ).map_err(|_| ())?;
+        print_line(1 + line as i32, &text)?;
+    }
 
-        text.clear();
-        let _ = write!(&mut text, "G {:?}", gyro);
-        Text::new(&text, Point::new(0, 19), text_style).draw(&mut frame_buffer).unwrap();
+    Ok(())
+}
+
+/// Acceleration in milli-g and angular rate in degrees per second
+/// for every axis, the temperature and the configuration
+fn print_measurement(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    mpu: &Mpu6050,
+    measurement: &Measurement,
+) -> Result<(), ()> {
+    let mut text = ArrayString::<32>::new();
+    let text_style = MonoTextStyle::new(&FONT_5X7, BinaryColor::On);
+    let config = mpu.config();
+    let acc = measurement.acceleration;
+    let gyro = measurement.angular_rate;
+
+    let mut print_line = |line: i32, text: &str| {
+        Text::new(text, Point::new(0, 5 + 8*line), text_style).draw(frame_buffer).map_err(|_| ())
+    };
+
+    write!(&mut text, "MPU6050 at {:#04x}", mpu.address()).map_err(|_| ())?;
+    print_line(0, &text)?;
+    print_line(1, "       mg     dps")?;
 
+    for (line, (axis, acc, gyro)) in [("X", acc.x, gyro.x), ("Y", acc.y, gyro.y), ("Z", acc.z, gyro.z)].iter().enumerate() {
         text.clear();
-        let _ = write!(&mut text, "ACC {:?}", acc);
-        Text::new(&text, Point::new(0, 26), text_style).draw(&mut frame_buffer).unwrap();
+        write!(&mut text, "{} {:>6} {:>7}", axis, acc, Tenths::from_thousandths(*gyro)).map_err(|_| ())?;
+        print_line(2 + line as i32, &text)?;
+    }
 
-        let mut driver = SpiDriver::new(&mut spi, &mut display_cs);
-        driver.send_buffer(&frame_buffer).unwrap();
+    text.clear();
+    write!(&mut text, "T {} C", measurement.temperature).map_err(|_| ())?;
+    print_line(5, &text)?;
+    text.clear();
+    write!(&mut text, "+-{}g +-{}dps", config.accel_range.g(), config.gyro_range.dps()).map_err(|_| ())?;
+    print_line(6, &text)?;
+    text.clear();
+    write!(&mut text, "LP {}Hz at {}Hz", config.low_pass.hz(), config.sample_rate_hz()).map_err(|_| ())?;
+    print_line(7, &text)?;
 
-        diameter = diameter + 1;
+    Ok(())
+}
 
-        led.set_high();
+/// Value with a single decimal, rounded from thousandths
+struct Tenths(i32);
+
+impl Tenths {
+    fn from_thousandths(value: i32) -> Self {
+        Self((value + 50*value.signum())/100)
+    }
+}
 
-        delay.delay_ms(100_u16);
+/// Padded as a whole, so it can be aligned
+impl fmt::Display for Tenths {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut text = ArrayString::<12>::new();
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let value = self.0.unsigned_abs();
+        write!(&mut text, "{}{}.{}", sign, value/10, value % 10)?;
+        f.pad(&text)
     }
 }
 
@@ -0,0 +1,119 @@
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::Mutex;
+use cortex_m_rt::entry;
+use lib_tone::{Buzzer, Melody, Player};
+use stm32f1xx_hal::gpio::{Alternate, PushPull, PA0};
+use stm32f1xx_hal::pac::{interrupt, Interrupt, TIM2, TIM3};
+use stm32f1xx_hal::timer::{Ch, Channel, CounterHz, Event, PwmHz, Tim2NoRemap, C1};
+use stm32f1xx_hal::{pac, prelude::*};
+use lib_panic_led as _;
+
+/// Frequency of player updates
+const UPDATE_RATE: u32 = 100;
+
+/// Time between two player updates in milliseconds
+const UPDATE_MS: u32 = 1000/UPDATE_RATE;
+
+/// Fast beeping played when a threshold is crossed
+const ALARM: &str = "alarm:d=16,o=6,b=140:c7,p,c7,p,c7,p,c7,8p,c7,p,c7,p,c7,p,c7,4p";
+
+/// Slow falling tone signalling a return to normal values
+const CLEAR: &str = "clear:d=8,o=5,b=100:c6,g,e,4c";
+
+type BuzzerPwm = PwmHz<TIM2, Tim2NoRemap, Ch<C1>, PA0<Alternate<PushPull>>>;
+
+struct Alarm {
+    timer: CounterHz<TIM3>,
+    buzzer: Buzzer<BuzzerPwm>,
+    player: Player<'static>,
+}
+
+impl Alarm {
+    fn update(&mut self) {
+        self.timer.clear_interrupt(Event::Update);
+
+        if let Some(output) = self.player.update(UPDATE_MS) {
+            self.buzzer.apply(output);
+        }
+    }
+}
+
+// Player and buzzer are moved to the interrupt handler once initialized
+static G_ALARM: Mutex<RefCell<Option<Alarm>>> = Mutex::new(RefCell::new(None));
+
+#[interrupt]
+fn TIM3() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(alarm) = G_ALARM.borrow(cs).borrow_mut().as_mut() {
+            alarm.update();
+        }
+    });
+}
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr.use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    let mut gpioa = dp.GPIOA.split();
+    let mut afio = dp.AFIO.constrain();
+
+    // Piezo buzzer between PA0 and ground
+    let pin = gpioa.pa0.into_alternate_push_pull(&mut gpioa.crl);
+
+    let pwm = dp
+        .TIM2
+        .pwm_hz::<Tim2NoRemap, _, _>(pin, &mut afio.mapr, 1.kHz(), &clocks);
+
+    let buzzer = Buzzer::new(pwm, Channel::C1);
+
+    // Melodies are validated before they are played
+    let alarm = Melody::parse(ALARM).unwrap();
+    let clear = Melody::parse(CLEAR).unwrap();
+
+    // Melody is timed by TIM3, independently of the main loop
+    let mut timer = dp.TIM3.counter_hz(&clocks);
+    timer.start(UPDATE_RATE.Hz()).unwrap();
+    timer.listen(Event::Update);
+
+    let state = Alarm { timer, buzzer, player: Player::new() };
+
+    cortex_m::interrupt::free(|cs| G_ALARM.borrow(cs).replace(Some(state)));
+
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(Interrupt::TIM3);
+    }
+
+    let mut delay = cp.SYST.delay(&clocks);
+
+    loop {
+        // Simulate a measured value crossing the threshold, the alarm keeps
+        // beeping while the value stays above it
+        play(|player| player.play_looped(alarm));
+        delay.delay_ms(5000u32);
+
+        play(|player| player.play(clear));
+        delay.delay_ms(5000u32);
+    }
+}
+
+fn play<F: FnOnce(&mut Player<'static>)>(start: F) {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(alarm) = G_ALARM.borrow(cs).borrow_mut().as_mut() {
+            start(&mut alarm.player);
+        }
+    });
+}
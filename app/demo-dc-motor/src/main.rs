@@ -0,0 +1,94 @@
+#![no_std]
+#![no_main]
+
+use cortex_m_rt::entry;
+use lib_dc_motor::{Config, DualPwm, HBridge, Motor, PwmDirection, StopMode};
+use stm32f1xx_hal::timer::Tim2NoRemap;
+use stm32f1xx_hal::{pac, prelude::*};
+use lib_panic_led as _;
+
+/// Time between two motor updates in milliseconds
+const UPDATE_MS: u32 = 10;
+
+/// Target speeds of both motors and the time they are held
+const STEPS: [(i16, i16, u32); 5] = [
+    (800, 800, 3000),
+    (-800, 400, 3000),
+    (300, -1000, 3000),
+    (1000, 1000, 2000),
+    (0, 0, 2000),
+];
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr.use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    let mut gpioa = dp.GPIOA.split();
+    let mut gpiob = dp.GPIOB.split();
+
+    // PWM input of a TB6612 (or the enable input of a L298N) on PA0,
+    // both inputs of a DRV8833 on PA2 and PA3
+    let p1 = gpioa.pa0.into_alternate_push_pull(&mut gpioa.crl);
+    let p3 = gpioa.pa2.into_alternate_push_pull(&mut gpioa.crl);
+    let p4 = gpioa.pa3.into_alternate_push_pull(&mut gpioa.crl);
+
+    let mut afio = dp.AFIO.constrain();
+    let pins = (p1, p3, p4);
+
+    // 20 kHz is above the audible range, so the motors do not whine
+    let (mut pwm, mut in_a, mut in_b) = dp
+        .TIM2
+        .pwm_hz::<Tim2NoRemap, _, _>(pins, &mut afio.mapr, 20.kHz(), &clocks)
+        .split();
+
+    pwm.enable();
+    in_a.enable();
+    in_b.enable();
+
+    // Direction inputs of the TB6612
+    let in1 = gpiob.pb10.into_push_pull_output(&mut gpiob.crh);
+    let in2 = gpiob.pb11.into_push_pull_output(&mut gpiob.crh);
+
+    let mut left = Motor::new(
+        PwmDirection::new(pwm, in1, in2).unwrap(),
+        Config { stop_mode: StopMode::Brake, ..Config::default() },
+    );
+
+    // Faster ramps and a longer dead time for a larger motor
+    let mut right = Motor::new(
+        DualPwm::new(in_a, in_b),
+        Config { acceleration: 1000, deceleration: 2000, dead_time_ms: 250, stop_mode: StopMode::Coast },
+    );
+
+    let mut delay = cp.SYST.delay(&clocks);
+
+    loop {
+        for &(left_speed, right_speed, hold_ms) in STEPS.iter() {
+            left.set_speed(left_speed);
+            right.set_speed(right_speed);
+
+            let mut elapsed_ms = 0;
+
+            while elapsed_ms < hold_ms {
+                update(&mut left).unwrap();
+                update(&mut right).unwrap();
+                delay.delay_ms(UPDATE_MS);
+                elapsed_ms += UPDATE_MS;
+            }
+        }
+    }
+}
+
+fn update<B: HBridge>(motor: &mut Motor<B>) -> Result<(), ()> {
+    motor.update(UPDATE_MS).map_err(|_| ())
+}
@@ -0,0 +1,77 @@
+#![no_std]
+#![no_main]
+
+use cortex_m_rt::entry;
+use lib_max7219_matrix::{ChainOrder, Marquee, MatrixBuffer, Orientation};
+use max7219::MAX7219;
+use stm32f1xx_hal::{pac, prelude::*, spi::{NoMiso, Spi}};
+use lib_panic_led as _;
+use embedded_hal::{
+    spi::{Mode, Phase, Polarity},
+};
+
+pub const SPI_MODE: Mode = Mode {
+    phase: Phase::CaptureOnFirstTransition,
+    polarity: Polarity::IdleLow,
+};
+
+/// Number of 8x8 modules in the chain
+const MODULES: usize = 4;
+
+/// Time between two animation steps in milliseconds
+const FRAME_MS: u32 = 10;
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    // Freeze the configuration of all the clocks in the system and store the frozen frequencies in
+    // `clocks`
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    let mut afio = dp.AFIO.constrain();
+    let mut gpioa = dp.GPIOA.split();
+    let mut gpioc = dp.GPIOC.split();
+
+    let mut delay = cp.SYST.delay(&clocks);
+
+    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+
+    // SPI1, we use only output, so there is no miso input
+    let sck = gpioa.pa5.into_alternate_push_pull(&mut gpioa.crl);
+    let mosi = gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl);
+    let cs = gpioa.pa6.into_push_pull_output(&mut gpioa.crl);
+
+    let spi = Spi::spi1(dp.SPI1, (sck, NoMiso, mosi), &mut afio.mapr, SPI_MODE, 1.MHz(), clocks);
+    delay.delay_ms(200_u16);
+    let mut display = MAX7219::from_spi_cs(MODULES, spi, cs).unwrap();
+    display.power_on().unwrap();
+
+    for module in 0..MODULES {
+        display.set_intensity(module, 0x02).unwrap();
+    }
+
+    let mut frame_buffer: MatrixBuffer<MODULES> = MatrixBuffer::new();
+    let mut marquee = Marquee::new("Hello from the Blue Pill!", frame_buffer.width())
+        .with_speed(25);
+
+    loop {
+        if marquee.update(FRAME_MS) {
+            led.set_low();
+            marquee.render(&mut frame_buffer);
+            frame_buffer.flush(&mut display, Orientation::Rotate90, ChainOrder::RightToLeft).unwrap();
+            led.set_high();
+        }
+
+        delay.delay_ms(FRAME_MS as u16);
+    }
+}
@@ -2,7 +2,8 @@
 #![no_main]
 
 use cortex_m_rt::entry;
-use max7219::MAX7219;
+use lib_max7219_supervisor::{Config, Health, Supervisor};
+use lib_seven_segment::{ScrollMode, Scroller};
 use stm32f1xx_hal::{pac, prelude::*, spi::{NoMiso, Spi}};
 use lib_panic_led as _;
 use embedded_hal::{
@@ -14,6 +15,9 @@ pub const SPI_MODE: Mode = Mode {
     polarity: Polarity::IdleLow,
 };
 
+/// Time between two display updates in milliseconds
+const FRAME_MS: u32 = 50;
+
 #[entry]
 fn main() -> ! {
     let cp = cortex_m::Peripherals::take().unwrap();
@@ -46,23 +50,39 @@ fn main() -> ! {
 
     let spi = Spi::spi1(dp.SPI1, (sck, NoMiso, mosi), &mut afio.mapr, SPI_MODE, 1.MHz(), clocks);
     delay.delay_ms(200_u16);
-    let mut display = MAX7219::from_spi_cs(1, spi, cs).unwrap();
 
-    let buffer = b"        Hello        ";
-    let mut shift: usize = 0;
-    let mut data = [0; 8];
+    // Registers are written again every second and the display test runs
+    // once a minute, so the display recovers from garbage latched after
+    // supply noise without a power cycle
+    let config = Config {
+        intensity: 0x02,
+        refresh_ms: 1000,
+        test_interval_ms: 60_000,
+        ..Config::default()
+    };
+
+    let mut display = Supervisor::new(spi, cs, 1, config);
+    let _ = display.init();
+
+    let mut scroller = Scroller::new("Hello, temperature is 21.5 C.")
+        .with_mode(ScrollMode::Wrap)
+        .with_step(300)
+        .with_dwell(1000);
 
     loop {
-        led.set_low();
-        display.power_on().unwrap();
-        data.copy_from_slice(&buffer[shift..(shift + 8)]);
+        // MAX7219 digit register 1 drives the rightmost digit
+        let mut data = scroller.frame();
+        data.reverse();
 
-        display.write_str(0, &data, 0x00).unwrap();
-        display.set_intensity(0, 0x02).unwrap();
-        shift = (shift + 1) % 13;
+        let _ = display.write_digits(0, &data);
 
-        led.set_high();
+        // Onboard LED is lit while the display is not healthy
+        match display.update(FRAME_MS) {
+            Health::Ok => led.set_high(),
+            Health::Recovering | Health::Failed => led.set_low(),
+        }
 
-        delay.delay_ms(300_u16);
+        delay.delay_ms(FRAME_MS as u16);
+        scroller.update(FRAME_MS);
     }
 }
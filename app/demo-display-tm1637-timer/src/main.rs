@@ -0,0 +1,109 @@
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::Mutex;
+use cortex_m_rt::entry;
+use lib_tm1637_timer::{Command, Tm1637Transport};
+use stm32f1xx_hal::gpio::{gpiob, OpenDrain, Output};
+use stm32f1xx_hal::pac::{interrupt, Interrupt, TIM2};
+use stm32f1xx_hal::timer::{CounterHz, Event};
+use stm32f1xx_hal::{pac, prelude::*};
+use lib_panic_led as _;
+
+type Display = Tm1637Transport<gpiob::PB9<Output<OpenDrain>>, gpiob::PB8<Output<OpenDrain>>>;
+
+// Display transport and its timer are shared between main and the interrupt handler
+static G_DISPLAY: Mutex<RefCell<Option<Display>>> = Mutex::new(RefCell::new(None));
+static G_TIMER: Mutex<RefCell<Option<CounterHz<TIM2>>>> = Mutex::new(RefCell::new(None));
+
+#[interrupt]
+fn TIM2() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(timer) = G_TIMER.borrow(cs).borrow_mut().as_mut() {
+            timer.clear_interrupt(Event::Update);
+        }
+
+        if let Some(display) = G_DISPLAY.borrow(cs).borrow_mut().as_mut() {
+            display.tick().unwrap();
+        }
+    });
+}
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr.use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    let mut gpiob = dp.GPIOB.split();
+    let mut gpioc = dp.GPIOC.split();
+
+    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+    led.set_low();
+
+    let mut delay = cp.SYST.delay(&clocks);
+
+    let clk = gpiob.pb9.into_open_drain_output(&mut gpiob.crh);
+    let dio = gpiob.pb8.into_open_drain_output(&mut gpiob.crh);
+
+    let mut display = Tm1637Transport::new(clk, dio);
+    display.submit(Command::Init).unwrap();
+    display.submit(Command::Brightness(7)).unwrap();
+
+    // Each tick is a half period of the display clock, 10 kHz gives 5 kHz
+    // on the wire and leaves the core 900 cycles of the 9 MHz HCLK per tick
+    let mut timer = dp.TIM2.counter_hz(&clocks);
+    timer.start(10.kHz()).unwrap();
+    timer.listen(Event::Update);
+
+    cortex_m::interrupt::free(|cs| {
+        G_DISPLAY.borrow(cs).replace(Some(display));
+        G_TIMER.borrow(cs).replace(Some(timer));
+    });
+
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(Interrupt::TIM2);
+    }
+
+    let mut index: u16 = 0;
+
+    loop {
+        // Submitting a frame takes only a few microseconds, the main loop
+        // is free to do other work while the frame is clocked out
+        let command = Command::hex_digits(0, &decimal_to_digits(index));
+
+        cortex_m::interrupt::free(|cs| {
+            if let Some(display) = G_DISPLAY.borrow(cs).borrow_mut().as_mut() {
+                let _ = display.submit_frame(command);
+            }
+        });
+
+        led.toggle();
+        delay.delay_ms(10_u16);
+
+        index += 1;
+
+        if index == 10000 {
+            index = 0;
+        }
+    }
+}
+
+fn decimal_to_digits(decimal: u16) -> [u8; 4] {
+    [
+        (decimal / 1000) as u8,
+        ((decimal % 1000) / 100) as u8,
+        ((decimal % 100) / 10) as u8,
+        (decimal % 10) as u8
+    ]
+}
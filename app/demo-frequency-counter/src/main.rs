@@ -0,0 +1,151 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use arrayvec::ArrayString;
+use embedded_graphics::Drawable;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::mono_font::{ascii::FONT_7X13, ascii::FONT_7X13_BOLD, MonoTextStyle};
+use embedded_graphics::text::Text;
+use embedded_hal::spi::{Mode as SpiMode, Phase, Polarity};
+use hx1230::{ArrayDisplayBuffer, DisplayBuffer, SpiDriver, DisplayDriver};
+use lib_input_capture::{FrequencyMeter, Measurement};
+use stm32f1xx_hal::timer::{Channel, Tim2NoRemap};
+use stm32f1xx_hal::{pac, prelude::*, spi::{NoMiso, Spi}};
+
+use cortex_m_rt::entry;
+
+use lib_panic_led as _;
+
+pub const SPI_MODE: SpiMode = SpiMode {
+    phase: Phase::CaptureOnFirstTransition,
+    polarity: Polarity::IdleLow,
+};
+
+/// Slower signals are shown as missing
+const MIN_FREQUENCY_HZ: u32 = 1;
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+    let mut afio = dp.AFIO.constrain();
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    // Timers run from the full 72 MHz clock to get the best resolution
+    // of fast signals
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(72.MHz())    // bus clock
+        .pclk1(36.MHz())   // APB1 clock, timers get 72 MHz
+        .freeze(&mut flash.acr);
+
+    let mut gpioa = dp.GPIOA.split();
+    let mut gpiob = dp.GPIOB.split();
+    let mut gpioc = dp.GPIOC.split();
+
+    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+    let mut display_cs = gpiob.pb12.into_push_pull_output(&mut gpiob.crh);
+
+    // SPI2, we use only output, so there is no miso input
+    let sck = gpiob.pb13.into_alternate_push_pull(&mut gpiob.crh);
+    let mosi = gpiob.pb15.into_alternate_push_pull(&mut gpiob.crh);
+
+    let mut spi = Spi::spi2(
+        dp.SPI2,
+        (sck, NoMiso, mosi),
+        SPI_MODE,
+        2.MHz(),
+        clocks,
+    );
+
+    // Test signal of 1 kHz with 25 % duty, connect PA0 to PA6 to measure it
+    let test_pin = gpioa.pa0.into_alternate_push_pull(&mut gpioa.crl);
+
+    let mut test_signal = dp
+        .TIM2
+        .pwm_hz::<Tim2NoRemap, _, _>(test_pin, &mut afio.mapr, 1.kHz(), &clocks);
+
+    test_signal.set_duty(Channel::C1, test_signal.get_max_duty()/4);
+    test_signal.enable(Channel::C1);
+
+    // Measured signal on the first channel of TIM3
+    let input = gpioa.pa6.into_floating_input(&mut gpioa.crl);
+
+    let mut meter = FrequencyMeter::new(
+        dp.TIM3,
+        input,
+        &mut afio.mapr,
+        &clocks,
+        MIN_FREQUENCY_HZ,
+    );
+
+    let mut delay = cp.SYST.delay(&clocks);
+
+    let mut frame_buffer: ArrayDisplayBuffer = ArrayDisplayBuffer::new();
+    let mut display = SpiDriver::new(&mut spi, &mut display_cs);
+    display.initialize(&mut delay).unwrap();
+
+    loop {
+        led.set_low();
+        frame_buffer.clear_buffer(0x00);
+
+        // Waits for a single period, or until the prescaler is at the
+        // maximum and no edge comes
+        let measurement = nb::block!(meter.read()).ok();
+
+        print_measurement(&mut frame_buffer, measurement).unwrap();
+
+        display.send_buffer(&frame_buffer).unwrap();
+
+        led.set_high();
+
+        delay.delay_ms(250_u16);
+    }
+}
+
+const FREQUENCY: &str = "Frequency:";
+const DUTY: &str = "Duty cycle:";
+
+fn print_measurement(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    measurement: Option<Measurement>,
+) -> Result<(), ()> {
+    let mut text = ArrayString::<20>::new();
+    let regular = MonoTextStyle::new(&FONT_7X13, BinaryColor::On);
+    let bold = MonoTextStyle::new(&FONT_7X13_BOLD, BinaryColor::On);
+
+    Text::new(FREQUENCY, Point::new(0, 15), regular).draw(frame_buffer).map_err(|_| ())?;
+    Text::new(DUTY, Point::new(0, 45), regular).draw(frame_buffer).map_err(|_| ())?;
+
+    let measurement = match measurement {
+        Some(measurement) => measurement,
+        None => {
+            Text::new("No signal", Point::new(0, 30), bold).draw(frame_buffer).map_err(|_| ())?;
+            return Ok(());
+        }
+    };
+
+    let millihertz = measurement.frequency_millihertz();
+
+    match millihertz < 1_000_000 {
+        true => write!(&mut text, "{}.{:03} Hz", millihertz/1000, millihertz % 1000),
+        false => {
+            let hertz = measurement.frequency_hz();
+            write!(&mut text, "{}.{:03} kHz", hertz/1000, hertz % 1000)
+        }
+    }.map_err(|_| ())?;
+
+    Text::new(&text, Point::new(0, 30), bold).draw(frame_buffer).map_err(|_| ())?;
+
+    let duty = measurement.duty_permille();
+    text.clear();
+    write!(&mut text, "{}.{} %", duty/10, duty % 10).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 60), bold).draw(frame_buffer).map_err(|_| ())?;
+
+    Ok(())
+}
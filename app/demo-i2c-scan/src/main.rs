@@ -0,0 +1,162 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use arrayvec::{ArrayString, ArrayVec};
+use embedded_graphics::Drawable;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::mono_font::{ascii::FONT_6X10, ascii::FONT_7X13_BOLD, MonoTextStyle};
+use embedded_graphics::text::Text;
+use embedded_hal::spi::{Mode as SpiMode, Phase, Polarity};
+use hx1230::{ArrayDisplayBuffer, DisplayBuffer, SpiDriver, DisplayDriver};
+use lib_i2c_scanner::{scan, Found};
+use stm32f1xx_hal::i2c::{BlockingI2c, Mode};
+use stm32f1xx_hal::serial::{Config, Serial};
+use stm32f1xx_hal::{pac, prelude::*, spi::{NoMiso, Spi}};
+
+use cortex_m_rt::entry;
+
+use lib_panic_led as _;
+
+pub const SPI_MODE: SpiMode = SpiMode {
+    phase: Phase::CaptureOnFirstTransition,
+    polarity: Polarity::IdleLow,
+};
+
+/// Lines of the display below the title
+const DISPLAY_LINES: usize = 5;
+
+/// At most 112 devices fit on the bus
+const MAX_DEVICES: usize = 112;
+
+#[entry]
+fn main() -> ! {
+    let mut cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+    let mut afio = dp.AFIO.constrain();
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    // I2C timeouts are measured by the cycle counter, without it a bus
+    // stuck low would hang the scan instead of being reported
+    cp.DCB.enable_trace();
+    cp.DWT.enable_cycle_counter();
+
+    let mut gpioa = dp.GPIOA.split();
+    let mut gpiob = dp.GPIOB.split();
+    let mut gpioc = dp.GPIOC.split();
+
+    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+    let mut display_cs = gpiob.pb12.into_push_pull_output(&mut gpiob.crh);
+
+    // SPI2, we use only output, so there is no miso input
+    let sck = gpiob.pb13.into_alternate_push_pull(&mut gpiob.crh);
+    let mosi = gpiob.pb15.into_alternate_push_pull(&mut gpiob.crh);
+
+    let mut spi = Spi::spi2(
+        dp.SPI2,
+        (sck, NoMiso, mosi),
+        SPI_MODE,
+        4.MHz(),
+        clocks,
+    );
+
+    // USART1 transmitting the results, connect PA9 to RX of a serial adapter
+    let tx = gpioa.pa9.into_alternate_push_pull(&mut gpioa.crh);
+    let rx = gpioa.pa10;
+
+    let serial = Serial::usart1(
+        dp.USART1,
+        (tx, rx),
+        &mut afio.mapr,
+        Config::default().baudrate(115_200.bps()),
+        clocks,
+    );
+
+    let (mut serial, _) = serial.split();
+
+    let scl = gpiob.pb6.into_alternate_open_drain(&mut gpiob.crl);
+    let sda = gpiob.pb7.into_alternate_open_drain(&mut gpiob.crl);
+
+    let mut i2c = BlockingI2c::i2c1(
+        dp.I2C1,
+        (scl, sda),
+        &mut afio.mapr,
+        Mode::Standard {
+            frequency: 100.kHz(),
+        },
+        clocks,
+        1000,
+        10,
+        1000,
+        1000,
+    );
+
+    let mut delay = cp.SYST.delay(&clocks);
+
+    let mut frame_buffer: ArrayDisplayBuffer = ArrayDisplayBuffer::new();
+    SpiDriver::new(&mut spi, &mut display_cs).initialize(&mut delay).unwrap();
+
+    loop {
+        led.set_low();
+
+        let devices: ArrayVec<Found, MAX_DEVICES> = scan(&mut i2c).collect();
+
+        print_serial(&mut serial, &devices).unwrap();
+
+        frame_buffer.clear_buffer(0x00);
+        print_devices(&mut frame_buffer, &devices).unwrap();
+        SpiDriver::new(&mut spi, &mut display_cs).send_buffer(&frame_buffer).unwrap();
+
+        led.set_high();
+
+        delay.delay_ms(2000_u16);
+    }
+}
+
+fn print_serial<W: Write>(serial: &mut W, devices: &[Found]) -> Result<(), ()> {
+    write!(serial, "I2C scan: {} device(s)\r\n", devices.len()).map_err(|_| ())?;
+
+    for found in devices {
+        // Devices without a chip ID are only guessed from the address
+        let mark = if found.identity.confirmed { "" } else { "?" };
+        write!(serial, "  0x{:02X} {}{}\r\n", found.address, found.identity.device.name(), mark)
+            .map_err(|_| ())?;
+    }
+
+    Ok(())
+}
+
+fn print_devices(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    devices: &[Found],
+) -> Result<(), ()> {
+    let mut text = ArrayString::<20>::new();
+    let regular = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let bold = MonoTextStyle::new(&FONT_7X13_BOLD, BinaryColor::On);
+
+    write!(&mut text, "I2C: {} found", devices.len()).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 11), bold).draw(frame_buffer).map_err(|_| ())?;
+
+    for (line, found) in devices.iter().take(DISPLAY_LINES).enumerate() {
+        let mark = if found.identity.confirmed { "" } else { "?" };
+        text.clear();
+
+        match line == DISPLAY_LINES - 1 && devices.len() > DISPLAY_LINES {
+            true => write!(&mut text, "+{} more", devices.len() + 1 - DISPLAY_LINES),
+            false => write!(&mut text, "{:02X} {}{}", found.address, found.identity.device.name(), mark),
+        }.map_err(|_| ())?;
+
+        Text::new(&text, Point::new(0, 23 + 10*line as i32), regular).draw(frame_buffer).map_err(|_| ())?;
+    }
+
+    Ok(())
+}
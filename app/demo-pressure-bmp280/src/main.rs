@@ -3,17 +3,26 @@
 
 use core::fmt::Write;
 use arrayvec::ArrayString;
-use bmp280_rs::{BMP280, I2CAddress, Config};
 use embedded_graphics::Drawable;
 use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::*;
-use embedded_graphics::mono_font::{ascii::FONT_7X13, ascii::FONT_7X13_BOLD, MonoTextStyle};
+use embedded_graphics::mono_font::{ascii::FONT_5X8, ascii::FONT_6X10, ascii::FONT_7X13, ascii::FONT_7X13_BOLD, MonoTextStyle};
+use embedded_graphics::primitives::{Circle, Line, PrimitiveStyle, Rectangle, Triangle};
 use embedded_graphics::text::Text;
+use embedded_hal::blocking::{i2c, spi};
+use embedded_hal::digital::v2::OutputPin;
 use embedded_hal::spi::{Mode as SpiMode, Phase, Polarity};
 use hx1230::{ArrayDisplayBuffer, DisplayBuffer, SpiDriver, DisplayDriver};
+use lib_barometer::{
+    sea_level_pressure, Altimeter, Barometer, Chip, Config, Forecast, I2cRegisters, Measurement,
+    PressureHistory, Registers, SpiRegisters, Weather, ADDRESS_SDO_GROUNDED, ADDRESS_SDO_HIGH,
+};
+use lib_i2c_bus::RecoveringI2c;
 use stm32f1xx_hal::i2c::{BlockingI2c, Mode, DutyCycle};
-use stm32f1xx_hal::{pac, prelude::*, spi::{NoMiso, Spi}};
+use stm32f1xx_hal::gpio::PinState;
+use stm32f1xx_hal::{pac, prelude::*, spi::Spi};
 
+use cortex_m::peripheral::DWT;
 use cortex_m_rt::entry;
 
 use lib_panic_led as _;
@@ -23,9 +32,36 @@ pub const SPI_MODE: SpiMode = SpiMode {
     polarity: Polarity::IdleLow,
 };
 
+/// Altitude of the place the demo runs at (250 m), used to compute the sea
+/// level pressure the weather reports show and the forecast is based on.
+/// Set it to your own altitude above the sea level, an error of 10 m shifts
+/// the sea level pressure by about 1.2 hPa.
+const ALTITUDE_CM: i32 = 25_000;
+
+/// Time between two measurements
+const MEASUREMENT_MS: u32 = 1000;
+
+/// Pressure is sampled every 10 minutes, 36 samples cover 6 hours
+const SAMPLE_INTERVAL_MS: u32 = 10*60*1000;
+const SAMPLES: usize = 36;
+
+/// Bus the sensor was found on at startup
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Connection {
+    I2c(u8),
+    Spi,
+}
+
+/// All places a sensor is looked for, in this order
+const CONNECTIONS: [Connection; 3] = [
+    Connection::I2c(ADDRESS_SDO_GROUNDED),
+    Connection::I2c(ADDRESS_SDO_HIGH),
+    Connection::Spi,
+];
+
 #[entry]
 fn main() -> ! {
-    let cp = cortex_m::Peripherals::take().unwrap();
+    let mut cp = cortex_m::Peripherals::take().unwrap();
     let dp = pac::Peripherals::take().unwrap();
     let mut afio = dp.AFIO.constrain();
     let mut flash = dp.FLASH.constrain();
@@ -40,19 +76,30 @@ fn main() -> ! {
         .hclk(8.MHz())     // clock used for timers
         .freeze(&mut flash.acr);
 
+    // I2C timeouts and the time between measurements are measured by
+    // the cycle counter
+    cp.DCB.enable_trace();
+    cp.DWT.enable_cycle_counter();
+
     let mut gpiob = dp.GPIOB.split();
     let mut gpioc = dp.GPIOC.split();
 
     let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
     let mut display_cs = gpiob.pb12.into_push_pull_output(&mut gpiob.crh);
 
-    // SPI2, we use only output, so there is no miso input
+    // Chip select of a sensor connected to SPI, it has to stay high while
+    // the display is drawn
+    let mut sensor_cs = gpiob.pb0.into_push_pull_output_with_state(&mut gpiob.crl, PinState::High);
+
+    // SPI2 is shared by the display and the sensor, only the sensor
+    // uses the miso input
     let sck = gpiob.pb13.into_alternate_push_pull(&mut gpiob.crh);
+    let miso = gpiob.pb14.into_floating_input(&mut gpiob.crh);
     let mosi = gpiob.pb15.into_alternate_push_pull(&mut gpiob.crh);
 
     let mut spi = Spi::spi2(
         dp.SPI2,
-        (sck, NoMiso, mosi),
+        (sck, miso, mosi),
         SPI_MODE,
         4.MHz(),
         clocks,
@@ -61,7 +108,7 @@ fn main() -> ! {
     let scl = gpiob.pb6.into_alternate_open_drain(&mut gpiob.crl);
     let sda = gpiob.pb7.into_alternate_open_drain(&mut gpiob.crl);
 
-    let mut i2c = BlockingI2c::i2c1(
+    let i2c = BlockingI2c::i2c1(
         dp.I2C1,
         (scl, sda),
         &mut afio.mapr,
@@ -76,6 +123,10 @@ fn main() -> ! {
         1000,
     );
 
+    // A sensor holding SDA low after a glitch would make every following
+    // transaction time out, the bus is recovered and the transaction retried
+    let mut i2c = RecoveringI2c::new(i2c, clocks);
+
     let mut delay = cp.SYST.delay(&clocks);
 
     let mut frame_buffer: ArrayDisplayBuffer = ArrayDisplayBuffer::new();
@@ -85,56 +136,228 @@ fn main() -> ! {
     display.send_buffer(&frame_buffer).unwrap();
     delay.delay_ms(200_u16);
 
+    // The first sensor answering with a known chip ID is used
     let config = Config::handheld_device_dynamic();
-    let mut bmp = BMP280::new(&mut i2c, I2CAddress::SdoGrounded, config).unwrap();
+    let (connection, barometer) = CONNECTIONS.iter()
+        .find_map(|&connection| {
+            with_registers(connection, &mut i2c, &mut spi, &mut sensor_cs, |registers| {
+                Barometer::new(registers, config).ok().map(|barometer| (connection, barometer))
+            })
+        })
+        .unwrap();
+
+    frame_buffer.clear_buffer(0x00);
+    print_sensor(&mut frame_buffer, barometer.chip(), connection).unwrap();
+    SpiDriver::new(&mut spi, &mut display_cs).send_buffer(&frame_buffer).unwrap();
+    delay.delay_ms(1000_u16);
+
+    let mut history: PressureHistory<SAMPLES> = PressureHistory::new(SAMPLE_INTERVAL_MS);
+
+    // Standard atmosphere reference, altitude drifts with the weather
+    let altimeter = Altimeter::default();
+
+    // Reading the sensor, drawing and recovering the bus add to the delay,
+    // so the history gets the time actually elapsed since the last sample,
+    // the counter wraps after 477 s at 9 MHz, far more than a loop takes
+    let cycles_per_ms = clocks.hclk().raw()/1000;
+    let mut last_sample = DWT::cycle_count();
 
     loop {
         led.set_low();
         frame_buffer.clear_buffer(0x00);
 
-        bmp.trigger_measurement(&mut i2c).unwrap();
-        let pressure = bmp.read_pressure(&mut i2c).unwrap();
-        let temperature = bmp.read_temperature(&mut i2c).unwrap();
+        let measurement = with_registers(connection, &mut i2c, &mut spi, &mut sensor_cs, |registers| {
+            barometer.trigger(registers)?;
+            delay.delay_us(barometer.measurement_time_us());
+            barometer.read(registers)
+        });
 
-        print_measurement(&mut frame_buffer, pressure, temperature).unwrap();
+        // A failed measurement is skipped, the next one may succeed
+        match measurement {
+            Ok(measurement) => {
+                let elapsed_ms = DWT::cycle_count().wrapping_sub(last_sample)/cycles_per_ms;
+                last_sample = last_sample.wrapping_add(elapsed_ms*cycles_per_ms);
 
-        display.send_buffer(&frame_buffer).unwrap();
+                history.update(elapsed_ms, measurement.pressure);
+                print_measurement(&mut frame_buffer, &measurement, &history, &altimeter)
+            }
+            Err(_) => print_text(&mut frame_buffer, "Sensor error"),
+        }.unwrap();
+
+        SpiDriver::new(&mut spi, &mut display_cs).send_buffer(&frame_buffer).unwrap();
 
         led.set_high();
 
-        delay.delay_ms(500_u16);
+        delay.delay_us(MEASUREMENT_MS*1000 - barometer.measurement_time_us());
     }
 }
 
-const TEMPERATURE: &str = "Temperature:";
-const PRESSURE: &str = "Pressure:";
+/// Run `action` with registers of the sensor on the given bus
+fn with_registers<I2C, SPI, CS, T, F>(
+    connection: Connection,
+    i2c: &mut I2C,
+    spi: &mut SPI,
+    cs: &mut CS,
+    action: F,
+) -> T
+where
+    I2C: i2c::WriteRead + i2c::Write,
+    SPI: spi::Transfer<u8> + spi::Write<u8>,
+    CS: OutputPin,
+    F: FnOnce(&mut dyn Registers) -> T,
+{
+    match connection {
+        Connection::I2c(address) => action(&mut I2cRegisters::new(i2c, address)),
+        Connection::Spi => action(&mut SpiRegisters::new(spi, cs)),
+    }
+}
 
 fn print_text(
     frame_buffer: &mut ArrayDisplayBuffer,
     message: &str,
 ) -> Result<(), ()> {
     let regular = MonoTextStyle::new(&FONT_7X13, BinaryColor::On);
-    Text::new(&message, Point::new(0, 20), regular).draw(frame_buffer).map_err(|_| ())?;
+    Text::new(message, Point::new(0, 20), regular).draw(frame_buffer).map_err(|_| ())?;
     Ok(())
 }
 
-fn print_measurement(
+fn print_sensor(
     frame_buffer: &mut ArrayDisplayBuffer,
-    raw_pressure: i32,
-    raw_temp: i32,
+    chip: Chip,
+    connection: Connection,
 ) -> Result<(), ()> {
     let mut text = ArrayString::<20>::new();
     let regular = MonoTextStyle::new(&FONT_7X13, BinaryColor::On);
     let bold = MonoTextStyle::new(&FONT_7X13_BOLD, BinaryColor::On);
-    let pressure = raw_pressure/256;
 
-    Text::new(&TEMPERATURE, Point::new(0, 15), regular).draw(frame_buffer).map_err(|_| ())?;
-    write!(&mut text, "{}.{:02} C", raw_temp/100, raw_temp % 100).map_err(|_| ())?;
-    Text::new(&text, Point::new(0, 30), bold).draw(frame_buffer).map_err(|_| ())?;
-    Text::new(&PRESSURE, Point::new(0, 45), regular).draw(frame_buffer).map_err(|_| ())?;
+    let name = match chip {
+        Chip::Bmp280 => "BMP280",
+        Chip::Bme280 => "BME280",
+    };
+
+    Text::new(name, Point::new(0, 20), bold).draw(frame_buffer).map_err(|_| ())?;
+
+    match connection {
+        Connection::I2c(address) => write!(&mut text, "I2C 0x{:02X}", address).map_err(|_| ())?,
+        Connection::Spi => write!(&mut text, "SPI").map_err(|_| ())?,
+    }
+
+    Text::new(&text, Point::new(0, 35), regular).draw(frame_buffer).map_err(|_| ())?;
+    Ok(())
+}
+
+fn print_measurement(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    measurement: &Measurement,
+    history: &PressureHistory<SAMPLES>,
+    altimeter: &Altimeter,
+) -> Result<(), ()> {
+    let mut text = ArrayString::<20>::new();
+    let small = MonoTextStyle::new(&FONT_5X8, BinaryColor::On);
+    let regular = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let bold = MonoTextStyle::new(&FONT_7X13_BOLD, BinaryColor::On);
+    let sea_level = sea_level_pressure(measurement.pressure, ALTITUDE_CM);
+
+    match measurement.humidity {
+        Some(humidity) => write!(&mut text, "{}C {}%", measurement.temperature, humidity.percent()),
+        None => write!(&mut text, "{} C", measurement.temperature),
+    }.map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 11), bold).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+    write!(&mut text, "{} hPa", sea_level).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 25), bold).draw(frame_buffer).map_err(|_| ())?;
     text.clear();
-    write!(&mut text, "{}.{:02} hPa", pressure/100, pressure % 100).map_err(|_| ())?;
-    Text::new(&text, Point::new(0, 60), bold).draw(frame_buffer).map_err(|_| ())?;
+
+    // Altitude is aligned to the right end of the tendency line
+    write!(&mut text, "{}m", altimeter.altitude_m(measurement.pressure)).map_err(|_| ())?;
+    let x = 96 - 6*text.len() as i32;
+    Text::new(&text, Point::new(x, 38), regular).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+
+    // Tendency needs at least an hour of history
+    let tendency = match history.tendency() {
+        Some(tendency) => tendency,
+        None => {
+            Text::new("Trend in 1h", Point::new(0, 38), regular).draw(frame_buffer).map_err(|_| ())?;
+            return Ok(());
+        }
+    };
+
+    write!(&mut text, "{} hPa/3h", tendency).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 38), regular).draw(frame_buffer).map_err(|_| ())?;
+
+    let forecast = Forecast::zambretti(sea_level, tendency.trend());
+    draw_icon(frame_buffer, forecast.weather(), Point::new(78, 0))?;
+
+    // Forecast text is wrapped at spaces into lines of 19 characters
+    let mut line = ArrayString::<20>::new();
+    let mut y = 48;
+
+    for word in forecast.text().split(' ') {
+        if !line.is_empty() && line.len() + 1 + word.len() > 19 {
+            Text::new(&line, Point::new(0, y), small).draw(frame_buffer).map_err(|_| ())?;
+            line.clear();
+            y += 9;
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+
+        line.try_push_str(word).map_err(|_| ())?;
+    }
+
+    Text::new(&line, Point::new(0, y), small).draw(frame_buffer).map_err(|_| ())?;
+
+    Ok(())
+}
+
+/// Draw a 16x16 weather icon with the top left corner at `origin`
+fn draw_icon(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    weather: Weather,
+    origin: Point,
+) -> Result<(), ()> {
+    let fill = PrimitiveStyle::with_fill(BinaryColor::On);
+    let stroke = PrimitiveStyle::with_stroke(BinaryColor::On, 1);
+
+    if weather == Weather::Fine || weather == Weather::Fair {
+        // Sun with four rays, partly covered by a cloud when fair
+        Circle::new(origin + Point::new(4, 4), 8).into_styled(fill).draw(frame_buffer).map_err(|_| ())?;
+        Line::new(origin + Point::new(8, 0), origin + Point::new(8, 2)).into_styled(stroke).draw(frame_buffer).map_err(|_| ())?;
+        Line::new(origin + Point::new(8, 13), origin + Point::new(8, 15)).into_styled(stroke).draw(frame_buffer).map_err(|_| ())?;
+        Line::new(origin + Point::new(0, 8), origin + Point::new(2, 8)).into_styled(stroke).draw(frame_buffer).map_err(|_| ())?;
+        Line::new(origin + Point::new(13, 8), origin + Point::new(15, 8)).into_styled(stroke).draw(frame_buffer).map_err(|_| ())?;
+
+        if weather == Weather::Fine {
+            return Ok(());
+        }
+    }
+
+    // Cloud in the top part, the sun stays visible above it when fair
+    let top = if weather == Weather::Fair { 8 } else { 1 };
+    Circle::new(origin + Point::new(1, top + 2), 6).into_styled(fill).draw(frame_buffer).map_err(|_| ())?;
+    Circle::new(origin + Point::new(5, top), 8).into_styled(fill).draw(frame_buffer).map_err(|_| ())?;
+    Rectangle::new(origin + Point::new(3, top + 4), Size::new(12, 4)).into_styled(fill).draw(frame_buffer).map_err(|_| ())?;
+
+    match weather {
+        Weather::Showers => {
+            Line::new(origin + Point::new(5, 11), origin + Point::new(4, 13)).into_styled(stroke).draw(frame_buffer).map_err(|_| ())?;
+            Line::new(origin + Point::new(11, 11), origin + Point::new(10, 13)).into_styled(stroke).draw(frame_buffer).map_err(|_| ())?;
+        }
+        Weather::Rain => {
+            for x in [3, 7, 11] {
+                Line::new(origin + Point::new(x + 1, 11), origin + Point::new(x - 1, 15)).into_styled(stroke).draw(frame_buffer).map_err(|_| ())?;
+            }
+        }
+        Weather::Storm => {
+            Triangle::new(origin + Point::new(9, 10), origin + Point::new(5, 13), origin + Point::new(8, 13))
+                .into_styled(fill).draw(frame_buffer).map_err(|_| ())?;
+            Triangle::new(origin + Point::new(8, 13), origin + Point::new(6, 16), origin + Point::new(10, 12))
+                .into_styled(fill).draw(frame_buffer).map_err(|_| ())?;
+        }
+        _ => {}
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
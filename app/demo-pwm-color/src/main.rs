@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+use cortex_m_rt::entry;
+use embedded_hal::PwmPin;
+use lib_fixed_math::Curve;
+use lib_rgb_led::{Calibration, Fade, Hsv, Rgbw, RgbwLed};
+use stm32f1xx_hal::timer::Tim2NoRemap;
+use stm32f1xx_hal::{pac, prelude::*};
+use lib_panic_led as _;
+
+/// Time between two colour updates in milliseconds
+const FRAME_MS: u32 = 10;
+
+/// Green and blue LEDs are more efficient than the red one, the white
+/// LED is a cold white of about 6500 K
+const CALIBRATION: Calibration = Calibration::new(1000, 450, 600, 800, 6500);
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr.use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    let mut gpioa = dp.GPIOA.split();
+
+    // Red, green, blue and white channels of the LED
+    let p1 = gpioa.pa0.into_alternate_push_pull(&mut gpioa.crl);
+    let p2 = gpioa.pa1.into_alternate_push_pull(&mut gpioa.crl);
+    let p3 = gpioa.pa2.into_alternate_push_pull(&mut gpioa.crl);
+    let p4 = gpioa.pa3.into_alternate_push_pull(&mut gpioa.crl);
+
+    let mut afio = dp.AFIO.constrain();
+    let pins = (p1, p2, p3, p4);
+
+    let (mut r, mut g, mut b, mut w) = dp
+        .TIM2
+        .pwm_hz::<Tim2NoRemap, _, _>(pins, &mut afio.mapr, 500.Hz(), &clocks)
+        .split();
+
+    // Enable clock on each of the channels
+    r.enable();
+    g.enable();
+    b.enable();
+    w.enable();
+
+    let mut led = RgbwLed::new(r, g, b, w, CALIBRATION, Curve::Cie1931);
+    let mut delay = cp.SYST.delay(&clocks);
+
+    loop {
+        // Walk around the colour wheel
+        for hue in 0..360 {
+            led.set_hsv(Hsv::new(hue, 255, 255));
+            delay.delay_ms(3*FRAME_MS);
+        }
+
+        // Cross-fade to warm white, then to cold white and back to red
+        let warm = led.white(2700, 255);
+        let cold = led.white(6500, 255);
+
+        fade(&mut led, &mut delay, warm, 2000);
+        fade(&mut led, &mut delay, cold, 2000);
+        fade(&mut led, &mut delay, Rgbw::new(255, 0, 0, 0), 2000);
+    }
+}
+
+fn fade<R, G, B, W, D>(led: &mut RgbwLed<R, G, B, W>, delay: &mut D, to: Rgbw, duration_ms: u32)
+where
+    R: PwmPin<Duty = u16>,
+    G: PwmPin<Duty = u16>,
+    B: PwmPin<Duty = u16>,
+    W: PwmPin<Duty = u16>,
+    D: embedded_hal::blocking::delay::DelayMs<u32>,
+{
+    let mut fade = Fade::new(led.color(), to, duration_ms);
+
+    while !fade.is_done() {
+        led.set(fade.update(FRAME_MS));
+        delay.delay_ms(FRAME_MS);
+    }
+}
@@ -1,14 +1,65 @@
 #![no_std]
 #![no_main]
 
-use core::cmp;
-use core::f32::consts::FRAC_PI_2;
+use core::cell::RefCell;
 
+use cortex_m::interrupt::Mutex;
 use cortex_m_rt::entry;
-use stm32f1xx_hal::timer::{Tim2NoRemap, Channel};
+use lib_fixed_math::{Brightness, Curve};
+use lib_led_animation::{Animator, ChannelAnimation, Easing, Keyframe, Sequence, MAX_LEVEL};
+use stm32f1xx_hal::pac::{interrupt, Interrupt, TIM2, TIM3};
+use stm32f1xx_hal::timer::{Tim2NoRemap, Channel, CounterHz, Event, PwmChannel, C1, C2, C3, C4};
 use stm32f1xx_hal::{pac, prelude::*};
 use lib_panic_led as _;
-use micromath::F32Ext;
+
+/// Frequency of animation updates
+const FRAME_RATE: u32 = 100;
+
+/// Each LED fades in and out during the first half of the period and stays
+/// dark during the second half
+static PULSE: [Keyframe; 2] = [
+    Keyframe::new(MAX_LEVEL, 2000, Easing::Breathing),
+    Keyframe::new(0, 2000, Easing::Linear),
+];
+
+/// Shift between neighbouring LEDs, a quarter of the period
+const SHIFT_MS: u32 = 1000;
+
+type Channels = (
+    PwmChannel<TIM2, C1>,
+    PwmChannel<TIM2, C2>,
+    PwmChannel<TIM2, C3>,
+    PwmChannel<TIM2, C4>,
+);
+
+struct Leds {
+    timer: CounterHz<TIM3>,
+    channels: Channels,
+    animator: Animator<'static, 4>,
+}
+
+impl Leds {
+    fn update(&mut self) {
+        self.timer.clear_interrupt(Event::Update);
+        let duties = self.animator.tick();
+        self.channels.0.set_duty(duties[0]);
+        self.channels.1.set_duty(duties[1]);
+        self.channels.2.set_duty(duties[2]);
+        self.channels.3.set_duty(duties[3]);
+    }
+}
+
+// Animation state is moved to the interrupt handler once initialized
+static G_LEDS: Mutex<RefCell<Option<Leds>>> = Mutex::new(RefCell::new(None));
+
+#[interrupt]
+fn TIM3() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(leds) = G_LEDS.borrow(cs).borrow_mut().as_mut() {
+            leds.update();
+        }
+    });
+}
 
 #[entry]
 fn main() -> ! {
@@ -45,37 +96,39 @@ fn main() -> ! {
 
     pwm.set_period(500.Hz());
 
-    let mut phase: f32 = 0.0;
+    // Yellow and blue LEDs are much brighter than the others
+    let max_duty = pwm.get_max_duty();
+    let g = Brightness::new(Curve::Gamma22, max_duty);
+    let y = Brightness::new(Curve::Gamma22, max_duty/6);
+    let r = Brightness::new(Curve::Gamma22, max_duty);
+    let b = Brightness::new(Curve::Gamma22, max_duty/8);
 
-    let max_duty = pwm.get_max_duty() as f32;
-    let max_g = max_duty;
-    let max_y = max_duty/6.0;
-    let max_r = max_duty;
-    let max_b = max_duty/8.0;
+    let pulse = Sequence::new(&PULSE, true);
 
-    loop {
-        pwm.set_duty(Channel::C1, compute_duty(phase, C1_SHIFT, max_g));
-        pwm.set_duty(Channel::C2, compute_duty(phase, C2_SHIFT, max_y));
-        pwm.set_duty(Channel::C3, compute_duty(phase, C3_SHIFT, max_r));
-        pwm.set_duty(Channel::C4, compute_duty(phase, C4_SHIFT, max_b));
+    let animator = Animator::new(
+        [
+            ChannelAnimation::new(pulse, 0, g),
+            ChannelAnimation::new(pulse, SHIFT_MS, y),
+            ChannelAnimation::new(pulse, 2*SHIFT_MS, r),
+            ChannelAnimation::new(pulse, 3*SHIFT_MS, b),
+        ],
+        FRAME_RATE,
+    );
 
-        phase += 0.001;
-    }
-}
+    // Animation frames are timed by TIM3, independently of the CPU speed
+    let mut timer = dp.TIM3.counter_hz(&clocks);
+    timer.start(FRAME_RATE.Hz()).unwrap();
+    timer.listen(Event::Update);
 
-fn maxf(a: f32, b: f32) -> f32 {
-    if a > b { a } else { b }
-}
+    let leds = Leds { timer, channels: pwm.split(), animator };
 
-fn compute_duty(phase: f32, shift: f32, max: f32) -> u16 {
-    cmp::max(1, (sqr(maxf(0.0, (phase + shift).sin()))*max) as u16)
-}
+    cortex_m::interrupt::free(|cs| G_LEDS.borrow(cs).replace(Some(leds)));
 
-fn sqr(value: f32) -> f32 {
-    value*value
-}
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(Interrupt::TIM3);
+    }
 
-const C1_SHIFT: f32 = FRAC_PI_2*0.0;
-const C2_SHIFT: f32 = FRAC_PI_2*1.0;
-const C3_SHIFT: f32 = FRAC_PI_2*2.0;
-const C4_SHIFT: f32 = FRAC_PI_2*3.0;
\ No newline at end of file
+    loop {
+        cortex_m::asm::wfi();
+    }
+}
@@ -0,0 +1,223 @@
+#![no_std]
+#![no_main]
+
+use core::fmt::Write;
+use arrayvec::ArrayString;
+use embedded_graphics::Drawable;
+use embedded_graphics::pixelcolor::BinaryColor;
+use embedded_graphics::prelude::*;
+use embedded_graphics::mono_font::{ascii::FONT_6X10, ascii::FONT_7X13, ascii::FONT_7X13_BOLD, MonoTextStyle};
+use embedded_graphics::text::Text;
+use embedded_hal::spi::{Mode as SpiMode, Phase, Polarity};
+use hx1230::{ArrayDisplayBuffer, DisplayBuffer, SpiDriver, DisplayDriver};
+use lib_barometer::{Barometer, Config, I2cRegisters, Measurement, ADDRESS_SDO_GROUNDED, ADDRESS_SDO_HIGH};
+use lib_i2c_bus::{BusMutex, RecoveringI2c, SharedBus};
+use lib_i2c_scanner::{find, Device};
+use mpu6050::Mpu6050;
+use stm32f1xx_hal::i2c::{BlockingI2c, Mode, DutyCycle};
+use stm32f1xx_hal::{pac, prelude::*, spi::{NoMiso, Spi}};
+
+use cortex_m_rt::entry;
+
+use lib_panic_led as _;
+
+pub const SPI_MODE: SpiMode = SpiMode {
+    phase: Phase::CaptureOnFirstTransition,
+    polarity: Polarity::IdleLow,
+};
+
+/// Time between two measurements
+const MEASUREMENT_MS: u32 = 500;
+
+/// Tilt of the accelerometer in degrees
+struct Tilt {
+    roll: f32,
+    pitch: f32,
+}
+
+#[entry]
+fn main() -> ! {
+    let mut cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+    let mut afio = dp.AFIO.constrain();
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    // Freeze the configuration of all the clocks in the system and store the frozen frequencies in
+    // `clocks`
+    let clocks = rcc
+        .cfgr
+        .use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    // I2C timeouts are measured by the cycle counter
+    cp.DCB.enable_trace();
+    cp.DWT.enable_cycle_counter();
+
+    let mut gpiob = dp.GPIOB.split();
+    let mut gpioc = dp.GPIOC.split();
+
+    let mut led = gpioc.pc13.into_push_pull_output(&mut gpioc.crh);
+    let mut display_cs = gpiob.pb12.into_push_pull_output(&mut gpiob.crh);
+
+    // SPI2, we use only output, so there is no miso input
+    let sck = gpiob.pb13.into_alternate_push_pull(&mut gpiob.crh);
+    let mosi = gpiob.pb15.into_alternate_push_pull(&mut gpiob.crh);
+
+    let mut spi = Spi::spi2(
+        dp.SPI2,
+        (sck, NoMiso, mosi),
+        SPI_MODE,
+        4.MHz(),
+        clocks,
+    );
+
+    // Both sensors are connected to I2C1
+    let scl = gpiob.pb6.into_alternate_open_drain(&mut gpiob.crl);
+    let sda = gpiob.pb7.into_alternate_open_drain(&mut gpiob.crl);
+
+    let i2c = BlockingI2c::i2c1(
+        dp.I2C1,
+        (scl, sda),
+        &mut afio.mapr,
+        Mode::Fast {
+            frequency: 100.kHz(),
+            duty_cycle: DutyCycle::Ratio2to1,
+        },
+        clocks,
+        1000,
+        10,
+        1000,
+        1000,
+    );
+
+    // Barometer borrows its proxy for every call, MPU6050 driver keeps
+    // its own one
+    let bus = SharedBus::new(RecoveringI2c::new(i2c, clocks));
+    let mut barometer_i2c = bus.acquire();
+
+    let mut delay = cp.SYST.delay(&clocks);
+
+    let mut frame_buffer: ArrayDisplayBuffer = ArrayDisplayBuffer::new();
+    SpiDriver::new(&mut spi, &mut display_cs).initialize(&mut delay).unwrap();
+    print_text(&mut frame_buffer, "Starting up...").unwrap();
+    SpiDriver::new(&mut spi, &mut display_cs).send_buffer(&frame_buffer).unwrap();
+
+    let config = Config::weather_monitoring();
+    let barometer = [ADDRESS_SDO_GROUNDED, ADDRESS_SDO_HIGH].into_iter().find_map(|address| {
+        Barometer::new(&mut I2cRegisters::new(&mut barometer_i2c, address), config)
+            .ok()
+            .map(|barometer| (address, barometer))
+    });
+
+    let mpu_address = find(&mut bus.acquire(), &[Device::Mpu6050]).map(|found| found.address);
+
+    let ((barometer_address, barometer), mpu_address) = match (barometer, mpu_address) {
+        (Some(barometer), Some(mpu_address)) => (barometer, mpu_address),
+        (None, _) => fail(&mut frame_buffer, &mut spi, &mut display_cs, "No BMP280"),
+        (_, None) => fail(&mut frame_buffer, &mut spi, &mut display_cs, "No MPU6050"),
+    };
+
+    let mut mpu = Mpu6050::new_with_addr(bus.acquire(), mpu_address);
+    if mpu.init(&mut delay).is_err() {
+        fail(&mut frame_buffer, &mut spi, &mut display_cs, "MPU6050 error");
+    }
+
+    loop {
+        led.set_low();
+
+        let mut registers = I2cRegisters::new(&mut barometer_i2c, barometer_address);
+        let measurement = barometer.trigger(&mut registers)
+            .map(|_| delay.delay_us(barometer.measurement_time_us()))
+            .and_then(|_| barometer.read(&mut registers))
+            .ok();
+
+        let tilt = mpu.get_acc_angles().ok().map(|angles| Tilt {
+            roll: angles[0].to_degrees(),
+            pitch: angles[1].to_degrees(),
+        });
+
+        let recoveries = bus.lock(|i2c| i2c.recoveries());
+
+        frame_buffer.clear_buffer(0x00);
+        print_station(&mut frame_buffer, measurement.as_ref(), tilt.as_ref(), recoveries).unwrap();
+        SpiDriver::new(&mut spi, &mut display_cs).send_buffer(&frame_buffer).unwrap();
+
+        led.set_high();
+
+        delay.delay_ms(MEASUREMENT_MS);
+    }
+}
+
+/// Show the error and stop
+fn fail<SPI, CS>(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    spi: &mut SPI,
+    cs: &mut CS,
+    message: &str,
+) -> !
+where
+    SPI: embedded_hal::blocking::spi::Write<u8>,
+    CS: embedded_hal::digital::v2::OutputPin,
+{
+    frame_buffer.clear_buffer(0x00);
+    print_text(frame_buffer, message).unwrap();
+    SpiDriver::new(spi, cs).send_buffer(frame_buffer).unwrap();
+    panic!("sensor not found");
+}
+
+fn print_text(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    message: &str,
+) -> Result<(), ()> {
+    let regular = MonoTextStyle::new(&FONT_7X13, BinaryColor::On);
+    Text::new(message, Point::new(0, 20), regular).draw(frame_buffer).map_err(|_| ())?;
+    Ok(())
+}
+
+fn print_station(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    measurement: Option<&Measurement>,
+    tilt: Option<&Tilt>,
+    recoveries: u32,
+) -> Result<(), ()> {
+    let mut text = ArrayString::<20>::new();
+    let regular = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let bold = MonoTextStyle::new(&FONT_7X13_BOLD, BinaryColor::On);
+
+    // A failed reading is shown as dashes, the next one may succeed
+    match measurement {
+        Some(measurement) => write!(&mut text, "{} C", measurement.temperature),
+        None => write!(&mut text, "-- C"),
+    }.map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 11), bold).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+
+    match measurement {
+        Some(measurement) => write!(&mut text, "{} hPa", measurement.pressure),
+        None => write!(&mut text, "-- hPa"),
+    }.map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 25), bold).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+
+    match tilt {
+        Some(tilt) => write!(&mut text, "Roll  {:6.1}", tilt.roll),
+        None => write!(&mut text, "Roll      --"),
+    }.map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 38), regular).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+
+    match tilt {
+        Some(tilt) => write!(&mut text, "Pitch {:6.1}", tilt.pitch),
+        None => write!(&mut text, "Pitch     --"),
+    }.map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 49), regular).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+
+    write!(&mut text, "Recovered {}", recoveries).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 60), regular).draw(frame_buffer).map_err(|_| ())?;
+
+    Ok(())
+}
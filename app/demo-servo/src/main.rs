@@ -0,0 +1,99 @@
+#![no_std]
+#![no_main]
+
+use cortex_m_rt::entry;
+use lib_servo::{duty, Calibration, Servo, ServoBank, PERIOD_US};
+use stm32f1xx_hal::timer::Tim2NoRemap;
+use stm32f1xx_hal::{pac, prelude::*};
+use lib_panic_led as _;
+
+/// Time between two servo updates, one servo frame
+const FRAME_MS: u32 = 20;
+
+/// Speed of slow moves in degrees per second
+const SLOW: u16 = 45;
+
+/// Speed of fast moves in degrees per second
+const FAST: u16 = 180;
+
+/// Positions visited by all four servos in turn
+const POSES: [[u16; 4]; 4] = [
+    [0, 45, 90, 135],
+    [180, 135, 90, 45],
+    [90, 90, 90, 90],
+    [45, 0, 180, 135],
+];
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr.use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    let mut gpioa = dp.GPIOA.split();
+
+    // Signal wires of the four servos
+    let p1 = gpioa.pa0.into_alternate_push_pull(&mut gpioa.crl);
+    let p2 = gpioa.pa1.into_alternate_push_pull(&mut gpioa.crl);
+    let p3 = gpioa.pa2.into_alternate_push_pull(&mut gpioa.crl);
+    let p4 = gpioa.pa3.into_alternate_push_pull(&mut gpioa.crl);
+
+    let mut afio = dp.AFIO.constrain();
+    let pins = (p1, p2, p3, p4);
+
+    // Timer counts microseconds, 20 ms period gives 50 Hz servo frames
+    let (mut c1, mut c2, mut c3, mut c4) = dp
+        .TIM2
+        .pwm_us::<Tim2NoRemap, _, _>(pins, &mut afio.mapr, 20.millis(), &clocks)
+        .split();
+
+    c1.enable();
+    c2.enable();
+    c3.enable();
+    c4.enable();
+
+    let max_duty = c1.get_max_duty();
+
+    // SG90 servos usually need wider pulses to reach full 180 degrees
+    let sg90 = Calibration::new(500, 2400, 180);
+
+    let mut servos = ServoBank::new([
+        Servo::new(Calibration::default(), 90),
+        Servo::new(Calibration::default(), 90),
+        Servo::new(sg90, 90),
+        Servo::new(sg90, 90),
+    ]);
+
+    let mut delay = cp.SYST.delay(&clocks);
+    let mut speed = SLOW;
+
+    loop {
+        for pose in POSES.iter() {
+            servos.move_to(*pose, speed);
+
+            while servos.is_moving() {
+                let pulses = servos.update(FRAME_MS);
+                c1.set_duty(duty(pulses[0], max_duty, PERIOD_US));
+                c2.set_duty(duty(pulses[1], max_duty, PERIOD_US));
+                c3.set_duty(duty(pulses[2], max_duty, PERIOD_US));
+                c4.set_duty(duty(pulses[3], max_duty, PERIOD_US));
+                delay.delay_ms(FRAME_MS);
+            }
+
+            delay.delay_ms(500u32);
+        }
+
+        speed = match speed {
+            SLOW => FAST,
+            _ => SLOW,
+        };
+    }
+}
@@ -0,0 +1,155 @@
+#![no_std]
+#![no_main]
+
+use core::cell::RefCell;
+
+use cortex_m::interrupt::Mutex;
+use cortex_m_rt::entry;
+use lib_stepper::{Config, HalfStep, StepDir, Stepper};
+use stm32f1xx_hal::gpio::{gpioa, gpiob, Output, PushPull};
+use stm32f1xx_hal::pac::{interrupt, Interrupt, TIM3};
+use stm32f1xx_hal::timer::{CounterHz, Event};
+use stm32f1xx_hal::{pac, prelude::*};
+use lib_panic_led as _;
+
+/// Frequency of stepper ticks, the fastest step rate is half of it
+const TICK_RATE: u32 = 10_000;
+
+/// 200 full steps per turn with 1/16 microstepping
+const DRIVER_TURN: i32 = 200*16;
+
+/// Half steps per turn of the 28BYJ-48 output shaft
+const UNIPOLAR_TURN: i32 = 4096;
+
+type Driver = StepDir<gpiob::PB10<Output<PushPull>>, gpiob::PB11<Output<PushPull>>>;
+
+type Unipolar = HalfStep<
+    gpioa::PA4<Output<PushPull>>,
+    gpioa::PA5<Output<PushPull>>,
+    gpioa::PA6<Output<PushPull>>,
+    gpioa::PA7<Output<PushPull>>,
+>;
+
+struct Motors {
+    timer: CounterHz<TIM3>,
+    driver: Stepper<Driver>,
+    unipolar: Stepper<Unipolar>,
+}
+
+impl Motors {
+    fn tick(&mut self) {
+        self.timer.clear_interrupt(Event::Update);
+        // GPIO pins of this board never fail
+        let _ = self.driver.tick();
+        let _ = self.unipolar.tick();
+    }
+}
+
+// Steppers are moved to the interrupt handler once initialized
+static G_MOTORS: Mutex<RefCell<Option<Motors>>> = Mutex::new(RefCell::new(None));
+
+#[interrupt]
+fn TIM3() {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(motors) = G_MOTORS.borrow(cs).borrow_mut().as_mut() {
+            motors.tick();
+        }
+    });
+}
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr.use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(8.MHz())     // clock used for timers
+        .freeze(&mut flash.acr);
+
+    let mut gpioa = dp.GPIOA.split();
+    let mut gpiob = dp.GPIOB.split();
+
+    // STEP and DIR inputs of an A4988 or DRV8825 driver
+    let step = gpiob.pb10.into_push_pull_output(&mut gpiob.crh);
+    let dir = gpiob.pb11.into_push_pull_output(&mut gpiob.crh);
+
+    // IN1 - IN4 inputs of the ULN2003 board
+    let in1 = gpioa.pa4.into_push_pull_output(&mut gpioa.crl);
+    let in2 = gpioa.pa5.into_push_pull_output(&mut gpioa.crl);
+    let in3 = gpioa.pa6.into_push_pull_output(&mut gpioa.crl);
+    let in4 = gpioa.pa7.into_push_pull_output(&mut gpioa.crl);
+
+    let driver = Stepper::new(
+        StepDir::new(step, dir).unwrap(),
+        Config { max_speed: 4000, acceleration: 8000, start_speed: 200 },
+        TICK_RATE,
+    );
+
+    // 28BYJ-48 loses steps above about 800 half steps per second
+    let unipolar = Stepper::new(
+        HalfStep::new(in1, in2, in3, in4).unwrap(),
+        Config { max_speed: 800, acceleration: 1000, start_speed: 200 },
+        TICK_RATE,
+    );
+
+    // Steps are timed by TIM3, the main loop only plans the moves
+    let mut timer = dp.TIM3.counter_hz(&clocks);
+    timer.start(TICK_RATE.Hz()).unwrap();
+    timer.listen(Event::Update);
+
+    let motors = Motors { timer, driver, unipolar };
+
+    cortex_m::interrupt::free(|cs| G_MOTORS.borrow(cs).replace(Some(motors)));
+
+    unsafe {
+        cortex_m::peripheral::NVIC::unmask(Interrupt::TIM3);
+    }
+
+    let mut delay = cp.SYST.delay(&clocks);
+
+    loop {
+        // Two turns forward and a half turn back, both motors at once
+        with_motors(|motors| {
+            motors.driver.move_by(2*DRIVER_TURN);
+            motors.unipolar.move_to(UNIPOLAR_TURN/2);
+        });
+
+        wait_for_motors(&mut delay);
+
+        with_motors(|motors| {
+            motors.driver.move_by(-DRIVER_TURN/2);
+            motors.unipolar.move_to(0);
+        });
+
+        wait_for_motors(&mut delay);
+
+        // Unipolar motor does not need to hold the position
+        with_motors(|motors| motors.unipolar.output_mut().power_off().unwrap());
+        delay.delay_ms(1000u32);
+    }
+}
+
+fn with_motors<F: FnOnce(&mut Motors)>(action: F) {
+    cortex_m::interrupt::free(|cs| {
+        if let Some(motors) = G_MOTORS.borrow(cs).borrow_mut().as_mut() {
+            action(motors);
+        }
+    });
+}
+
+fn wait_for_motors<D: embedded_hal::blocking::delay::DelayMs<u32>>(delay: &mut D) {
+    let mut moving = true;
+
+    while moving {
+        delay.delay_ms(10);
+
+        with_motors(|motors| {
+            moving = motors.driver.is_moving() || motors.unipolar.is_moving();
+        });
+    }
+}
@@ -3,14 +3,17 @@
 
 use core::fmt::Write;
 use arrayvec::ArrayString;
-use dht11::{Dht11, Measurement};
 use embedded_graphics::Drawable;
 use embedded_graphics::pixelcolor::BinaryColor;
 use embedded_graphics::prelude::*;
-use embedded_graphics::mono_font::{ascii::FONT_7X13, ascii::FONT_7X13_BOLD, MonoTextStyle};
+use embedded_graphics::mono_font::{ascii::FONT_6X10, ascii::FONT_7X13, ascii::FONT_7X13_BOLD, MonoTextStyle};
 use embedded_graphics::text::Text;
 use embedded_hal::spi::{Mode as SpiMode, Phase, Polarity};
 use hx1230::{ArrayDisplayBuffer, DisplayBuffer, SpiDriver, DisplayDriver};
+use lib_comfort::Comfort;
+use lib_dht::{DhtCapture, Measurement, Model, Reading, RobustDht, Statistics, Temperature};
+use stm32f1xx_hal::gpio::PinState;
+use stm32f1xx_hal::timer::Tim3PartialRemap;
 use stm32f1xx_hal::{pac, prelude::*, spi::{NoMiso, Spi}};
 
 use cortex_m_rt::entry;
@@ -22,12 +25,38 @@ pub const SPI_MODE: SpiMode = SpiMode {
     polarity: Polarity::IdleLow,
 };
 
+/// Display refresh period, the sensor is read only when its sampling
+/// interval has passed
+const LOOP_MS: u32 = 500;
+
+/// Time each page is shown for
+const PAGE_MS: u32 = 5000;
+
+/// Valid measurements the shown values are the median of
+const SAMPLES: usize = 5;
+
+/// Pages of the display, switched every `PAGE_MS`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Page {
+    Measurement,
+    Comfort,
+    Statistics,
+}
+
+impl Page {
+    fn next(self) -> Self {
+        match self {
+            Page::Measurement => Page::Comfort,
+            Page::Comfort => Page::Statistics,
+            Page::Statistics => Page::Measurement,
+        }
+    }
+}
+
 #[entry]
 fn main() -> ! {
-    let mut cp = cortex_m::Peripherals::take().unwrap();
+    let cp = cortex_m::Peripherals::take().unwrap();
     let dp = pac::Peripherals::take().unwrap();
-    cp.DCB.enable_trace();
-    cp.DWT.enable_cycle_counter();
 
     let mut flash = dp.FLASH.constrain();
     let rcc = dp.RCC.constrain();
@@ -41,6 +70,8 @@ fn main() -> ! {
         .hclk(8.MHz())     // clock used for timers
         .freeze(&mut flash.acr);
 
+    let mut afio = dp.AFIO.constrain();
+    let dma1 = dp.DMA1.split();
     let mut gpiob = dp.GPIOB.split();
     let mut gpioc = dp.GPIOC.split();
 
@@ -59,7 +90,10 @@ fn main() -> ! {
         clocks,
     );
 
-    let thermo_pin = gpiob.pb5.into_open_drain_output(&mut gpiob.crl);
+    // Line is released until the first measurement starts, PB5 is the
+    // second channel of TIM3 with the partial remap
+    let thermo_pin = gpiob.pb5.into_open_drain_output_with_state(&mut gpiob.crl, PinState::High);
+    let thermo = DhtCapture::new::<Tim3PartialRemap>(dp.TIM3, thermo_pin, dma1.6, &mut afio.mapr, &clocks);
 
     let mut delay = cp.SYST.delay(&clocks);
 
@@ -70,28 +104,40 @@ fn main() -> ! {
     display.send_buffer(&frame_buffer).unwrap();
     delay.delay_ms(200_u16);
 
-    let mut dht11 = Dht11::new(thermo_pin);
+    // DHT11 or DHT22 (AM2302, AM2301) is detected from the first reading
+    let mut sensor: RobustDht<_, SAMPLES> = RobustDht::new(thermo);
+
+    let mut page = Page::Measurement;
+    let mut page_ms = 0;
 
     loop {
         led.set_low();
-        frame_buffer.clear_buffer(0x00);
 
-        let measurement = dht11.perform_measurement(&mut delay);
+        sensor.update(LOOP_MS, &mut delay);
+
+        frame_buffer.clear_buffer(0x00);
 
-        match measurement {
-            Err(err) => {
-                let mut text = ArrayString::<40>::new();
-                let _ = write!(&mut text, "E:{:?}", err);
-                print_text(&mut frame_buffer, &text).unwrap();
+        match page {
+            Page::Measurement => print_reading(&mut frame_buffer, sensor.reading(), sensor.sensor().model()),
+            Page::Comfort => match sensor.reading() {
+                Reading::Valid(values) => print_comfort(&mut frame_buffer, values),
+                reading => print_reading(&mut frame_buffer, reading, sensor.sensor().model()),
             },
-            Ok(values) => print_measurement(&mut frame_buffer, values).unwrap(),
-        }
+            Page::Statistics => print_statistics(&mut frame_buffer, sensor.statistics()),
+        }.unwrap();
 
         display.send_buffer(&frame_buffer).unwrap();
 
         led.set_high();
 
-        delay.delay_ms(500_u16);
+        page_ms += LOOP_MS;
+
+        if page_ms >= PAGE_MS {
+            page_ms = 0;
+            page = page.next();
+        }
+
+        delay.delay_ms(LOOP_MS);
     }
 }
 
@@ -107,21 +153,97 @@ fn print_text(
     Ok(())
 }
 
+fn print_reading(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    reading: Reading,
+    model: Option<Model>,
+) -> Result<(), ()> {
+    match reading {
+        Reading::Starting => print_text(frame_buffer, "Starting up..."),
+        Reading::Valid(values) => print_measurement(frame_buffer, values, model),
+        Reading::Failed(error) => {
+            let mut text = ArrayString::<40>::new();
+            write!(&mut text, "E:{:?}", error).map_err(|_| ())?;
+            print_text(frame_buffer, &text)
+        }
+    }
+}
+
 fn print_measurement(
     frame_buffer: &mut ArrayDisplayBuffer,
     values: Measurement,
+    model: Option<Model>,
 ) -> Result<(), ()> {
     let mut text = ArrayString::<20>::new();
     let regular = MonoTextStyle::new(&FONT_7X13, BinaryColor::On);
+    let small = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
     let bold = MonoTextStyle::new(&FONT_7X13_BOLD, BinaryColor::On);
 
     Text::new(&TEMPERATURE, Point::new(0, 15), regular).draw(frame_buffer).map_err(|_| ())?;
-    write!(&mut text, "{}.{} C", values.temperature/10, values.temperature%10).map_err(|_| ())?;
+    write!(&mut text, "{} C", values.temperature).map_err(|_| ())?;
     Text::new(&text, Point::new(0, 30), bold).draw(frame_buffer).map_err(|_| ())?;
     Text::new(&HUMIDITY, Point::new(0, 45), regular).draw(frame_buffer).map_err(|_| ())?;
+
+    if let Some(model) = model {
+        Text::new(model.name(), Point::new(66, 45), small).draw(frame_buffer).map_err(|_| ())?;
+    }
+
     text.clear();
-    write!(&mut text, "{}.{} %", values.humidity/10, values.humidity%10).map_err(|_| ())?;
+    write!(&mut text, "{} %", values.humidity).map_err(|_| ())?;
     Text::new(&text, Point::new(0, 60), bold).draw(frame_buffer).map_err(|_| ())?;
 
     Ok(())
-}
\ No newline at end of file
+}
+
+fn print_comfort(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    values: Measurement,
+) -> Result<(), ()> {
+    let mut text = ArrayString::<20>::new();
+    let small = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+    let comfort = Comfort::new(values.temperature.decidegrees().into(), values.humidity.permille().into());
+    let degrees = |decidegrees: i32| Temperature::from_decidegrees(decidegrees as i16);
+
+    write!(&mut text, "Dew pt   {} C", degrees(comfort.dew_point)).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 9), small).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+    write!(&mut text, "Heat idx {} C", degrees(comfort.heat_index)).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 22), small).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+    write!(&mut text, "Humidex  {}", degrees(comfort.humidex)).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 35), small).draw(frame_buffer).map_err(|_| ())?;
+    text.clear();
+
+    // Milligrams to grams with a single decimal
+    let grams = (comfort.absolute_humidity + 50)/100;
+    write!(&mut text, "Water {}.{} g/m3", grams/10, grams % 10).map_err(|_| ())?;
+    Text::new(&text, Point::new(0, 48), small).draw(frame_buffer).map_err(|_| ())?;
+
+    Ok(())
+}
+
+fn print_statistics(
+    frame_buffer: &mut ArrayDisplayBuffer,
+    statistics: &Statistics,
+) -> Result<(), ()> {
+    let mut text = ArrayString::<20>::new();
+    let small = MonoTextStyle::new(&FONT_6X10, BinaryColor::On);
+
+    let lines = [
+        ("Reads", statistics.attempts),
+        ("Valid", statistics.valid),
+        ("No answer", statistics.no_responses),
+        // Frame cut short or with a pulse out of its range
+        ("Timing", statistics.timeouts + statistics.timing_errors),
+        ("Checksum", statistics.checksum_errors),
+        ("Failing", statistics.consecutive_failures),
+    ];
+
+    for (line, (label, count)) in lines.iter().enumerate() {
+        text.clear();
+        write!(&mut text, "{:<10}{:>6}", label, count).map_err(|_| ())?;
+        Text::new(&text, Point::new(0, 9 + 11*line as i32), small).draw(frame_buffer).map_err(|_| ())?;
+    }
+
+    Ok(())
+}
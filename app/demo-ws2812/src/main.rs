@@ -0,0 +1,89 @@
+#![no_std]
+#![no_main]
+
+use cortex_m::singleton;
+use cortex_m_rt::entry;
+use lib_rgb_led::{Hsv, Rgb};
+use lib_ws2812::{frame_size, Frame, SPI_FREQUENCY_HZ};
+use stm32f1xx_hal::spi::{Mode, NoMiso, NoSck, Phase, Polarity, Spi};
+use stm32f1xx_hal::{pac, prelude::*};
+use lib_panic_led as _;
+
+pub const SPI_MODE: Mode = Mode {
+    phase: Phase::CaptureOnFirstTransition,
+    polarity: Polarity::IdleLow,
+};
+
+/// Number of LEDs on the strip, each LED takes 9 bytes of RAM,
+/// so even 300 LEDs fit into 20K RAM easily
+const LEDS: usize = 60;
+
+/// Hue difference between two neighbouring LEDs
+const HUE_STEP: u16 = 6;
+
+/// Limit brightness, full white on all LEDs draws 60 mA per LED
+const BRIGHTNESS: u8 = 64;
+
+#[entry]
+fn main() -> ! {
+    let cp = cortex_m::Peripherals::take().unwrap();
+    let dp = pac::Peripherals::take().unwrap();
+
+    let mut flash = dp.FLASH.constrain();
+    let rcc = dp.RCC.constrain();
+
+    let clocks = rcc
+        .cfgr.use_hse(8.MHz())  // use external oscillator (8 MHz)
+        .sysclk(72.MHz())  // system clock, PLL multiplier should be 6
+        .hclk(72.MHz())    // bus clock, SPI1 divides it by 32 to 2.25 MHz
+        .pclk2(72.MHz())   // clock used for SPI1
+        .freeze(&mut flash.acr);
+
+    let mut gpioa = dp.GPIOA.split();
+    let mut afio = dp.AFIO.constrain();
+
+    // Only the data line is needed
+    let mosi = gpioa.pa7.into_alternate_push_pull(&mut gpioa.crl);
+
+    let spi = Spi::spi1(
+        dp.SPI1,
+        (NoSck, NoMiso, mosi),
+        &mut afio.mapr,
+        SPI_MODE,
+        SPI_FREQUENCY_HZ.Hz(),
+        clocks,
+    );
+
+    // SPI1 transmit requests are served by the DMA1 channel 3
+    let dma = dp.DMA1.split();
+    let mut spi_dma = spi.with_tx_dma(dma.3);
+
+    // DMA needs a buffer with the static lifetime
+    let mut buffer: &'static mut [u8; frame_size(LEDS)] =
+        singleton!(: [u8; frame_size(LEDS)] = [0; frame_size(LEDS)]).unwrap();
+
+    let mut delay = cp.SYST.delay(&clocks);
+    let mut offset: u16 = 0;
+
+    loop {
+        let mut frame = Frame::new(&mut buffer[..]);
+        rainbow(&mut frame, offset);
+
+        // Stream the whole frame including the reset gap by the DMA, this
+        // demo has nothing else to do, so it just waits for the transfer
+        let (returned_buffer, returned_spi) = spi_dma.write(buffer).wait();
+        buffer = returned_buffer;
+        spi_dma = returned_spi;
+
+        offset = (offset + 2) % 360;
+        delay.delay_ms(20u32);
+    }
+}
+
+fn rainbow(frame: &mut Frame, offset: u16) {
+    for index in 0..frame.len() {
+        let hue = (offset + index as u16*HUE_STEP) % 360;
+        let color: Rgb = Hsv::new(hue, 255, BRIGHTNESS).into();
+        frame.set(index, color);
+    }
+}
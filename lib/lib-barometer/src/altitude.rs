@@ -0,0 +1,278 @@
+use crate::units::Pressure;
+
+/// Lowest altitude accepted by `sea_level_pressure`, -1000 m
+pub const MIN_ALTITUDE_CM: i32 = -100_000;
+
+/// Highest altitude accepted by `sea_level_pressure`, 7192 m
+pub const MAX_ALTITUDE_CM: i32 = MIN_ALTITUDE_CM + SEA_LEVEL_STEP_CM*256;
+
+/// Altitude difference between entries of the `SEA_LEVEL` table
+const SEA_LEVEL_STEP_CM: i32 = 3200;
+
+/// Fixed point values in the tables have 24 fractional bits
+const ONE: i64 = 1 << 24;
+
+/// Pressure ratio of the first `POWER` entry (0.25) in the same format
+const POWER_START: u64 = 1 << 22;
+
+/// Height of the atmosphere in the barometric formula, 44330 m
+const SCALE_HEIGHT_CM: i64 = 4_433_000;
+
+/// Converts pressure to altitude using the international barometric formula
+///
+/// `h = 44330 m * (1 - (p/p0)^(1/5.255))`
+///
+/// The reference pressure `p0` is the pressure at the sea level. The standard
+/// atmosphere gives altitudes off by tens of meters as the weather changes,
+/// `calibrate` sets the reference from a place with a known altitude.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Altimeter {
+    reference: Pressure,
+}
+
+impl Default for Altimeter {
+    fn default() -> Self {
+        Self::new(Pressure::STANDARD)
+    }
+}
+
+impl Altimeter {
+    pub const fn new(reference: Pressure) -> Self {
+        Self { reference }
+    }
+
+    pub fn reference(&self) -> Pressure {
+        self.reference
+    }
+
+    pub fn set_reference(&mut self, reference: Pressure) {
+        self.reference = reference;
+    }
+
+    /// Set the reference so that `pressure` measured at `altitude_cm`
+    /// gives exactly that altitude
+    pub fn calibrate(&mut self, pressure: Pressure, altitude_cm: i32) {
+        self.reference = sea_level_pressure(pressure, altitude_cm);
+    }
+
+    /// Altitude in centimeters above the level where the pressure equals
+    /// the reference, from about -1900 m to 10300 m, pressures out of
+    /// this range give the nearest limit
+    pub fn altitude_cm(&self, pressure: Pressure) -> i32 {
+        let reference = self.reference.q24_8().max(1) as u64;
+        let ratio = ((pressure.q24_8() as u64) << 24)/reference;
+
+        // Table covers ratios from 0.25 to 1.25 in 256 steps
+        let position = ratio.saturating_sub(POWER_START).min(256 << 16);
+        let power = interpolate(&POWER, (position >> 16) as usize, (position & 0xFFFF) as i64, 1 << 16);
+
+        div_round((ONE - power)*SCALE_HEIGHT_CM, ONE) as i32
+    }
+
+    /// Altitude in whole meters, rounded
+    pub fn altitude_m(&self, pressure: Pressure) -> i32 {
+        div_round(self.altitude_cm(pressure) as i64, 100) as i32
+    }
+}
+
+/// Pressure at the sea level given `pressure` measured at `altitude_cm`,
+/// the value weather reports use (QNH)
+///
+/// `p0 = p/(1 - h/44330 m)^5.255`
+///
+/// Altitude is limited to `MIN_ALTITUDE_CM` - `MAX_ALTITUDE_CM`.
+pub fn sea_level_pressure(pressure: Pressure, altitude_cm: i32) -> Pressure {
+    let position = altitude_cm.clamp(MIN_ALTITUDE_CM, MAX_ALTITUDE_CM) - MIN_ALTITUDE_CM;
+    let index = (position/SEA_LEVEL_STEP_CM) as usize;
+    let fraction = (position % SEA_LEVEL_STEP_CM) as i64;
+
+    let factor = interpolate(&SEA_LEVEL, index, fraction, SEA_LEVEL_STEP_CM as i64);
+    let value = div_round(pressure.q24_8() as i64*factor, ONE);
+    Pressure::from_q24_8(value.min(u32::MAX as i64) as u32)
+}
+
+/// Linear interpolation between `table[index]` and `table[index + 1]`,
+/// `fraction` is from 0 to `range`, the last entry is reached with
+/// index 256
+fn interpolate(table: &[u32; 257], index: usize, fraction: i64, range: i64) -> i64 {
+    let (index, fraction) = match index {
+        256 => (255, range),
+        index => (index, fraction),
+    };
+
+    let start = table[index] as i64;
+    let end = table[index + 1] as i64;
+    start + div_round((end - start)*fraction, range)
+}
+
+/// Division rounding half away from zero, `divisor` is positive
+fn div_round(value: i64, divisor: i64) -> i64 {
+    match value >= 0 {
+        true => (value + divisor/2)/divisor,
+        false => (value - divisor/2)/divisor,
+    }
+}
+
+/// `(0.25 + i/256)^(1/5.255)` with 24 fractional bits
+const POWER: [u32; 257] = [
+    12886973, 12925050, 12962656, 12999804, 13036505, 13072772, 13108616, 13144047,
+    13179077, 13213714, 13247970, 13281853, 13315372, 13348536, 13381353, 13413831,
+    13445978, 13477801, 13509308, 13540505, 13571399, 13601997, 13632304, 13662328,
+    13692073, 13721547, 13750753, 13779697, 13808385, 13836822, 13865012, 13892961,
+    13920672, 13948150, 13975400, 14002426, 14029232, 14055821, 14082198, 14108367,
+    14134331, 14160093, 14185657, 14211027, 14236206, 14261196, 14286002, 14310626,
+    14335070, 14359339, 14383434, 14407359, 14431116, 14454708, 14478137, 14501405,
+    14524516, 14547472, 14570274, 14592926, 14615429, 14637785, 14659998, 14682067,
+    14703997, 14725788, 14747443, 14768964, 14790352, 14811609, 14832737, 14853738,
+    14874613, 14895364, 14915993, 14936501, 14956891, 14977162, 14997318, 15017359,
+    15037286, 15057102, 15076808, 15096405, 15115894, 15135277, 15154554, 15173728,
+    15192800, 15211770, 15230639, 15249410, 15268083, 15286660, 15305141, 15323527,
+    15341820, 15360021, 15378130, 15396150, 15414079, 15431921, 15449675, 15467343,
+    15484925, 15502423, 15519837, 15537168, 15554418, 15571586, 15588675, 15605684,
+    15622614, 15639467, 15656243, 15672943, 15689567, 15706117, 15722593, 15738995,
+    15755326, 15771584, 15787772, 15803889, 15819937, 15835916, 15851826, 15867669,
+    15883444, 15899154, 15914797, 15930376, 15945889, 15961339, 15976726, 15992049,
+    16007311, 16022511, 16037649, 16052728, 16067746, 16082704, 16097604, 16112445,
+    16127228, 16141954, 16156623, 16171236, 16185792, 16200293, 16214739, 16229130,
+    16243468, 16257751, 16271982, 16286160, 16300285, 16314359, 16328381, 16342352,
+    16356272, 16370142, 16383962, 16397733, 16411455, 16425128, 16438753, 16452330,
+    16465860, 16479342, 16492778, 16506167, 16519510, 16532808, 16546060, 16559267,
+    16572429, 16585547, 16598621, 16611652, 16624639, 16637583, 16650484, 16663343,
+    16676160, 16688935, 16701669, 16714361, 16727013, 16739624, 16752194, 16764725,
+    16777216, 16789667, 16802080, 16814453, 16826788, 16839085, 16851343, 16863563,
+    16875746, 16887892, 16900001, 16912072, 16924108, 16936107, 16948070, 16959997,
+    16971888, 16983744, 16995565, 17007351, 17019103, 17030820, 17042503, 17054152,
+    17065767, 17077348, 17088897, 17100412, 17111894, 17123344, 17134761, 17146146,
+    17157498, 17168819, 17180108, 17191366, 17202592, 17213788, 17224952, 17236086,
+    17247189, 17258262, 17269305, 17280317, 17291300, 17302254, 17313178, 17324073,
+    17334938, 17345775, 17356583, 17367362, 17378113, 17388836, 17399531, 17410198,
+    17420837, 17431449, 17442033, 17452590, 17463119, 17473622, 17484098, 17494548,
+    17504970,
+];
+
+/// `(1 - (i*32 m - 1000 m)/44330 m)^-5.255` with 24 fractional bits
+const SEA_LEVEL: [u32; 257] = [
+    14921398, 14976874, 15032596, 15088565, 15144782, 15201248, 15257965, 15314935,
+    15372157, 15429634, 15487368, 15545358, 15603608, 15662117, 15720887, 15779921,
+    15839218, 15898781, 15958610, 16018708, 16079075, 16139713, 16200624, 16261808,
+    16323268, 16385004, 16447019, 16509313, 16571888, 16634746, 16697887, 16761314,
+    16825029, 16889031, 16953324, 17017908, 17082785, 17147957, 17213425, 17279191,
+    17345256, 17411621, 17478290, 17545262, 17612540, 17680126, 17748020, 17816225,
+    17884742, 17953573, 18022719, 18092183, 18161966, 18232069, 18302495, 18373244,
+    18444320, 18515722, 18587455, 18659518, 18731914, 18804644, 18877711, 18951116,
+    19024861, 19098948, 19173379, 19248155, 19323279, 19398751, 19474575, 19550752,
+    19627284, 19704173, 19781420, 19859029, 19937000, 20015335, 20094038, 20173109,
+    20252550, 20332364, 20412553, 20493119, 20574063, 20655388, 20737096, 20819189,
+    20901670, 20984539, 21067800, 21151454, 21235505, 21319952, 21404800, 21490051,
+    21575705, 21661767, 21748237, 21835118, 21922413, 22010124, 22098252, 22186801,
+    22275773, 22365169, 22454993, 22545247, 22635933, 22727053, 22818610, 22910607,
+    23003046, 23095928, 23189258, 23283036, 23377267, 23471952, 23567093, 23662694,
+    23758757, 23855285, 23952280, 24049744, 24147681, 24246093, 24344983, 24444353,
+    24544207, 24644546, 24745374, 24846693, 24948507, 25050817, 25153627, 25256940,
+    25360759, 25465085, 25569923, 25675275, 25781145, 25887534, 25994446, 26101884,
+    26209851, 26318350, 26427384, 26536957, 26647070, 26757728, 26868933, 26980689,
+    27092998, 27205864, 27319291, 27433281, 27547837, 27662963, 27778662, 27894938,
+    28011794, 28129232, 28247258, 28365873, 28485081, 28604887, 28725292, 28846301,
+    28967918, 29090145, 29212987, 29336446, 29460528, 29585234, 29710569, 29836537,
+    29963141, 30090385, 30218272, 30346808, 30475994, 30605836, 30736336, 30867500,
+    30999330, 31131831, 31265006, 31398860, 31533397, 31668621, 31804535, 31941145,
+    32078453, 32216464, 32355183, 32494614, 32634760, 32775626, 32917216, 33059535,
+    33202588, 33346377, 33490909, 33636186, 33782215, 33928998, 34076542, 34224850,
+    34373926, 34523776, 34674405, 34825816, 34978015, 35131007, 35284795, 35439386,
+    35594783, 35750992, 35908018, 36065865, 36224540, 36384045, 36544388, 36705572,
+    36867603, 37030486, 37194226, 37358829, 37524300, 37690644, 37857867, 38025973,
+    38194968, 38364858, 38535649, 38707345, 38879953, 39053477, 39227924, 39403300,
+    39579609, 39756858, 39935053, 40114200, 40294304, 40475371, 40657407, 40840419,
+    41024412, 41209393, 41395367, 41582341, 41770321, 41959314, 42149325, 42340362,
+    42532430,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Pressures of the standard atmosphere in 1/10 Pa at altitudes in meters
+    const STANDARD_ATMOSPHERE: [(i32, u32); 6] = [
+        (-500, 1_074_775),
+        (0, 1_013_250),
+        (1000, 898_746),
+        (2000, 794_952),
+        (5000, 540_199),
+        (7000, 410_607),
+    ];
+
+    fn decipascals(value: u32) -> Pressure {
+        Pressure::from_q24_8(((value as u64*256 + 5)/10) as u32)
+    }
+
+    #[test]
+    fn reference_pressure_is_zero_altitude() {
+        for hectopascals in [950, 1013, 1050] {
+            let reference = Pressure::from_hectopascals(hectopascals);
+            assert_eq!(Altimeter::new(reference).altitude_cm(reference), 0);
+        }
+    }
+
+    #[test]
+    fn standard_atmosphere() {
+        let altimeter = Altimeter::default();
+
+        // Rounded exponent of the formula drifts by about 1 m at 7 km
+        for (altitude_m, pressure) in STANDARD_ATMOSPHERE {
+            let altitude_cm = altimeter.altitude_cm(decipascals(pressure));
+            assert!((altitude_cm - altitude_m*100).abs() <= 150, "{} {}", altitude_m, altitude_cm);
+        }
+
+        assert_eq!(altimeter.altitude_m(decipascals(898_746)), 1000);
+    }
+
+    #[test]
+    fn sea_level_pressure_of_standard_atmosphere() {
+        for (altitude_m, pressure) in STANDARD_ATMOSPHERE {
+            let sea_level = sea_level_pressure(decipascals(pressure), altitude_m*100);
+            assert!(sea_level.pascals().abs_diff(101_325) <= 15, "{} {}", altitude_m, sea_level);
+        }
+    }
+
+    #[test]
+    fn calibration_round_trip() {
+        let pressure = Pressure::from_q24_8(25767236);
+
+        for altitude_cm in [-90_000, -1234, 0, 25_000, 100_000, 400_000, 700_000] {
+            let mut altimeter = Altimeter::default();
+            altimeter.calibrate(pressure, altitude_cm);
+            let error = altimeter.altitude_cm(pressure) - altitude_cm;
+            assert!(error.abs() <= 10, "{} {}", altitude_cm, error);
+        }
+    }
+
+    #[test]
+    fn sea_level_altitude_is_clamped() {
+        let pressure = Pressure::from_hectopascals(1000);
+
+        assert_eq!(
+            sea_level_pressure(pressure, MIN_ALTITUDE_CM - 50_000),
+            sea_level_pressure(pressure, MIN_ALTITUDE_CM),
+        );
+
+        assert_eq!(
+            sea_level_pressure(pressure, MAX_ALTITUDE_CM + 50_000),
+            sea_level_pressure(pressure, MAX_ALTITUDE_CM),
+        );
+
+        assert_eq!(sea_level_pressure(pressure, 0).pascals(), pressure.pascals());
+    }
+
+    #[test]
+    fn altitude_is_clamped() {
+        let altimeter = Altimeter::default();
+        let highest = altimeter.altitude_cm(Pressure::from_pascals(20_000));
+        let lowest = altimeter.altitude_cm(Pressure::from_pascals(130_000));
+
+        assert_eq!(altimeter.altitude_cm(Pressure::from_pascals(1000)), highest);
+        assert_eq!(altimeter.altitude_cm(Pressure::from_q24_8(0)), highest);
+        assert_eq!(altimeter.altitude_cm(Pressure::from_pascals(200_000)), lowest);
+        assert!((1_020_000..1_040_000).contains(&highest), "{}", highest);
+        assert!((-200_000..-185_000).contains(&lowest), "{}", lowest);
+    }
+}
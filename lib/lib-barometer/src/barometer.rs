@@ -0,0 +1,161 @@
+use crate::calibration::Calibration;
+use crate::config::Config;
+use crate::registers::{
+    Registers, CALIBRATION, CALIBRATION_H1, CALIBRATION_H2, CHIP_ID, CONFIG, CONTROL_HUMIDITY,
+    CONTROL_MEASUREMENT, DATA, STATUS,
+};
+use crate::units::{Humidity, Pressure, Temperature};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Communication with the sensor failed, or no sensor answered
+    Bus,
+    /// Chip ID register contains an ID of no supported sensor
+    UnknownChip(u8),
+}
+
+/// Sensors sharing the same registers and compensation formulas
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Chip {
+    /// Pressure and temperature
+    Bmp280,
+    /// Pressure, temperature and humidity
+    Bme280,
+}
+
+impl Chip {
+    /// Chip ID register value, BMP280 engineering samples use 0x56 and 0x57
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            0x56..=0x58 => Some(Chip::Bmp280),
+            0x60 => Some(Chip::Bme280),
+            _ => None,
+        }
+    }
+
+    pub fn has_humidity(&self) -> bool {
+        *self == Chip::Bme280
+    }
+}
+
+/// Compensated values of a single measurement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    pub temperature: Temperature,
+    pub pressure: Pressure,
+    /// Measured only by BME280
+    pub humidity: Option<Humidity>,
+}
+
+/// BMP280 or BME280 measuring on demand in the forced mode
+///
+/// The sensor does not keep the bus, registers are passed to every call,
+/// so it can share the bus with other devices.
+pub struct Barometer {
+    chip: Chip,
+    config: Config,
+    calibration: Calibration,
+}
+
+impl Barometer {
+    /// Detect the chip, read the calibration data and configure the sensor
+    pub fn new<R: Registers + ?Sized>(registers: &mut R, config: Config) -> Result<Self, Error> {
+        let chip = detect(registers)?;
+
+        let mut data = [0u8; 24];
+        registers.read(CALIBRATION, &mut data)?;
+        let mut calibration = Calibration::new(&data);
+
+        if chip.has_humidity() {
+            let mut h1 = [0u8; 1];
+            let mut data = [0u8; 7];
+            registers.read(CALIBRATION_H1, &mut h1)?;
+            registers.read(CALIBRATION_H2, &mut data)?;
+            calibration = calibration.with_humidity(h1[0], &data);
+        }
+
+        let barometer = Self { chip, config, calibration };
+        barometer.configure(registers)?;
+        Ok(barometer)
+    }
+
+    pub fn chip(&self) -> Chip {
+        self.chip
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn set_config<R: Registers + ?Sized>(&mut self, registers: &mut R, config: Config) -> Result<(), Error> {
+        self.config = config;
+        self.configure(registers)
+    }
+
+    /// Longest time the measurement takes with the current configuration
+    pub fn measurement_time_us(&self) -> u32 {
+        self.config.measurement_time_us(self.chip.has_humidity())
+    }
+
+    /// Start a single measurement, it takes `measurement_time_us`,
+    /// the sensor returns to sleep afterwards
+    pub fn trigger<R: Registers + ?Sized>(&self, registers: &mut R) -> Result<(), Error> {
+        registers.write(CONTROL_MEASUREMENT, self.config.control_measurement())
+    }
+
+    /// True while the measurement is running
+    pub fn is_measuring<R: Registers + ?Sized>(&self, registers: &mut R) -> Result<bool, Error> {
+        let mut status = [0u8; 1];
+        registers.read(STATUS, &mut status)?;
+        Ok(status[0] & 0b1000 != 0)
+    }
+
+    /// Read the result of the last measurement
+    pub fn read<R: Registers + ?Sized>(&self, registers: &mut R) -> Result<Measurement, Error> {
+        // Burst read makes sure all values come from the same measurement
+        let mut data = [0u8; 8];
+        let length = if self.chip.has_humidity() { 8 } else { 6 };
+        registers.read(DATA, &mut data[..length])?;
+
+        let adc_pressure = adc_20bit(data[0], data[1], data[2]);
+        let adc_temperature = adc_20bit(data[3], data[4], data[5]);
+
+        // Pressure and humidity compensation use the fine temperature
+        let fine = self.calibration.fine_temperature(adc_temperature);
+
+        let humidity = match self.chip.has_humidity() {
+            true => {
+                let adc_humidity = ((data[6] as i32) << 8) | data[7] as i32;
+                Some(Humidity::from_q22_10(self.calibration.humidity(adc_humidity, fine)))
+            }
+            false => None,
+        };
+
+        Ok(Measurement {
+            temperature: Temperature::from_centidegrees(self.calibration.temperature(fine)),
+            pressure: Pressure::from_q24_8(self.calibration.pressure(adc_pressure, fine)),
+            humidity,
+        })
+    }
+
+    fn configure<R: Registers + ?Sized>(&self, registers: &mut R) -> Result<(), Error> {
+        // Humidity control takes effect after the next write to the
+        // measurement control, that happens with every trigger
+        if self.chip.has_humidity() {
+            registers.write(CONTROL_HUMIDITY, self.config.control_humidity())?;
+        }
+
+        registers.write(CONFIG, self.config.config())
+    }
+}
+
+/// Read the chip ID, fails with `Error::Bus` when no sensor answers
+pub fn detect<R: Registers + ?Sized>(registers: &mut R) -> Result<Chip, Error> {
+    let mut id = [0u8; 1];
+    registers.read(CHIP_ID, &mut id)?;
+    Chip::from_id(id[0]).ok_or(Error::UnknownChip(id[0]))
+}
+
+fn adc_20bit(msb: u8, lsb: u8, xlsb: u8) -> i32 {
+    ((msb as i32) << 12) | ((lsb as i32) << 4) | (xlsb as i32 >> 4)
+}
@@ -0,0 +1,207 @@
+/// Trimming parameters stored in the sensor, the compensation formulas
+/// are the 32-bit and 64-bit integer ones from the datasheets
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Calibration {
+    t1: u16,
+    t2: i16,
+    t3: i16,
+    p1: u16,
+    p2: i16,
+    p3: i16,
+    p4: i16,
+    p5: i16,
+    p6: i16,
+    p7: i16,
+    p8: i16,
+    p9: i16,
+    h1: u8,
+    h2: i16,
+    h3: u8,
+    h4: i16,
+    h5: i16,
+    h6: i8,
+}
+
+impl Calibration {
+    /// Temperature and pressure parameters from registers 0x88 - 0x9F
+    pub fn new(data: &[u8; 24]) -> Self {
+        let unsigned = |index: usize| u16::from_le_bytes([data[index], data[index + 1]]);
+        let signed = |index: usize| unsigned(index) as i16;
+
+        Self {
+            t1: unsigned(0),
+            t2: signed(2),
+            t3: signed(4),
+            p1: unsigned(6),
+            p2: signed(8),
+            p3: signed(10),
+            p4: signed(12),
+            p5: signed(14),
+            p6: signed(16),
+            p7: signed(18),
+            p8: signed(20),
+            p9: signed(22),
+            ..Self::default()
+        }
+    }
+
+    /// Humidity parameters of BME280, `h1` from the register 0xA1,
+    /// the rest from registers 0xE1 - 0xE7
+    pub fn with_humidity(self, h1: u8, data: &[u8; 7]) -> Self {
+        Self {
+            h1,
+            h2: i16::from_le_bytes([data[0], data[1]]),
+            h3: data[2],
+            // 12-bit values sharing the register 0xE5
+            h4: ((data[3] as i8 as i16) << 4) | (data[4] & 0x0F) as i16,
+            h5: ((data[5] as i8 as i16) << 4) | (data[4] >> 4) as i16,
+            h6: data[6] as i8,
+            ..self
+        }
+    }
+
+    /// Fine temperature used by the pressure and humidity compensation
+    pub fn fine_temperature(&self, adc: i32) -> i32 {
+        let t1 = self.t1 as i32;
+        let var1 = (((adc >> 3) - (t1 << 1))*self.t2 as i32) >> 11;
+        let var2 = (((((adc >> 4) - t1)*((adc >> 4) - t1)) >> 12)*self.t3 as i32) >> 14;
+        var1 + var2
+    }
+
+    /// Temperature in hundredths of a degree Celsius
+    pub fn temperature(&self, fine: i32) -> i32 {
+        (fine*5 + 128) >> 8
+    }
+
+    /// Pressure in the Q24.8 format
+    pub fn pressure(&self, adc: i32, fine: i32) -> u32 {
+        let var1 = fine as i64 - 128_000;
+        let var2 = var1*var1*self.p6 as i64;
+        let var2 = var2 + ((var1*self.p5 as i64) << 17);
+        let var2 = var2 + ((self.p4 as i64) << 35);
+        let var1 = ((var1*var1*self.p3 as i64) >> 8) + ((var1*self.p2 as i64) << 12);
+        let var1 = (((1_i64 << 47) + var1)*self.p1 as i64) >> 33;
+
+        if var1 == 0 {
+            // Avoid division by zero with invalid parameters
+            return 0;
+        }
+
+        let p = 1_048_576 - adc as i64;
+        let p = (((p << 31) - var2)*3125)/var1;
+        let var1 = (self.p9 as i64*(p >> 13)*(p >> 13)) >> 25;
+        let var2 = (self.p8 as i64*p) >> 19;
+        let p = ((p + var1 + var2) >> 8) + ((self.p7 as i64) << 4);
+        p.clamp(0, u32::MAX as i64) as u32
+    }
+
+    /// Relative humidity in the Q22.10 format, in percent
+    pub fn humidity(&self, adc: i32, fine: i32) -> u32 {
+        let v = fine - 76_800;
+        let v = ((((adc << 14) - ((self.h4 as i32) << 20) - (self.h5 as i32*v)) + 16_384) >> 15)
+            *(((((((v*self.h6 as i32) >> 10)*(((v*self.h3 as i32) >> 11) + 32_768)) >> 10)
+                + 2_097_152)*self.h2 as i32 + 8192) >> 14);
+        let v = v - (((((v >> 15)*(v >> 15)) >> 7)*self.h1 as i32) >> 4);
+        (v.clamp(0, 419_430_400) >> 12) as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::string::ToString;
+
+    use super::*;
+    use crate::units::Pressure;
+
+    /// Registers 0x88 - 0x9F with the trimming values of the BMP280
+    /// datasheet example
+    fn datasheet() -> Calibration {
+        let values: [i32; 12] = [27504, 26435, -1000, 36477, -10685, 3024, 2855, 140, -7, 15500, -14600, 6000];
+        let mut data = [0; 24];
+
+        for (chunk, value) in data.chunks_mut(2).zip(values) {
+            chunk.copy_from_slice(&(value as u16).to_le_bytes());
+        }
+
+        Calibration::new(&data)
+    }
+
+    /// Registers 0xE1 - 0xE7 for h2 = 362, h3 = 0, h4 = 313, h5 = 50, h6 = 30
+    const HUMIDITY: [u8; 7] = [0x6A, 0x01, 0x00, 0x13, 0x29, 0x03, 0x1E];
+
+    #[test]
+    fn datasheet_temperature() {
+        let calibration = datasheet();
+        let fine = calibration.fine_temperature(519888);
+        assert_eq!(fine, 128422);
+        assert_eq!(calibration.temperature(fine), 2508);
+    }
+
+    #[test]
+    fn datasheet_pressure() {
+        let calibration = datasheet();
+        let fine = calibration.fine_temperature(519888);
+        let pressure = calibration.pressure(415148, fine);
+
+        // Datasheet lists 25767236, the published integer formula gives
+        // 25767233 for the same inputs, both are 1006.53 hPa
+        assert!(pressure.abs_diff(25767236) <= 3, "{}", pressure);
+        assert_eq!(Pressure::from_q24_8(pressure).to_string(), "1006.53");
+    }
+
+    #[test]
+    fn invalid_parameters_give_zero_pressure() {
+        let calibration = Calibration::new(&[0; 24]);
+        assert_eq!(calibration.pressure(415148, 128422), 0);
+    }
+
+    #[test]
+    fn humidity_nibbles() {
+        let calibration = datasheet().with_humidity(75, &HUMIDITY);
+        assert_eq!(calibration.h1, 75);
+        assert_eq!(calibration.h2, 362);
+        assert_eq!(calibration.h3, 0);
+        assert_eq!(calibration.h4, 313);
+        assert_eq!(calibration.h5, 50);
+        assert_eq!(calibration.h6, 30);
+
+        // Sign of the 12-bit values comes from the whole bytes 0xE4 and 0xE6
+        let calibration = datasheet().with_humidity(0, &[0, 0, 0, 0xFF, 0x4B, 0x80, 0xE2]);
+        assert_eq!(calibration.h4, -5);
+        assert_eq!(calibration.h5, -2044);
+        assert_eq!(calibration.h6, -30);
+    }
+
+    /// Floating point formula of the BME280 datasheet
+    fn float_humidity(c: &Calibration, adc: i32, fine: i32) -> f64 {
+        let v = fine as f64 - 76800.0;
+        let v = (adc as f64 - (c.h4 as f64*64.0 + c.h5 as f64/16384.0*v))
+            *(c.h2 as f64/65536.0*(1.0 + c.h6 as f64/67108864.0*v*(1.0 + c.h3 as f64/67108864.0*v)));
+        (v*(1.0 - c.h1 as f64*v/524288.0)).clamp(0.0, 100.0)
+    }
+
+    #[test]
+    fn humidity_matches_float_formula() {
+        let calibration = datasheet().with_humidity(75, &HUMIDITY);
+
+        for adc_t in [400_000, 519_888, 600_000] {
+            let fine = calibration.fine_temperature(adc_t);
+
+            for adc in (20_000..50_000).step_by(1000) {
+                let humidity = calibration.humidity(adc, fine) as f64/1024.0;
+                let expected = float_humidity(&calibration, adc, fine);
+                assert!((humidity - expected).abs() < 0.05, "{} {} {}", adc, humidity, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn humidity_is_clamped() {
+        let calibration = datasheet().with_humidity(75, &HUMIDITY);
+        let fine = calibration.fine_temperature(519888);
+        assert_eq!(calibration.humidity(0, fine), 0);
+        assert_eq!(calibration.humidity(65535, fine), 100*1024);
+    }
+}
@@ -0,0 +1,90 @@
+/// Number of samples averaged into a single value, more samples lower
+/// the noise, but make the measurement longer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Oversampling {
+    X1 = 1,
+    X2 = 2,
+    X4 = 3,
+    X8 = 4,
+    X16 = 5,
+}
+
+impl Oversampling {
+    pub fn samples(&self) -> u32 {
+        1 << (*self as u32 - 1)
+    }
+}
+
+/// IIR filter coefficient, filters out short pressure changes like slammed
+/// doors or wind blowing into the sensor
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Off = 0,
+    X2 = 1,
+    X4 = 2,
+    X8 = 3,
+    X16 = 4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub temperature: Oversampling,
+    pub pressure: Oversampling,
+    /// Used only by BME280
+    pub humidity: Oversampling,
+    pub filter: Filter,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::weather_monitoring()
+    }
+}
+
+impl Config {
+    /// Lowest power and noise good enough for a measurement every minute,
+    /// recommended for weather monitoring by the datasheet
+    pub const fn weather_monitoring() -> Self {
+        Self {
+            temperature: Oversampling::X1,
+            pressure: Oversampling::X1,
+            humidity: Oversampling::X1,
+            filter: Filter::Off,
+        }
+    }
+
+    /// Low noise for frequent measurements, recommended for handheld
+    /// devices (altimeters) by the datasheet
+    pub const fn handheld_device_dynamic() -> Self {
+        Self {
+            temperature: Oversampling::X1,
+            pressure: Oversampling::X4,
+            humidity: Oversampling::X1,
+            filter: Filter::X16,
+        }
+    }
+
+    /// Longest time of a measurement in microseconds, humidity is
+    /// measured only by BME280
+    pub fn measurement_time_us(&self, humidity: bool) -> u32 {
+        let humidity = match humidity {
+            true => 2300*self.humidity.samples() + 575,
+            false => 0,
+        };
+
+        1250 + 2300*self.temperature.samples() + 2300*self.pressure.samples() + 575 + humidity
+    }
+
+    pub(crate) fn control_humidity(&self) -> u8 {
+        self.humidity as u8
+    }
+
+    /// Control register value starting a measurement in the forced mode
+    pub(crate) fn control_measurement(&self) -> u8 {
+        ((self.temperature as u8) << 5) | ((self.pressure as u8) << 2) | 0b01
+    }
+
+    pub(crate) fn config(&self) -> u8 {
+        (self.filter as u8) << 2
+    }
+}
@@ -0,0 +1,97 @@
+use crate::history::Trend;
+use crate::units::Pressure;
+
+/// Forecasts of the Zambretti table, `A` is the best weather, `Z` the worst
+const TEXTS: [&str; 26] = [
+    "Settled fine",
+    "Fine weather",
+    "Becoming fine",
+    "Fine, becoming less settled",
+    "Fine, possible showers",
+    "Fairly fine, improving",
+    "Fairly fine, possible showers early",
+    "Fairly fine, showery later",
+    "Showery early, improving",
+    "Changeable, mending",
+    "Fairly fine, showers likely",
+    "Rather unsettled, clearing later",
+    "Unsettled, probably improving",
+    "Showery, bright intervals",
+    "Showery, becoming less settled",
+    "Changeable, some rain",
+    "Unsettled, short fine intervals",
+    "Unsettled, rain later",
+    "Unsettled, some rain",
+    "Mostly very unsettled",
+    "Occasional rain, worsening",
+    "Rain at times, very unsettled",
+    "Rain at frequent intervals",
+    "Rain, very unsettled",
+    "Stormy, may improve",
+    "Stormy, much rain",
+];
+
+/// Forecast letters for Zambretti numbers 1 - 9 (falling pressure)
+const FALLING: &[u8] = b"ABDHORUXZ";
+
+/// Forecast letters for Zambretti numbers 10 - 19 (steady pressure)
+const STEADY: &[u8] = b"ABEKNPSWXZ";
+
+/// Forecast letters for Zambretti numbers 20 - 32 (rising pressure)
+const RISING: &[u8] = b"ABCFGIJLMQTYZ";
+
+/// Rough kind of the weather, useful to pick an icon
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weather {
+    Fine,
+    Fair,
+    Showers,
+    Rain,
+    Storm,
+}
+
+/// Zambretti forecast for the next 12 hours
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Forecast(u8);
+
+impl Forecast {
+    /// Forecast from the sea level pressure (see `sea_level_pressure`)
+    /// and its trend over the last three hours
+    ///
+    /// The Zambretti number is `127 - 0.12*P` for falling, `144 - 0.13*P`
+    /// for steady and `185 - 0.16*P` for rising pressure in hPa, pressures
+    /// out of the 950 - 1050 hPa range give the nearest forecast.
+    pub fn zambretti(sea_level: Pressure, trend: Trend) -> Self {
+        let pascals = sea_level.pascals().clamp(95_000, 105_000) as i32;
+
+        // Coefficients are in hundredths, so the pressure is in pascals
+        let (letters, start, base, slope) = match trend {
+            Trend::Falling => (FALLING, 1, 127, 12),
+            Trend::Steady => (STEADY, 10, 144, 13),
+            Trend::Rising => (RISING, 20, 185, 16),
+        };
+
+        let number = (base*10_000 - slope*pascals + 5_000)/10_000;
+        let index = (number - start).clamp(0, letters.len() as i32 - 1);
+        Self(letters[index as usize] - b'A')
+    }
+
+    /// Letter of the forecast, from `A` to `Z`
+    pub fn letter(&self) -> char {
+        (b'A' + self.0) as char
+    }
+
+    pub fn text(&self) -> &'static str {
+        TEXTS[self.0 as usize]
+    }
+
+    pub fn weather(&self) -> Weather {
+        match self.letter() {
+            'A'..='B' => Weather::Fine,
+            'C'..='H' => Weather::Fair,
+            'I'..='Q' => Weather::Showers,
+            'R'..='X' => Weather::Rain,
+            _ => Weather::Storm,
+        }
+    }
+}
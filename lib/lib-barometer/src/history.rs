@@ -0,0 +1,155 @@
+use core::fmt;
+
+use crate::units::Pressure;
+
+/// Pressure tendency is reported as the change over three hours
+const TENDENCY_MS: u64 = 3*60*60*1000;
+
+/// Shortest history the tendency is estimated from
+const MIN_TENDENCY_MS: u64 = 60*60*1000;
+
+/// Changes smaller than 1.6 hPa in three hours count as steady
+pub const STEADY_LIMIT_PA: i32 = 160;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Trend {
+    Falling,
+    Steady,
+    Rising,
+}
+
+/// Pressure change in pascals per three hours
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Tendency(i32);
+
+impl Tendency {
+    pub const fn from_pascals(pascals_per_3h: i32) -> Self {
+        Self(pascals_per_3h)
+    }
+
+    pub const fn pascals(&self) -> i32 {
+        self.0
+    }
+
+    pub fn trend(&self) -> Trend {
+        match self.0 {
+            change if change <= -STEADY_LIMIT_PA => Trend::Falling,
+            change if change >= STEADY_LIMIT_PA => Trend::Rising,
+            _ => Trend::Steady,
+        }
+    }
+}
+
+/// Formatted as hPa with one decimal and a sign, `+1.6`
+impl fmt::Display for Tendency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "+" };
+        let tenths = (self.0.unsigned_abs() + 5)/10;
+        write!(f, "{}{}.{}", sign, tenths/10, tenths % 10)
+    }
+}
+
+/// Pressure samples taken every `interval_ms`, the last `N` are kept
+///
+/// All pressures passed to `update` between two samples are averaged,
+/// so the noise of single readings does not show in the tendency.
+pub struct PressureHistory<const N: usize> {
+    samples: [Pressure; N],
+    /// Index the next sample is written to
+    next: usize,
+    len: usize,
+    interval_ms: u32,
+    elapsed_ms: u32,
+    sum: u64,
+    count: u32,
+}
+
+impl<const N: usize> PressureHistory<N> {
+    pub const fn new(interval_ms: u32) -> Self {
+        Self {
+            samples: [Pressure::from_q24_8(0); N],
+            next: 0,
+            len: 0,
+            interval_ms,
+            elapsed_ms: 0,
+            sum: 0,
+            count: 0,
+        }
+    }
+
+    /// Time between samples
+    pub fn interval_ms(&self) -> u32 {
+        self.interval_ms
+    }
+
+    /// Number of stored samples
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Add a reading made `elapsed_ms` after the previous one, returns true
+    /// when a new sample is stored, the first reading is stored immediately
+    pub fn update(&mut self, elapsed_ms: u32, pressure: Pressure) -> bool {
+        self.sum += pressure.q24_8() as u64;
+        self.count += 1;
+        self.elapsed_ms = self.elapsed_ms.saturating_add(elapsed_ms);
+
+        if self.len > 0 && self.elapsed_ms < self.interval_ms {
+            return false;
+        }
+
+        let average = (self.sum + self.count as u64/2)/self.count as u64;
+        self.push(Pressure::from_q24_8(average as u32));
+
+        self.elapsed_ms = self.elapsed_ms.saturating_sub(self.interval_ms).min(self.interval_ms);
+        self.sum = 0;
+        self.count = 0;
+        true
+    }
+
+    /// Sample taken `age` samples before the latest one
+    pub fn get(&self, age: usize) -> Option<Pressure> {
+        if age >= self.len || N == 0 {
+            return None;
+        }
+
+        Some(self.samples[(self.next + N - 1 - age) % N])
+    }
+
+    pub fn latest(&self) -> Option<Pressure> {
+        self.get(0)
+    }
+
+    /// Change of the pressure scaled to three hours, compared to the sample
+    /// three hours old, or the oldest one when the history is shorter,
+    /// `None` until the history covers at least an hour
+    pub fn tendency(&self) -> Option<Tendency> {
+        let interval = (self.interval_ms as u64).max(1);
+        let age = ((TENDENCY_MS/interval) as usize).min(self.len.saturating_sub(1));
+        let span_ms = age as u64*interval;
+
+        if span_ms < MIN_TENDENCY_MS {
+            return None;
+        }
+
+        let latest = self.latest()?.q24_8() as i64;
+        let past = self.get(age)?.q24_8() as i64;
+        let change = (latest - past)*TENDENCY_MS as i64/span_ms as i64;
+
+        Some(Tendency::from_pascals((change/256) as i32))
+    }
+
+    fn push(&mut self, pressure: Pressure) {
+        if N == 0 {
+            return;
+        }
+
+        self.samples[self.next] = pressure;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+}
@@ -0,0 +1,29 @@
+#![no_std]
+
+//! Typed measurements of BMP280 and BME280 pressure sensors
+//!
+//! `Barometer` detects the chip and reads compensated `Pressure`,
+//! `Temperature` and `Humidity` (BME280 only) over I2C or SPI using
+//! `I2cRegisters` or `SpiRegisters`, `Altimeter` converts pressure
+//! to the barometric altitude and back to the sea level pressure,
+//! all in fixed point arithmetic.
+//!
+//! `PressureHistory` keeps pressure samples of the last hours, its tendency
+//! together with the sea level pressure gives the Zambretti `Forecast`.
+
+mod altitude;
+mod barometer;
+mod calibration;
+mod config;
+mod forecast;
+mod history;
+mod registers;
+mod units;
+
+pub use altitude::{sea_level_pressure, Altimeter, MAX_ALTITUDE_CM, MIN_ALTITUDE_CM};
+pub use barometer::{detect, Barometer, Chip, Error, Measurement};
+pub use config::{Config, Filter, Oversampling};
+pub use forecast::{Forecast, Weather};
+pub use history::{PressureHistory, Tendency, Trend, STEADY_LIMIT_PA};
+pub use registers::{I2cRegisters, Registers, SpiRegisters, ADDRESS_SDO_GROUNDED, ADDRESS_SDO_HIGH};
+pub use units::{Humidity, Pressure, Temperature};
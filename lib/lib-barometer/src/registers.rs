@@ -0,0 +1,107 @@
+use embedded_hal::blocking::{i2c, spi};
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::barometer::Error;
+
+/// I2C address with the SDO pin connected to ground
+pub const ADDRESS_SDO_GROUNDED: u8 = 0x76;
+
+/// I2C address with the SDO pin connected to the supply voltage
+pub const ADDRESS_SDO_HIGH: u8 = 0x77;
+
+pub(crate) const CALIBRATION: u8 = 0x88;
+pub(crate) const CALIBRATION_H1: u8 = 0xA1;
+pub(crate) const CHIP_ID: u8 = 0xD0;
+pub(crate) const CALIBRATION_H2: u8 = 0xE1;
+pub(crate) const CONTROL_HUMIDITY: u8 = 0xF2;
+pub(crate) const STATUS: u8 = 0xF3;
+pub(crate) const CONTROL_MEASUREMENT: u8 = 0xF4;
+pub(crate) const CONFIG: u8 = 0xF5;
+pub(crate) const DATA: u8 = 0xF7;
+
+/// Register access of the sensor, BMP280 and BME280 have the same
+/// registers on both I2C and SPI
+pub trait Registers {
+    /// Read consecutive registers starting at `register`
+    fn read(&mut self, register: u8, buffer: &mut [u8]) -> Result<(), Error>;
+
+    fn write(&mut self, register: u8, value: u8) -> Result<(), Error>;
+}
+
+/// Sensor connected to I2C, the CSB pin is connected to the supply voltage
+///
+/// This is a cheap operation, so the registers can be constructed whenever
+/// they are needed and the bus stays available to other devices.
+pub struct I2cRegisters<'a, I2C> {
+    i2c: &'a mut I2C,
+    address: u8,
+}
+
+impl<'a, I2C> I2cRegisters<'a, I2C> {
+    pub fn new(i2c: &'a mut I2C, address: u8) -> Self {
+        Self { i2c, address }
+    }
+}
+
+impl<'a, I2C> Registers for I2cRegisters<'a, I2C>
+where
+    I2C: i2c::WriteRead + i2c::Write,
+{
+    fn read(&mut self, register: u8, buffer: &mut [u8]) -> Result<(), Error> {
+        self.i2c.write_read(self.address, &[register], buffer).map_err(|_| Error::Bus)
+    }
+
+    fn write(&mut self, register: u8, value: u8) -> Result<(), Error> {
+        self.i2c.write(self.address, &[register, value]).map_err(|_| Error::Bus)
+    }
+}
+
+/// Sensor connected to a 4-wire SPI (mode 0 or 3) with its own chip
+/// select pin, other devices can share the bus using their own pins
+///
+/// Pulling CSB low switches the sensor to SPI until it is powered off.
+pub struct SpiRegisters<'a, SPI, CS> {
+    spi: &'a mut SPI,
+    cs: &'a mut CS,
+}
+
+impl<'a, SPI, CS> SpiRegisters<'a, SPI, CS> {
+    pub fn new(spi: &'a mut SPI, cs: &'a mut CS) -> Self {
+        Self { spi, cs }
+    }
+}
+
+impl<'a, SPI, CS> SpiRegisters<'a, SPI, CS>
+where
+    CS: OutputPin,
+{
+    fn select<F>(&mut self, action: F) -> Result<(), Error>
+    where
+        F: FnOnce(&mut SPI) -> Result<(), Error>,
+    {
+        self.cs.set_low().map_err(|_| Error::Bus)?;
+        let result = action(self.spi);
+        self.cs.set_high().map_err(|_| Error::Bus)?;
+        result
+    }
+}
+
+impl<'a, SPI, CS> Registers for SpiRegisters<'a, SPI, CS>
+where
+    SPI: spi::Transfer<u8> + spi::Write<u8>,
+    CS: OutputPin,
+{
+    /// The highest address bit set means reading
+    fn read(&mut self, register: u8, buffer: &mut [u8]) -> Result<(), Error> {
+        self.select(|spi| {
+            spi.write(&[register | 0x80]).map_err(|_| Error::Bus)?;
+            buffer.iter_mut().for_each(|byte| *byte = 0);
+            spi.transfer(buffer).map_err(|_| Error::Bus)?;
+            Ok(())
+        })
+    }
+
+    fn write(&mut self, register: u8, value: u8) -> Result<(), Error> {
+        self.select(|spi| spi.write(&[register & 0x7F, value]).map_err(|_| Error::Bus))
+    }
+}
@@ -0,0 +1,168 @@
+use core::fmt;
+
+/// Atmospheric pressure kept in 1/256 Pa, the Q24.8 format the BMP280
+/// compensation formula produces
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Pressure(u32);
+
+impl Pressure {
+    /// Standard atmosphere at the sea level, 1013.25 hPa
+    pub const STANDARD: Pressure = Pressure::from_pascals(101_325);
+
+    /// Pressure in the Q24.8 format, 24 integer and 8 fractional bits,
+    /// the datasheet example value 25767236 is 100653.27 Pa
+    pub const fn from_q24_8(value: u32) -> Self {
+        Self(value)
+    }
+
+    pub const fn from_pascals(pascals: u32) -> Self {
+        Self(pascals.saturating_mul(256))
+    }
+
+    pub const fn from_hectopascals(hectopascals: u32) -> Self {
+        Self::from_pascals(hectopascals.saturating_mul(100))
+    }
+
+    pub const fn q24_8(&self) -> u32 {
+        self.0
+    }
+
+    /// Pressure in whole pascals, rounded
+    pub const fn pascals(&self) -> u32 {
+        ((self.0 as u64 + 128)/256) as u32
+    }
+
+    /// Pressure in hundredths of a pascal, rounded
+    pub const fn centipascals(&self) -> u32 {
+        ((self.0 as u64*100 + 128)/256) as u32
+    }
+}
+
+/// Formatted as hPa with two decimals, `1006.53`
+impl fmt::Display for Pressure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pascals = self.pascals();
+        write!(f, "{}.{:02}", pascals/100, pascals % 100)
+    }
+}
+
+/// Temperature in hundredths of a degree Celsius
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Temperature(i32);
+
+impl Temperature {
+    pub const fn from_centidegrees(centidegrees: i32) -> Self {
+        Self(centidegrees)
+    }
+
+    pub const fn centidegrees(&self) -> i32 {
+        self.0
+    }
+
+    /// Temperature in tenths of a degree Celsius, rounded
+    pub const fn decidegrees(&self) -> i32 {
+        match self.0 >= 0 {
+            true => (self.0 + 5)/10,
+            false => (self.0 - 5)/10,
+        }
+    }
+}
+
+/// Formatted as °C with two decimals, `-0.05` keeps the sign even
+/// when the integer part is zero
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let value = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, value/100, value % 100)
+    }
+}
+
+/// Relative humidity kept in 1/1024 %, the Q22.10 format of the BME280
+/// compensation formula
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Humidity(u32);
+
+impl Humidity {
+    /// Humidity in the Q22.10 format, the value 47445 is 46.333 %
+    pub const fn from_q22_10(value: u32) -> Self {
+        Self(value)
+    }
+
+    pub const fn from_permille(permille: u32) -> Self {
+        Self(((permille as u64*1024 + 5)/10) as u32)
+    }
+
+    pub const fn q22_10(&self) -> u32 {
+        self.0
+    }
+
+    /// Humidity in whole percent, rounded
+    pub const fn percent(&self) -> u32 {
+        ((self.0 as u64 + 512)/1024) as u32
+    }
+
+    /// Humidity in tenths of a percent, rounded
+    pub const fn permille(&self) -> u32 {
+        ((self.0 as u64*10 + 512)/1024) as u32
+    }
+}
+
+/// Formatted as % with one decimal, `46.3`
+impl fmt::Display for Humidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let permille = self.permille();
+        write!(f, "{}.{}", permille/10, permille % 10)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::string::ToString;
+
+    use super::*;
+
+    #[test]
+    fn datasheet_pressure() {
+        let pressure = Pressure::from_q24_8(25767236);
+        assert_eq!(pressure.pascals(), 100653);
+        assert_eq!(pressure.centipascals(), 10065327);
+        assert_eq!(pressure.to_string(), "1006.53");
+    }
+
+    #[test]
+    fn pressure_conversions() {
+        assert_eq!(Pressure::STANDARD.to_string(), "1013.25");
+        assert_eq!(Pressure::from_hectopascals(1000), Pressure::from_pascals(100_000));
+        assert_eq!(Pressure::from_pascals(u32::MAX).q24_8(), u32::MAX);
+        assert_eq!(Pressure::from_pascals(100_005).to_string(), "1000.05");
+    }
+
+    #[test]
+    fn temperature_display() {
+        assert_eq!(Temperature::from_centidegrees(2508).to_string(), "25.08");
+        assert_eq!(Temperature::from_centidegrees(0).to_string(), "0.00");
+        assert_eq!(Temperature::from_centidegrees(-5).to_string(), "-0.05");
+        assert_eq!(Temperature::from_centidegrees(-1234).to_string(), "-12.34");
+        assert_eq!(Temperature::from_centidegrees(-4000).to_string(), "-40.00");
+    }
+
+    #[test]
+    fn temperature_rounds_to_tenths() {
+        assert_eq!(Temperature::from_centidegrees(2508).decidegrees(), 251);
+        assert_eq!(Temperature::from_centidegrees(2504).decidegrees(), 250);
+        assert_eq!(Temperature::from_centidegrees(-5).decidegrees(), -1);
+        assert_eq!(Temperature::from_centidegrees(-4).decidegrees(), 0);
+    }
+
+    #[test]
+    fn humidity() {
+        let humidity = Humidity::from_q22_10(47445);
+        assert_eq!(humidity.percent(), 46);
+        assert_eq!(humidity.permille(), 463);
+        assert_eq!(humidity.to_string(), "46.3");
+        assert_eq!(Humidity::from_permille(463).permille(), 463);
+    }
+}
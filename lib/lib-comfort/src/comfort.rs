@@ -0,0 +1,255 @@
+use crate::fixed::{div_round, exp, ln, sqrt, ONE};
+
+/// `17.62*T/(243.12 + T)`, the exponent of the Magnus formula with
+/// the Sonntag constants, `T` in tenths of a degree
+fn magnus_exponent(decidegrees: i32) -> i64 {
+    let t = decidegrees as i64;
+    ((1762*t) << 16)/(243_120 + 100*t)
+}
+
+/// Temperature the air has to cool down to for the water vapour to
+/// condense, in tenths of a degree Celsius, Magnus formula
+pub fn dew_point(decidegrees: i32, permille: u32) -> i32 {
+    div_round(10*dew_point_q16(decidegrees, permille), ONE) as i32
+}
+
+/// Dew point in degrees Celsius with 16 fractional bits
+fn dew_point_q16(decidegrees: i32, permille: u32) -> i64 {
+    let permille = permille.clamp(1, 1000) as i64;
+    let gamma = ln((permille << 16)/1000) + magnus_exponent(decidegrees);
+
+    // 243.12*gamma/(17.62 - gamma)
+    div_round(24_312*gamma*ONE, 1762*ONE - 100*gamma)
+}
+
+/// Temperature as felt in hot humid weather, in tenths of a degree
+/// Celsius, the algorithm of the US National Weather Service
+///
+/// The Rothfusz regression is used from 80 F (26.7 C), the simple formula
+/// below, which is close to the temperature itself.
+pub fn heat_index(decidegrees: i32, permille: u32) -> i32 {
+    // Hundredths of a degree Fahrenheit, exact, and tenths of a percent
+    let t = decidegrees as i64*18 + 3200;
+    let r = permille.min(1000) as i64;
+
+    // Index is computed in 10^-8 F, the terms of T^a*RH^b are scaled
+    // down by 100^a*10^b
+    let simple = (t*1_000_000 + 6_100_000_000 + (t - 6800)*1_200_000 + r*940_000)/2;
+
+    let index = match (simple + t*1_000_000)/2 < 8_000_000_000 {
+        true => simple,
+        false => {
+            let rothfusz = -4_237_900_000
+                + 204_901_523*t/100
+                + 1_014_333_127*r/10
+                - 22_475_541*t*r/1000
+                - 683_783*t*t/10_000
+                - 5_481_717*r*r/100
+                + 122_874*t*t*r/100_000
+                + 85_282*t*r*r/10_000
+                - 199*t*t*r*r/1_000_000;
+
+            let adjustment = match (r, t) {
+                // Dry air, ((13 - RH)/4)*sqrt((17 - |T - 95|)/17)
+                (r, t) if r < 130 && (8000..=11_200).contains(&t) => {
+                    let root = sqrt((1700 - (t - 9500).abs())*100_000_000/1700);
+                    -(130 - r)*root*250
+                }
+                // Humid air, ((RH - 85)/10)*((87 - T)/5)
+                (r, t) if r > 850 && (8000..=8700).contains(&t) => (r - 850)*(8700 - t)*2000,
+                _ => 0,
+            };
+
+            rothfusz + adjustment
+        }
+    };
+
+    div_round((index - 3_200_000_000)*5, 90_000_000) as i32
+}
+
+/// Canadian humidex, temperature in tenths of a degree Celsius increased
+/// by the water vapour pressure computed from the dew point
+pub fn humidex(decidegrees: i32, permille: u32) -> i32 {
+    let dew_point = dew_point_q16(decidegrees, permille);
+
+    // 5417.7530*(1/273.16 - 1/(273.15 + Td)), vapour pressure in hPa is
+    // 6.11*e^x
+    let x = div_round(5_417_753*ONE, 273_160)
+        - div_round(5_417_753*ONE*ONE, 10*(27_315*ONE + 100*dew_point));
+    let vapour = 611*exp(x)/100;
+
+    // 0.5555*(e - 10)
+    decidegrees + div_round(5555*(vapour - 10*ONE)/1000, ONE) as i32
+}
+
+/// Mass of water vapour in a cubic meter of air, in milligrams
+pub fn absolute_humidity(decidegrees: i32, permille: u32) -> u32 {
+    // Saturation vapour pressure in hPa is 6.112*e^x, the actual one
+    // is the relative humidity of it
+    let saturation = 6112*exp(magnus_exponent(decidegrees))/1000;
+    let vapour = saturation*permille.min(1000) as i64/1000;
+
+    // 216.7*e/(273.15 + T) in g/m3
+    div_round(21_670_000*vapour/(27_315 + 10*decidegrees as i64), ONE).max(0) as u32
+}
+
+/// All metrics computed from a single measurement
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Comfort {
+    /// Tenths of a degree Celsius
+    pub dew_point: i32,
+    /// Tenths of a degree Celsius
+    pub heat_index: i32,
+    /// Tenths of a degree Celsius
+    pub humidex: i32,
+    /// Milligrams per cubic meter
+    pub absolute_humidity: u32,
+}
+
+impl Comfort {
+    pub fn new(decidegrees: i32, permille: u32) -> Self {
+        Self {
+            dew_point: dew_point(decidegrees, permille),
+            heat_index: heat_index(decidegrees, permille),
+            humidex: humidex(decidegrees, permille),
+            absolute_humidity: absolute_humidity(decidegrees, permille),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Temperature in degrees Celsius and relative humidity in percent
+    fn float_dew_point(t: f64, rh: f64) -> f64 {
+        let gamma = (rh.clamp(0.1, 100.0)/100.0).ln() + 17.62*t/(243.12 + t);
+        243.12*gamma/(17.62 - gamma)
+    }
+
+    fn float_heat_index(t: f64, rh: f64) -> f64 {
+        let t = t*1.8 + 32.0;
+        let simple = 0.5*(t + 61.0 + (t - 68.0)*1.2 + rh*0.094);
+
+        let index = match (simple + t)/2.0 < 80.0 {
+            true => simple,
+            false => {
+                let rothfusz = -42.379 + 2.04901523*t + 10.14333127*rh - 0.22475541*t*rh
+                    - 0.00683783*t*t - 0.05481717*rh*rh + 0.00122874*t*t*rh
+                    + 0.00085282*t*rh*rh - 0.00000199*t*t*rh*rh;
+
+                let adjustment = match (rh, t) {
+                    (rh, t) if rh < 13.0 && (80.0..=112.0).contains(&t) => {
+                        -(13.0 - rh)/4.0*((17.0 - (t - 95.0).abs())/17.0).sqrt()
+                    }
+                    (rh, t) if rh > 85.0 && (80.0..=87.0).contains(&t) => (rh - 85.0)/10.0*(87.0 - t)/5.0,
+                    _ => 0.0,
+                };
+
+                rothfusz + adjustment
+            }
+        };
+
+        (index - 32.0)/1.8
+    }
+
+    fn float_humidex(t: f64, rh: f64) -> f64 {
+        let dew_point = float_dew_point(t, rh);
+        let vapour = 6.11*(5417.7530*(1.0/273.16 - 1.0/(273.15 + dew_point))).exp();
+        t + 0.5555*(vapour - 10.0)
+    }
+
+    /// Grams per cubic meter
+    fn float_absolute_humidity(t: f64, rh: f64) -> f64 {
+        let saturation = 6.112*(17.62*t/(243.12 + t)).exp();
+        216.7*saturation*rh/100.0/(273.15 + t)
+    }
+
+    fn celsius(decidegrees: i32) -> f64 {
+        decidegrees as f64/10.0
+    }
+
+    /// Rows of the table in the documentation, the last is 90 F and 60 %
+    #[test]
+    fn documented_table() {
+        let rows = [
+            (200, 500, Comfort { dew_point: 93, heat_index: 194, humidex: 209, absolute_humidity: 8600 }),
+            (300, 700, Comfort { dew_point: 239, heat_index: 350, humidex: 412, absolute_humidity: 21200 }),
+            (322, 600, Comfort { dew_point: 234, heat_index: 375, humidex: 429, absolute_humidity: 20400 }),
+        ];
+
+        for (decidegrees, permille, expected) in rows {
+            let mut comfort = Comfort::new(decidegrees, permille);
+            comfort.absolute_humidity = div_round(comfort.absolute_humidity as i64, 100) as u32*100;
+            assert_eq!(comfort, expected);
+        }
+    }
+
+    #[test]
+    fn published_tables() {
+        // NWS heat index table, 90 F and 60 % feels like 100 F
+        let fahrenheit = div_round(heat_index(322, 600) as i64*18 + 3200, 100);
+        assert_eq!(fahrenheit, 100);
+
+        // Humidex table, 30 C and 70 % is 41
+        assert_eq!(div_round(humidex(300, 700) as i64, 10), 41);
+    }
+
+    #[test]
+    fn humidity_is_clamped() {
+        assert_eq!(dew_point(200, 0), dew_point(200, 1));
+        assert_eq!(dew_point(200, 1000), 200);
+        assert_eq!(dew_point(200, 1500), 200);
+        assert_eq!(heat_index(350, 1500), heat_index(350, 1000));
+        assert_eq!(absolute_humidity(200, 0), 0);
+        assert_eq!(absolute_humidity(200, 1500), absolute_humidity(200, 1000));
+    }
+
+    #[test]
+    fn cold_air() {
+        let (t, rh) = (-400, 500);
+        assert!((celsius(dew_point(t, rh)) - float_dew_point(-40.0, 50.0)).abs() <= 0.06);
+        assert!((celsius(humidex(t, rh)) - float_humidex(-40.0, 50.0)).abs() <= 0.06);
+        assert!((absolute_humidity(t, rh) as f64/1000.0 - float_absolute_humidity(-40.0, 50.0)).abs() < 0.01);
+        assert!((celsius(heat_index(t, rh)) - float_heat_index(-40.0, 50.0)).abs() <= 0.06);
+    }
+
+    #[test]
+    fn rothfusz_adjustments() {
+        // Dry air from 80 F to 112 F, 95 F and 10 % is lowered by 0.75 F
+        let dry = heat_index(350, 100);
+        assert!((celsius(dry) - float_heat_index(35.0, 10.0)).abs() <= 0.06, "{}", dry);
+
+        // Humid air from 80 F to 87 F, 84 F (28.9 C) and 95 % is raised by 0.6 F
+        let humid = heat_index(289, 950);
+        assert!((celsius(humid) - float_heat_index(28.9, 95.0)).abs() <= 0.06, "{}", humid);
+
+        // Both adjustments are left out just outside their ranges
+        assert!((celsius(heat_index(450, 100)) - float_heat_index(45.0, 10.0)).abs() <= 0.06);
+        assert!((celsius(heat_index(320, 950)) - float_heat_index(32.0, 95.0)).abs() <= 0.06);
+    }
+
+    #[test]
+    fn matches_float_formulas() {
+        for decidegrees in (-400..=600).step_by(5) {
+            for permille in (10..=1000).step_by(10) {
+                let (t, rh) = (celsius(decidegrees), permille as f64/10.0);
+
+                let errors = [
+                    celsius(dew_point(decidegrees, permille)) - float_dew_point(t, rh),
+                    celsius(heat_index(decidegrees, permille)) - float_heat_index(t, rh),
+                    celsius(humidex(decidegrees, permille)) - float_humidex(t, rh),
+                ];
+
+                // Rounding to a tenth and 0.01 C of the fixed point arithmetic
+                for error in errors {
+                    assert!(error.abs() <= 0.06, "{} {} {:?}", decidegrees, permille, errors);
+                }
+
+                let water = absolute_humidity(decidegrees, permille) as f64/1000.0;
+                let expected = float_absolute_humidity(t, rh);
+                assert!((water - expected).abs() <= 0.001 + expected*1e-4, "{} {} {}", decidegrees, permille, water);
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+/// Fixed point format of the arguments and results, 16 fractional bits
+pub const ONE: i64 = 1 << 16;
+
+/// `ln(2)` with 32 fractional bits
+const LN2_Q32: i64 = 2_977_044_472;
+
+/// Natural logarithm of a positive `x`, both with 16 fractional bits
+pub fn ln(x: i64) -> i64 {
+    // x = m*2^exponent, where m is in [1, 2) with 30 fractional bits
+    let msb = 63 - x.leading_zeros() as i64;
+    let exponent = msb - 16;
+    let m = match msb >= 30 {
+        true => x >> (msb - 30),
+        false => x << (30 - msb),
+    };
+
+    // ln(m) = 2*atanh(s), s = (m - 1)/(m + 1) is at most 1/3
+    let one = 1_i64 << 30;
+    let s = ((m - one) << 30)/(m + one);
+    let s2 = (s*s) >> 30;
+
+    let mut term = s;
+    let mut sum = 0;
+
+    for n in (1..=15).step_by(2) {
+        sum += term/n;
+        term = (term*s2) >> 30;
+    }
+
+    div_round((sum << 3) + exponent*LN2_Q32, 1 << 16)
+}
+
+/// Exponential function, the argument and the result with 16 fractional bits
+pub fn exp(x: i64) -> i64 {
+    // e^x = 2^k*e^r, r is at most ln(2)/2
+    let x = x << 16;
+    let k = div_round(x, LN2_Q32);
+    let r = x - k*LN2_Q32;
+
+    let mut term = 1_i64 << 32;
+    let mut sum = term;
+
+    for n in 1..=10 {
+        term = ((term*r) >> 32)/n;
+        sum += term;
+    }
+
+    match k {
+        k if k >= 16 => sum << (k - 16),
+        k if k > -48 => div_round(sum, 1 << (16 - k)),
+        _ => 0,
+    }
+}
+
+/// Integer square root, rounded down
+pub fn sqrt(value: i64) -> i64 {
+    if value <= 0 {
+        return 0;
+    }
+
+    let mut root = 0;
+    let mut bit = 1_i64 << ((63 - value.leading_zeros()) & !1);
+    let mut rest = value;
+
+    while bit != 0 {
+        if rest >= root + bit {
+            rest -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+
+        bit >>= 2;
+    }
+
+    root
+}
+
+/// Division rounding to the nearest, `divisor` has to be positive
+pub fn div_round(value: i64, divisor: i64) -> i64 {
+    match value >= 0 {
+        true => (value + divisor/2)/divisor,
+        false => (value - divisor/2)/divisor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn to_fixed(value: f64) -> i64 {
+        (value*ONE as f64).round() as i64
+    }
+
+    fn to_float(value: i64) -> f64 {
+        value as f64/ONE as f64
+    }
+
+    #[test]
+    fn ln_matches_float() {
+        for x in [1, 2, 100, 655, 6554, 32768, 65535, 65536, 65537, 100_000, 1 << 20, 1 << 40, i64::MAX] {
+            let error = to_float(ln(x)) - (x as f64/ONE as f64).ln();
+            assert!(error.abs() < 2.0/ONE as f64, "{} {}", x, error);
+        }
+
+        assert_eq!(ln(ONE), 0);
+    }
+
+    #[test]
+    fn exp_matches_float() {
+        for x in (-2000..=1400).map(|x| x as f64/100.0) {
+            let expected = x.exp();
+            let error = to_float(exp(to_fixed(x))) - expected;
+
+            // Relative error for large results, the resolution of the format for small
+            assert!(error.abs() < 1e-5*expected + 1.0/ONE as f64, "{} {}", x, error);
+        }
+
+        assert_eq!(exp(0), ONE);
+        assert_eq!(exp(-40*ONE), 0);
+    }
+
+    #[test]
+    fn exp_inverts_ln() {
+        for x in [ONE/100, ONE/2, ONE, 3*ONE, 1000*ONE] {
+            assert!((exp(ln(x)) - x).abs() <= x/10_000 + 1, "{}", x);
+        }
+    }
+
+    #[test]
+    fn sqrt_rounds_down() {
+        for value in (0..100_000).chain([i64::MAX, (1 << 62) - 1, 1 << 62]) {
+            let root = sqrt(value);
+            assert!(root*root <= value, "{}", value);
+            assert!((root + 1).checked_mul(root + 1).is_none_or(|square| square > value), "{}", value);
+        }
+
+        assert_eq!(sqrt(-5), 0);
+    }
+
+    #[test]
+    fn div_round_is_symmetric() {
+        assert_eq!(div_round(15, 10), 2);
+        assert_eq!(div_round(14, 10), 1);
+        assert_eq!(div_round(-15, 10), -2);
+        assert_eq!(div_round(-14, 10), -1);
+    }
+}
@@ -0,0 +1,13 @@
+#![no_std]
+
+//! Comfort metrics derived from temperature and relative humidity
+//!
+//! Dew point, heat index, humidex and absolute humidity computed in fixed
+//! point arithmetic from the temperature in tenths of a degree Celsius and
+//! the relative humidity in tenths of a percent, the units of DHT sensors,
+//! BMP280 and BME280 values convert by `decidegrees` and `permille`.
+
+mod comfort;
+mod fixed;
+
+pub use comfort::{absolute_humidity, dew_point, heat_index, humidex, Comfort};
@@ -0,0 +1,173 @@
+use core::convert::Infallible;
+
+use embedded_hal::digital::v2::OutputPin;
+use embedded_hal::PwmPin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+/// Output stage of a single motor
+pub trait HBridge {
+    type Error;
+
+    /// Largest duty accepted by `drive`
+    fn max_duty(&self) -> u16;
+
+    /// Drive the motor in `direction` with `duty`
+    fn drive(&mut self, direction: Direction, duty: u16) -> Result<(), Self::Error>;
+
+    /// Disconnect the motor, it spins freely until it stops
+    fn coast(&mut self) -> Result<(), Self::Error>;
+
+    /// Short the motor terminals, the motor stops quickly
+    fn brake(&mut self) -> Result<(), Self::Error>;
+}
+
+/// Driver with two PWM inputs, one for each half bridge
+/// (DRV8833, L9110, L298N with enable tied high)
+pub struct DualPwm<A, B> {
+    a: A,
+    b: B,
+}
+
+impl<A, B> DualPwm<A, B>
+where
+    A: PwmPin<Duty = u16>,
+    B: PwmPin<Duty = u16>,
+{
+    /// Both channels are expected to be enabled and to share the timer
+    pub fn new(a: A, b: B) -> Self {
+        let mut bridge = Self { a, b };
+        let _ = bridge.coast();
+        bridge
+    }
+
+    pub fn release(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<A, B> HBridge for DualPwm<A, B>
+where
+    A: PwmPin<Duty = u16>,
+    B: PwmPin<Duty = u16>,
+{
+    type Error = Infallible;
+
+    fn max_duty(&self) -> u16 {
+        self.a.get_max_duty().min(self.b.get_max_duty())
+    }
+
+    fn drive(&mut self, direction: Direction, duty: u16) -> Result<(), Self::Error> {
+        let duty = duty.min(self.max_duty());
+
+        // The other input stays low, so the motor coasts during the off time
+        match direction {
+            Direction::Forward => {
+                self.b.set_duty(0);
+                self.a.set_duty(duty);
+            }
+            Direction::Reverse => {
+                self.a.set_duty(0);
+                self.b.set_duty(duty);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn coast(&mut self) -> Result<(), Self::Error> {
+        self.a.set_duty(0);
+        self.b.set_duty(0);
+        Ok(())
+    }
+
+    fn brake(&mut self) -> Result<(), Self::Error> {
+        self.a.set_duty(self.a.get_max_duty());
+        self.b.set_duty(self.b.get_max_duty());
+        Ok(())
+    }
+}
+
+/// Driver with a PWM speed input and two direction inputs
+/// (TB6612, L298N using the enable input for PWM)
+pub struct PwmDirection<P, IN1, IN2> {
+    pwm: P,
+    in1: IN1,
+    in2: IN2,
+    /// Direction set on the inputs, `None` while coasting or braking
+    direction: Option<Direction>,
+}
+
+impl<P, IN1, IN2, E> PwmDirection<P, IN1, IN2>
+where
+    P: PwmPin<Duty = u16>,
+    IN1: OutputPin<Error = E>,
+    IN2: OutputPin<Error = E>,
+{
+    /// PWM channel is expected to be enabled, the motor starts coasting
+    pub fn new(pwm: P, in1: IN1, in2: IN2) -> Result<Self, E> {
+        let mut bridge = Self { pwm, in1, in2, direction: None };
+        bridge.coast()?;
+        Ok(bridge)
+    }
+
+    pub fn release(self) -> (P, IN1, IN2) {
+        (self.pwm, self.in1, self.in2)
+    }
+}
+
+impl<P, IN1, IN2, E> HBridge for PwmDirection<P, IN1, IN2>
+where
+    P: PwmPin<Duty = u16>,
+    IN1: OutputPin<Error = E>,
+    IN2: OutputPin<Error = E>,
+{
+    type Error = E;
+
+    fn max_duty(&self) -> u16 {
+        self.pwm.get_max_duty()
+    }
+
+    fn drive(&mut self, direction: Direction, duty: u16) -> Result<(), Self::Error> {
+        if self.direction != Some(direction) {
+            // Drop the duty first, so the bridge never drives the old duty
+            // in the new direction
+            self.pwm.set_duty(0);
+
+            match direction {
+                Direction::Forward => {
+                    self.in2.set_low()?;
+                    self.in1.set_high()?;
+                }
+                Direction::Reverse => {
+                    self.in1.set_low()?;
+                    self.in2.set_high()?;
+                }
+            }
+
+            self.direction = Some(direction);
+        }
+
+        self.pwm.set_duty(duty.min(self.max_duty()));
+        Ok(())
+    }
+
+    fn coast(&mut self) -> Result<(), Self::Error> {
+        self.direction = None;
+        self.pwm.set_duty(0);
+        self.in1.set_low()?;
+        self.in2.set_low()
+    }
+
+    fn brake(&mut self) -> Result<(), Self::Error> {
+        self.direction = None;
+        self.in1.set_high()?;
+        self.in2.set_high()?;
+        self.pwm.set_duty(self.pwm.get_max_duty());
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+#![no_std]
+
+//! DC motor control through common H-bridge drivers
+//!
+//! `Motor` ramps the speed with limited acceleration, passes direction
+//! changes through zero with a dead time and stops either by coasting
+//! or by braking. The bridge itself is hidden behind the `HBridge` trait
+//! implemented for drivers with two PWM inputs and drivers with a PWM
+//! enable input and two direction inputs.
+
+mod bridge;
+mod motor;
+
+pub use bridge::{Direction, DualPwm, HBridge, PwmDirection};
+pub use motor::{Config, Motor, StopMode, MAX_SPEED};
@@ -0,0 +1,160 @@
+use crate::bridge::{Direction, HBridge};
+
+/// Full speed in both directions, speeds are in per mille of the full duty
+pub const MAX_SPEED: i16 = 1000;
+
+/// Internal speed is kept in thousandths of the speed unit, so that
+/// small accelerations accumulate over short update periods
+const SCALE: i32 = 1000;
+
+/// What happens when the motor reaches zero speed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StopMode {
+    Coast,
+    Brake,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Speed increase per second in per mille of the full speed,
+    /// zero means no limit
+    pub acceleration: u16,
+    /// Speed decrease per second, zero means no limit
+    pub deceleration: u16,
+    /// Time the motor coasts at zero speed before changing direction
+    pub dead_time_ms: u32,
+    pub stop_mode: StopMode,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { acceleration: 500, deceleration: 1000, dead_time_ms: 100, stop_mode: StopMode::Coast }
+    }
+}
+
+/// DC motor with speed ramps, advanced by periodic `update` calls
+pub struct Motor<B> {
+    bridge: B,
+    config: Config,
+    speed: i32,
+    target: i16,
+    dead_time_ms: u32,
+}
+
+impl<B: HBridge> Motor<B> {
+    pub fn new(bridge: B, config: Config) -> Self {
+        Self { bridge, config, speed: 0, target: 0, dead_time_ms: 0 }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /// Set the target speed from -1000 (full reverse) to 1000 (full forward),
+    /// the motor ramps to it during the following updates
+    pub fn set_speed(&mut self, speed: i16) {
+        self.target = speed.clamp(-MAX_SPEED, MAX_SPEED);
+    }
+
+    /// Ramp down to zero, then stop using the configured stop mode
+    pub fn stop(&mut self) {
+        self.target = 0;
+    }
+
+    /// Stop immediately by shorting the motor
+    pub fn brake(&mut self) -> Result<(), B::Error> {
+        self.target = 0;
+        self.speed = 0;
+        self.bridge.brake()
+    }
+
+    /// Release the motor immediately, it spins freely until it stops
+    pub fn coast(&mut self) -> Result<(), B::Error> {
+        self.target = 0;
+        self.speed = 0;
+        self.bridge.coast()
+    }
+
+    /// Current speed from -1000 to 1000
+    pub fn speed(&self) -> i16 {
+        (self.speed/SCALE) as i16
+    }
+
+    pub fn target(&self) -> i16 {
+        self.target
+    }
+
+    /// True when the target speed is reached
+    pub fn is_settled(&self) -> bool {
+        self.speed == self.target as i32*SCALE && self.dead_time_ms == 0
+    }
+
+    /// Advance ramps by `elapsed_ms` and update the bridge outputs
+    pub fn update(&mut self, elapsed_ms: u32) -> Result<(), B::Error> {
+        if self.dead_time_ms > 0 {
+            self.dead_time_ms = self.dead_time_ms.saturating_sub(elapsed_ms);
+            return self.bridge.coast();
+        }
+
+        let target = self.target as i32*SCALE;
+        let reversing = self.speed != 0 && target.signum() != self.speed.signum();
+
+        if reversing || target.abs() < self.speed.abs() {
+            // Slow down, towards zero first when changing the direction
+            let limit = if reversing { 0 } else { target };
+            let step = step(self.config.deceleration, elapsed_ms);
+
+            self.speed = match self.speed > limit {
+                true => self.speed.saturating_sub(step).max(limit),
+                false => self.speed.saturating_add(step).min(limit),
+            };
+
+            if reversing && self.speed == 0 && target != 0 {
+                self.dead_time_ms = self.config.dead_time_ms;
+            }
+        } else {
+            let step = step(self.config.acceleration, elapsed_ms);
+
+            self.speed = match self.speed < target {
+                true => self.speed.saturating_add(step).min(target),
+                false => self.speed.saturating_sub(step).max(target),
+            };
+        }
+
+        self.apply()
+    }
+
+    pub fn release(self) -> B {
+        self.bridge
+    }
+
+    fn apply(&mut self) -> Result<(), B::Error> {
+        if self.speed == 0 {
+            return match (self.target, self.dead_time_ms, self.config.stop_mode) {
+                (0, 0, StopMode::Brake) => self.bridge.brake(),
+                _ => self.bridge.coast(),
+            };
+        }
+
+        let direction = match self.speed > 0 {
+            true => Direction::Forward,
+            false => Direction::Reverse,
+        };
+
+        let max_duty = self.bridge.max_duty() as i64;
+        let duty = self.speed.abs() as i64*max_duty/(MAX_SPEED as i64*SCALE as i64);
+        self.bridge.drive(direction, duty as u16)
+    }
+}
+
+/// Speed change over `elapsed_ms` at `rate` per mille per second
+fn step(rate: u16, elapsed_ms: u32) -> i32 {
+    match rate {
+        0 => i32::MAX,
+        rate => (rate as u32).saturating_mul(elapsed_ms).min(i32::MAX as u32) as i32,
+    }
+}
@@ -0,0 +1,143 @@
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+use stm32f1xx_hal::afio::MAPR;
+use stm32f1xx_hal::dma::dma1::C6;
+use stm32f1xx_hal::pac::TIM3;
+use stm32f1xx_hal::rcc::{BusTimerClock, Clocks};
+use stm32f1xx_hal::timer::{CPin, Remap, Timer, C2};
+
+use crate::frame::{Frame, Model};
+use crate::sensor::{decode, Error, Sensor};
+use crate::units::Measurement;
+
+/// Start signal, the response and 40 bits, with a few spare edges
+/// for glitches
+const EDGES: usize = 48;
+
+/// Whole frame takes at most 5 ms after the start signal
+const FRAME_MS: u16 = 6;
+
+/// DHT sensor with its falling edges captured by TIM3 and stored by DMA
+///
+/// The line is connected to the second channel of TIM3 (`PA7`, or `PB5`
+/// with the partial remap), the first channel captures its falling edges
+/// with 1 us resolution and its DMA channel 6 of DMA1 stores them. Nothing
+/// is timed by the CPU, so the frame is decoded correctly even when
+/// interrupts run during the transfer.
+pub struct DhtCapture<PIN> {
+    tim: TIM3,
+    pin: PIN,
+    dma: C6,
+    model: Option<Model>,
+    edges: [u16; EDGES],
+}
+
+impl<PIN> DhtCapture<PIN>
+where
+    PIN: InputPin + OutputPin,
+{
+    /// Sensor of an unknown model on `pin`, an open drain output, it is
+    /// detected from the first frame
+    pub fn new<REMAP>(tim: TIM3, pin: PIN, dma: C6, mapr: &mut MAPR, clocks: &Clocks) -> Self
+    where
+        REMAP: Remap<Periph = TIM3>,
+        PIN: CPin<REMAP, C2>,
+    {
+        REMAP::remap(mapr);
+
+        let clock_hz = TIM3::timer_clock(clocks).raw();
+
+        // Enable and reset the timer
+        let tim = Timer::new(tim, clocks).release();
+
+        // Free running counter with 1 us ticks
+        tim.psc.write(|w| w.psc().bits((clock_hz/1_000_000 - 1) as u16));
+        tim.arr.write(|w| w.arr().bits(u16::MAX));
+        tim.egr.write(|w| w.ug().set_bit());
+
+        // First channel captures falling edges of TI2, filtered over
+        // 8 timer clocks, and requests DMA on every capture
+        tim.ccmr1_input().modify(|_, w| w.cc1s().ti2().ic1f().fck_int_n8());
+        tim.ccer.modify(|_, w| w.cc1p().set_bit());
+        tim.dier.modify(|_, w| w.cc1de().set_bit());
+        tim.cr1.modify(|_, w| w.cen().set_bit());
+
+        let mut capture = Self { tim, pin, dma, model: None, edges: [0; EDGES] };
+
+        let address = &capture.tim.ccr1 as *const _ as u32;
+        capture.dma.set_peripheral_address(address, false);
+        capture.dma.ch().cr.modify(|_, w| w.dir().from_peripheral().psize().bits16().msize().bits16());
+        capture
+    }
+
+    pub fn with_model(self, model: Model) -> Self {
+        Self { model: Some(model), ..self }
+    }
+
+    /// Model given or detected, `None` until the first valid frame
+    pub fn model(&self) -> Option<Model> {
+        self.model
+    }
+
+    /// Stop the timer and give back the timer, the pin and the DMA channel
+    pub fn release(self) -> (TIM3, PIN, C6) {
+        self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+        (self.tim, self.pin, self.dma)
+    }
+
+    /// Start a measurement and decode the answer
+    pub fn read<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<Measurement, Error> {
+        let frame = self.read_frame(delay)?;
+        decode(&frame, &mut self.model)
+    }
+
+    /// Start a measurement and decode the captured edges into a frame,
+    /// its checksum is not checked
+    pub fn read_frame<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<Frame, Error> {
+        // DHT11 start signal is long enough for both models
+        let start_ms = self.model.unwrap_or(Model::Dht11).start_ms();
+
+        let address = self.edges.as_mut_ptr() as u32;
+        self.dma.set_memory_address(address, true);
+        self.dma.set_transfer_length(EDGES);
+
+        compiler_fence(Ordering::Release);
+        self.dma.start();
+        self.tim.ccer.modify(|_, w| w.cc1e().set_bit());
+
+        // Falling edge of the start signal is captured too, it is skipped
+        // by the decoding
+        let started = self.pin.set_low().and_then(|_| {
+            delay.delay_ms(start_ms);
+            self.pin.set_high()
+        });
+
+        if started.is_ok() {
+            delay.delay_ms(FRAME_MS);
+        }
+
+        self.tim.ccer.modify(|_, w| w.cc1e().clear_bit());
+        self.dma.stop();
+        compiler_fence(Ordering::Acquire);
+
+        started.map_err(|_| Error::Gpio)?;
+
+        let captured = EDGES - self.dma.get_ndtr() as usize;
+        Frame::from_falling_edges(&self.edges[..captured])
+    }
+}
+
+impl<PIN> Sensor for DhtCapture<PIN>
+where
+    PIN: InputPin + OutputPin,
+{
+    fn model(&self) -> Option<Model> {
+        DhtCapture::model(self)
+    }
+
+    fn read<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<Measurement, Error> {
+        DhtCapture::read(self, delay)
+    }
+}
@@ -0,0 +1,260 @@
+use core::ops::RangeInclusive;
+
+use crate::sensor::Error;
+use crate::units::{Humidity, Measurement, Temperature};
+
+/// Response of the sensor is 80 us low and 80 us high
+const RESPONSE_US: RangeInclusive<u16> = 140..=220;
+
+/// Every bit is 50 us low followed by 26 - 28 us high for zero
+/// and 70 us high for one
+const BIT_US: RangeInclusive<u16> = 50..=139;
+const ONE_THRESHOLD_US: u16 = 100;
+
+/// Sensors differing in the format of the data they send
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Model {
+    /// Integer humidity, temperature with one decimal on newer revisions
+    Dht11,
+    /// DHT22, AM2302 and AM2301, humidity and temperature in tenths,
+    /// the highest temperature bit is the sign
+    Dht22,
+}
+
+impl Model {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Model::Dht11 => "DHT11",
+            Model::Dht22 => "DHT22",
+        }
+    }
+
+    /// How long the line is held low to start a measurement, DHT11 needs
+    /// at least 18 ms, DHT22 wakes up after 1 ms
+    pub fn start_ms(&self) -> u16 {
+        match self {
+            Model::Dht11 => 20,
+            Model::Dht22 => 2,
+        }
+    }
+
+    /// Shortest time between two reads, the sensor answers faster reads
+    /// with the previous values or not at all
+    pub fn min_interval_ms(&self) -> u32 {
+        match self {
+            Model::Dht11 => 1000,
+            Model::Dht22 => 2000,
+        }
+    }
+}
+
+/// 40 bits sent by the sensor, two bytes of humidity, two bytes of
+/// temperature and a checksum
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Frame([u8; 5]);
+
+impl Frame {
+    pub const fn new(data: [u8; 5]) -> Self {
+        Self(data)
+    }
+
+    /// Decode the times of falling edges of the line in microseconds
+    ///
+    /// Each bit is given by the period between two falling edges, the
+    /// first bit follows the period of the response. Edges before the
+    /// response, like the start signal, are skipped.
+    pub fn from_falling_edges(edges: &[u16]) -> Result<Self, Error> {
+        let period = |index: usize| edges[index + 1].wrapping_sub(edges[index]);
+        let periods = edges.len().saturating_sub(1);
+
+        let response = (0..periods)
+            .find(|&index| RESPONSE_US.contains(&period(index)))
+            .ok_or(Error::NoResponse)?;
+
+        if periods < response + 41 {
+            return Err(Error::Timeout);
+        }
+
+        let mut data = [0u8; 5];
+
+        for bit in 0..40 {
+            let period = period(response + 1 + bit);
+
+            if !BIT_US.contains(&period) {
+                return Err(Error::Timing);
+            }
+
+            data[bit/8] <<= 1;
+            if period > ONE_THRESHOLD_US {
+                data[bit/8] |= 1;
+            }
+        }
+
+        Ok(Self(data))
+    }
+
+    pub fn data(&self) -> &[u8; 5] {
+        &self.0
+    }
+
+    /// Last byte is the lowest byte of the sum of the others
+    pub fn is_valid(&self) -> bool {
+        let sum = self.0[..4].iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        sum == self.0[4]
+    }
+
+    /// Model that sent the frame
+    ///
+    /// DHT22 humidity is at most 1000 tenths of a percent, so its first
+    /// byte is at most 3, DHT11 sends integer percents in the first byte
+    /// and does not measure below 5 %.
+    pub fn model(&self) -> Model {
+        match self.0[0] {
+            0..=3 => Model::Dht22,
+            _ => Model::Dht11,
+        }
+    }
+
+    pub fn measurement(&self, model: Model) -> Measurement {
+        let [h1, h0, t1, t0, _] = self.0;
+
+        let (humidity, temperature, negative) = match model {
+            // Temperature decimal is below 10, its highest bit is the sign
+            Model::Dht11 => (
+                h1 as u16*10 + h0 as u16,
+                t1 as i16*10 + (t0 & 0x7F) as i16,
+                t0 & 0x80 != 0,
+            ),
+            Model::Dht22 => (
+                u16::from_be_bytes([h1, h0]),
+                i16::from_be_bytes([t1 & 0x7F, t0]),
+                t1 & 0x80 != 0,
+            ),
+        };
+
+        Measurement {
+            temperature: Temperature::from_decidegrees(if negative { -temperature } else { temperature }),
+            humidity: Humidity::from_permille(humidity),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// Data with the checksum in the last byte
+    fn with_checksum(data: [u8; 4]) -> [u8; 5] {
+        let sum = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        [data[0], data[1], data[2], data[3], sum]
+    }
+
+    /// Falling edges of a whole read starting at `start`, the start signal,
+    /// the response and 40 bits, zero bits 76 us and one bits 120 us long
+    fn edges(data: [u8; 5], start: u16, start_ms: u16) -> Vec<u16> {
+        let mut time = start;
+        let mut edges = [time].to_vec();
+
+        let mut push = |period: u16| {
+            time = time.wrapping_add(period);
+            edges.push(time);
+        };
+
+        push(start_ms*1000 + 30);
+        push(160);
+
+        for bit in 0..40 {
+            let one = data[bit/8] & (0x80 >> (bit % 8)) != 0;
+            push(if one { 120 } else { 76 });
+        }
+
+        edges
+    }
+
+    #[test]
+    fn dht11_frame() {
+        let data = with_checksum([45, 0, 23, 5]);
+        let frame = Frame::from_falling_edges(&edges(data, 1000, 20)).unwrap();
+
+        assert_eq!(frame.data(), &data);
+        assert!(frame.is_valid());
+        assert_eq!(frame.model(), Model::Dht11);
+
+        let measurement = frame.measurement(Model::Dht11);
+        assert_eq!(measurement.temperature.decidegrees(), 235);
+        assert_eq!(measurement.humidity.permille(), 450);
+    }
+
+    #[test]
+    fn dht22_frame() {
+        let data = with_checksum([0x02, 0x8C, 0x01, 0x5F]);
+        let frame = Frame::from_falling_edges(&edges(data, 1000, 2)).unwrap();
+
+        assert_eq!(frame.data(), &data);
+        assert_eq!(frame.model(), Model::Dht22);
+
+        let measurement = frame.measurement(Model::Dht22);
+        assert_eq!(measurement.temperature.decidegrees(), 351);
+        assert_eq!(measurement.humidity.permille(), 652);
+    }
+
+    #[test]
+    fn negative_temperature() {
+        let data = with_checksum([0x02, 0x8C, 0x80, 0x65]);
+        let frame = Frame::from_falling_edges(&edges(data, 1000, 2)).unwrap();
+        assert_eq!(frame.measurement(Model::Dht22).temperature.decidegrees(), -101);
+
+        // DHT11 keeps the sign in the highest bit of the decimal
+        let data = with_checksum([30, 0, 2, 0x83]);
+        let frame = Frame::from_falling_edges(&edges(data, 1000, 20)).unwrap();
+        assert_eq!(frame.measurement(Model::Dht11).temperature.decidegrees(), -23);
+    }
+
+    #[test]
+    fn counter_wraps_during_the_frame() {
+        let data = with_checksum([0x01, 0xF4, 0x00, 0xFA]);
+
+        // Wraps during the start signal, the response and the data bits
+        for start in [u16::MAX - 1000, u16::MAX - 2050, u16::MAX - 4000] {
+            let edges = edges(data, start, 2);
+            assert!(edges.windows(2).any(|pair| pair[1] < pair[0]));
+            assert_eq!(Frame::from_falling_edges(&edges).unwrap().data(), &data);
+        }
+    }
+
+    #[test]
+    fn missing_response() {
+        assert_eq!(Frame::from_falling_edges(&[]), Err(Error::NoResponse));
+        assert_eq!(Frame::from_falling_edges(&[1000]), Err(Error::NoResponse));
+        assert_eq!(Frame::from_falling_edges(&[1000, 21030]), Err(Error::NoResponse));
+    }
+
+    #[test]
+    fn truncated_frame() {
+        let edges = edges(with_checksum([45, 0, 23, 5]), 1000, 20);
+        assert_eq!(Frame::from_falling_edges(&edges[..edges.len() - 1]), Err(Error::Timeout));
+        assert_eq!(Frame::from_falling_edges(&edges[..10]), Err(Error::Timeout));
+    }
+
+    #[test]
+    fn pulse_out_of_range() {
+        let mut edges = edges(with_checksum([45, 0, 23, 5]), 1000, 20);
+
+        // Bit stretched by an extra 100 us
+        for edge in &mut edges[20..] {
+            *edge = edge.wrapping_add(100);
+        }
+
+        assert_eq!(Frame::from_falling_edges(&edges), Err(Error::Timing));
+    }
+
+    #[test]
+    fn checksum_mismatch() {
+        let frame = Frame::from_falling_edges(&edges([45, 0, 23, 5, 0], 1000, 20)).unwrap();
+        assert!(!frame.is_valid());
+    }
+}
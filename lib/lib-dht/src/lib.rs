@@ -0,0 +1,25 @@
+#![no_std]
+
+//! DHT11, DHT22, AM2302 and AM2301 single-wire humidity sensors
+//!
+//! `Dht` sends the start signal and times the pulses of the answer with
+//! the DWT cycle counter, the 40-bit `Frame` is then decoded according to
+//! the `Model`, which is either given or detected from the first frame.
+//! `DhtCapture` captures the edges by a timer and DMA instead, so the
+//! frame survives interrupts, the CPU only decodes it afterwards.
+//!
+//! `RobustDht` reads the sensor no more often than it measures, hides
+//! single failed reads, keeps `Statistics` of the errors and median filters
+//! the valid measurements.
+
+mod capture;
+mod frame;
+mod robust;
+mod sensor;
+mod units;
+
+pub use capture::DhtCapture;
+pub use frame::{Frame, Model};
+pub use robust::{Reading, RobustDht, Statistics, FAILURE_LIMIT};
+pub use sensor::{Dht, Error, Sensor};
+pub use units::{Humidity, Measurement, Temperature};
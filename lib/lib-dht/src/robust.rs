@@ -0,0 +1,280 @@
+use embedded_hal::blocking::delay::DelayMs;
+
+use crate::sensor::{Error, Sensor};
+use crate::units::{Humidity, Measurement, Temperature};
+
+/// Consecutive failed reads before the error is reported
+pub const FAILURE_LIMIT: u8 = 3;
+
+/// Counts of all read attempts and their results
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Statistics {
+    pub attempts: u32,
+    pub valid: u32,
+    pub no_responses: u32,
+    pub timeouts: u32,
+    pub timing_errors: u32,
+    pub checksum_errors: u32,
+    pub gpio_errors: u32,
+    /// Reads failed since the last valid one
+    pub consecutive_failures: u32,
+}
+
+impl Statistics {
+    fn record(&mut self, result: &Result<Measurement, Error>) {
+        self.attempts = self.attempts.wrapping_add(1);
+
+        let counter = match result {
+            Ok(_) => {
+                self.consecutive_failures = 0;
+                &mut self.valid
+            }
+            Err(error) => {
+                self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+
+                match error {
+                    Error::NoResponse => &mut self.no_responses,
+                    Error::Timeout => &mut self.timeouts,
+                    Error::Timing => &mut self.timing_errors,
+                    Error::Checksum => &mut self.checksum_errors,
+                    Error::Gpio => &mut self.gpio_errors,
+                }
+            }
+        };
+
+        *counter = counter.wrapping_add(1);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Reading {
+    /// No valid measurement yet
+    Starting,
+    /// Median of the last valid measurements
+    Valid(Measurement),
+    /// Last error after `FAILURE_LIMIT` failed updates in a row
+    Failed(Error),
+}
+
+/// DHT sensor read no more often than it measures, with valid reads
+/// median filtered over the last `N` samples
+///
+/// A failed read is not repeated before the sampling interval passes
+/// again, the sensor does not measure faster. A single failed update
+/// keeps the last reading, only `FAILURE_LIMIT` failed updates in a row
+/// report the error. `N` has to be at least one.
+pub struct RobustDht<S, const N: usize> {
+    sensor: S,
+    since_read_ms: u32,
+    samples: [Measurement; N],
+    len: usize,
+    next: usize,
+    failed_updates: u8,
+    last_error: Option<Error>,
+    statistics: Statistics,
+}
+
+impl<S: Sensor, const N: usize> RobustDht<S, N> {
+    /// Evaluated by `new`, so `RobustDht<_, 0>` does not compile
+    const HAS_SAMPLES: () = assert!(N > 0, "RobustDht needs at least one sample");
+
+    /// The first read happens after the sampling interval, the sensor
+    /// needs about a second after power up anyway
+    pub fn new(sensor: S) -> Self {
+        let () = Self::HAS_SAMPLES;
+
+        Self {
+            sensor,
+            since_read_ms: 0,
+            samples: [Measurement::default(); N],
+            len: 0,
+            next: 0,
+            failed_updates: 0,
+            last_error: None,
+            statistics: Statistics::default(),
+        }
+    }
+
+    pub fn sensor(&self) -> &S {
+        &self.sensor
+    }
+
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Read the sensor when its sampling interval has passed,
+    /// returns true when it was read
+    pub fn update<D: DelayMs<u16>>(&mut self, elapsed_ms: u32, delay: &mut D) -> bool {
+        self.since_read_ms = self.since_read_ms.saturating_add(elapsed_ms);
+
+        // Model is not known before the first valid read, the longer
+        // interval is safe for both
+        let interval_ms = self.sensor.model().map(|model| model.min_interval_ms()).unwrap_or(2000);
+
+        if self.since_read_ms < interval_ms {
+            return false;
+        }
+
+        self.since_read_ms = 0;
+
+        let result = self.sensor.read(delay);
+        self.statistics.record(&result);
+
+        match result {
+            Ok(measurement) => {
+                self.push(measurement);
+                self.failed_updates = 0;
+                self.last_error = None;
+                return true;
+            }
+            Err(error) => self.last_error = Some(error),
+        }
+
+        self.failed_updates = self.failed_updates.saturating_add(1);
+
+        // Samples from before the failure may be far from the current values
+        if self.failed_updates >= FAILURE_LIMIT {
+            self.len = 0;
+        }
+
+        true
+    }
+
+    pub fn reading(&self) -> Reading {
+        match (self.failed_updates >= FAILURE_LIMIT, self.last_error) {
+            (true, Some(error)) => Reading::Failed(error),
+            _ if self.len == 0 => Reading::Starting,
+            _ => Reading::Valid(self.median()),
+        }
+    }
+
+    fn push(&mut self, measurement: Measurement) {
+        self.samples[self.next] = measurement;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+    }
+
+    /// Temperature and humidity are filtered separately, with an even
+    /// count of samples the lower of the two middle values is used
+    fn median(&self) -> Measurement {
+        let mut temperatures = [0i16; N];
+        let mut humidities = [0u16; N];
+
+        for (index, sample) in self.samples[..self.len].iter().enumerate() {
+            temperatures[index] = sample.temperature.decidegrees();
+            humidities[index] = sample.humidity.permille();
+        }
+
+        let middle = (self.len - 1)/2;
+        temperatures[..self.len].sort_unstable();
+        humidities[..self.len].sort_unstable();
+
+        Measurement {
+            temperature: Temperature::from_decidegrees(temperatures[middle]),
+            humidity: Humidity::from_permille(humidities[middle]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Model;
+
+    /// Sensor answering with the scripted results one after another,
+    /// the last one is repeated
+    struct Scripted<'a> {
+        results: &'a [Result<i16, Error>],
+        reads: usize,
+    }
+
+    impl<'a> Sensor for Scripted<'a> {
+        fn model(&self) -> Option<Model> {
+            Some(Model::Dht22)
+        }
+
+        fn read<D: DelayMs<u16>>(&mut self, _delay: &mut D) -> Result<Measurement, Error> {
+            let index = self.reads.min(self.results.len() - 1);
+            self.reads += 1;
+
+            self.results[index].map(|decidegrees| Measurement {
+                temperature: Temperature::from_decidegrees(decidegrees),
+                humidity: Humidity::from_permille(500),
+            })
+        }
+    }
+
+    /// Delay that must not be used, the sensor is scripted
+    struct NoDelay;
+
+    impl DelayMs<u16> for NoDelay {
+        fn delay_ms(&mut self, _ms: u16) {
+            panic!("RobustDht should not wait");
+        }
+    }
+
+    fn sensor(results: &[Result<i16, Error>]) -> RobustDht<Scripted<'_>, 3> {
+        RobustDht::new(Scripted { results, reads: 0 })
+    }
+
+    fn temperature(reading: Reading) -> i16 {
+        match reading {
+            Reading::Valid(measurement) => measurement.temperature.decidegrees(),
+            reading => panic!("{:?}", reading),
+        }
+    }
+
+    #[test]
+    fn failed_read_waits_for_the_next_interval() {
+        let mut sensor = sensor(&[Err(Error::Timeout), Ok(215)]);
+
+        assert!(!sensor.update(1999, &mut NoDelay));
+        assert!(sensor.update(1, &mut NoDelay));
+        assert_eq!(sensor.sensor().reads, 1);
+        assert_eq!(sensor.reading(), Reading::Starting);
+
+        for _ in 0..19 {
+            assert!(!sensor.update(100, &mut NoDelay));
+        }
+
+        assert_eq!(sensor.sensor().reads, 1);
+        assert!(sensor.update(100, &mut NoDelay));
+        assert_eq!(sensor.sensor().reads, 2);
+        assert_eq!(temperature(sensor.reading()), 215);
+        assert_eq!(sensor.statistics().timeouts, 1);
+        assert_eq!(sensor.statistics().valid, 1);
+    }
+
+    #[test]
+    fn single_wrong_value_is_filtered_out() {
+        let mut sensor = sensor(&[Ok(210), Ok(990), Ok(212), Ok(214)]);
+
+        sensor.update(2000, &mut NoDelay);
+        assert_eq!(temperature(sensor.reading()), 210);
+
+        sensor.update(2000, &mut NoDelay);
+        assert_eq!(temperature(sensor.reading()), 210);
+
+        sensor.update(2000, &mut NoDelay);
+        assert_eq!(temperature(sensor.reading()), 212);
+
+        sensor.update(2000, &mut NoDelay);
+        assert_eq!(temperature(sensor.reading()), 214);
+    }
+
+    #[test]
+    fn error_is_reported_after_failure_limit() {
+        let mut sensor = sensor(&[Ok(210), Err(Error::Checksum)]);
+        sensor.update(2000, &mut NoDelay);
+
+        for _ in 1..FAILURE_LIMIT {
+            sensor.update(2000, &mut NoDelay);
+            assert_eq!(temperature(sensor.reading()), 210);
+        }
+
+        sensor.update(2000, &mut NoDelay);
+        assert_eq!(sensor.reading(), Reading::Failed(Error::Checksum));
+        assert_eq!(sensor.statistics().consecutive_failures, FAILURE_LIMIT as u32);
+    }
+}
@@ -0,0 +1,189 @@
+use cortex_m::peripheral::DWT;
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+use crate::frame::{Frame, Model};
+use crate::units::Measurement;
+
+/// Sensor starts answering 20 - 40 us after the line is released
+const RESPONSE_TIMEOUT_US: u32 = 100;
+
+/// All pulses of the answer are 80 us long at most
+const PULSE_TIMEOUT_US: u32 = 100;
+
+/// High pulse of a zero bit takes 26 - 28 us, of a one bit 70 us
+const ONE_THRESHOLD_US: u32 = 48;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Sensor did not answer the start signal
+    NoResponse,
+    /// Sensor stopped sending in the middle of the frame
+    Timeout,
+    /// Pulse of the frame is too short or too long, or all bits of the
+    /// frame were read as zeros
+    Timing,
+    /// Checksum of the frame does not match its data
+    Checksum,
+    /// Reading or setting the pin failed
+    Gpio,
+}
+
+/// Sensor read by the polling `Dht` or the timer capturing `DhtCapture`
+pub trait Sensor {
+    /// Model given or detected, `None` until the first valid frame
+    fn model(&self) -> Option<Model>;
+
+    /// Start a measurement and decode the answer
+    fn read<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<Measurement, Error>;
+}
+
+/// Check the frame and decode it, the model is detected from the first
+/// valid frame unless it is known
+pub(crate) fn decode(frame: &Frame, model: &mut Option<Model>) -> Result<Measurement, Error> {
+    // Zeros pass the checksum, but no sensor reports 0 % humidity at
+    // 0.0 C, pulses timed by a wrong clock are all read as zeros
+    if frame.data() == &[0; 5] {
+        return Err(Error::Timing);
+    }
+
+    if !frame.is_valid() {
+        return Err(Error::Checksum);
+    }
+
+    let model = *model.get_or_insert_with(|| frame.model());
+    Ok(frame.measurement(model))
+}
+
+/// DHT sensor on an open drain pin with a pull-up resistor
+///
+/// Pulses are timed by the DWT cycle counter, it has to be enabled by
+/// `DCB::enable_trace` and `DWT::enable_cycle_counter`. It counts cycles
+/// of the core, so the sensor is given the HCLK frequency, not SYSCLK.
+pub struct Dht<PIN> {
+    pin: PIN,
+    model: Option<Model>,
+    cycles_per_us: u32,
+}
+
+impl<PIN> Dht<PIN>
+where
+    PIN: InputPin + OutputPin,
+{
+    /// Sensor of an unknown model, it is detected from the first frame
+    pub fn new(pin: PIN, hclk_hz: u32) -> Self {
+        Self { pin, model: None, cycles_per_us: hclk_hz/1_000_000 }
+    }
+
+    pub fn with_model(pin: PIN, model: Model, hclk_hz: u32) -> Self {
+        Self { model: Some(model), ..Self::new(pin, hclk_hz) }
+    }
+
+    /// Model given or detected, `None` until the first valid frame
+    pub fn model(&self) -> Option<Model> {
+        self.model
+    }
+
+    pub fn release(self) -> PIN {
+        self.pin
+    }
+
+    /// Start a measurement and decode the answer
+    pub fn read<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<Measurement, Error> {
+        let frame = self.read_frame(delay)?;
+        decode(&frame, &mut self.model)
+    }
+
+    /// Start a measurement and read the raw frame, its checksum is not checked
+    pub fn read_frame<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<Frame, Error> {
+        // DHT11 start signal is long enough for both models
+        let start_ms = self.model.unwrap_or(Model::Dht11).start_ms();
+
+        self.pin.set_low().map_err(|_| Error::Gpio)?;
+        delay.delay_ms(start_ms);
+        self.pin.set_high().map_err(|_| Error::Gpio)?;
+
+        // Sensor answers with 80 us low and 80 us high
+        self.wait_for(false, RESPONSE_TIMEOUT_US).map_err(|_| Error::NoResponse)?;
+        self.wait_for(true, PULSE_TIMEOUT_US)?;
+        self.wait_for(false, PULSE_TIMEOUT_US)?;
+
+        // Every bit is 50 us low followed by a high pulse, its length is
+        // the value of the bit
+        let mut data = [0u8; 5];
+
+        for bit in 0..40 {
+            self.wait_for(true, PULSE_TIMEOUT_US)?;
+            let high = self.wait_for(false, PULSE_TIMEOUT_US)?;
+
+            data[bit/8] <<= 1;
+            if high > ONE_THRESHOLD_US*self.cycles_per_us {
+                data[bit/8] |= 1;
+            }
+        }
+
+        Ok(Frame::new(data))
+    }
+
+    /// Wait until the line is at `level`, returns the number of cycles it took
+    fn wait_for(&self, level: bool, timeout_us: u32) -> Result<u32, Error> {
+        let start = DWT::cycle_count();
+        let timeout = timeout_us*self.cycles_per_us;
+
+        loop {
+            let elapsed = DWT::cycle_count().wrapping_sub(start);
+
+            if self.pin.is_high().map_err(|_| Error::Gpio)? == level {
+                return Ok(elapsed);
+            }
+
+            if elapsed > timeout {
+                return Err(Error::Timeout);
+            }
+        }
+    }
+}
+
+impl<PIN> Sensor for Dht<PIN>
+where
+    PIN: InputPin + OutputPin,
+{
+    fn model(&self) -> Option<Model> {
+        Dht::model(self)
+    }
+
+    fn read<D: DelayMs<u16>>(&mut self, delay: &mut D) -> Result<Measurement, Error> {
+        Dht::read(self, delay)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_checksum(data: [u8; 4]) -> Frame {
+        let sum = data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte));
+        Frame::new([data[0], data[1], data[2], data[3], sum])
+    }
+
+    #[test]
+    fn zero_frame_is_rejected() {
+        let mut model = None;
+        assert_eq!(decode(&Frame::new([0; 5]), &mut model), Err(Error::Timing));
+        assert_eq!(model, None);
+
+        let mut model = Some(Model::Dht22);
+        assert_eq!(decode(&Frame::new([0; 5]), &mut model), Err(Error::Timing));
+    }
+
+    #[test]
+    fn model_detected_from_first_valid_frame() {
+        let mut model = None;
+        assert_eq!(decode(&with_checksum([45, 0, 23, 6]), &mut model).map(|m| m.humidity.permille()), Ok(450));
+        assert_eq!(model, Some(Model::Dht11));
+
+        let mut model = None;
+        assert_eq!(decode(&Frame::new([0x02, 0x8C, 0x01, 0x5F, 0]), &mut model), Err(Error::Checksum));
+        assert_eq!(model, None);
+    }
+}
@@ -0,0 +1,49 @@
+use core::fmt;
+
+/// Temperature in tenths of a degree Celsius
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Temperature(i16);
+
+impl Temperature {
+    pub const fn from_decidegrees(decidegrees: i16) -> Self {
+        Self(decidegrees)
+    }
+
+    pub const fn decidegrees(&self) -> i16 {
+        self.0
+    }
+}
+
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let value = self.0.unsigned_abs();
+        write!(f, "{}{}.{}", sign, value/10, value % 10)
+    }
+}
+
+/// Relative humidity in tenths of a percent
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Humidity(u16);
+
+impl Humidity {
+    pub const fn from_permille(permille: u16) -> Self {
+        Self(permille)
+    }
+
+    pub const fn permille(&self) -> u16 {
+        self.0
+    }
+}
+
+impl fmt::Display for Humidity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.0/10, self.0 % 10)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Measurement {
+    pub temperature: Temperature,
+    pub humidity: Humidity,
+}
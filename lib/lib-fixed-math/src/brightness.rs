@@ -0,0 +1,200 @@
+use crate::interpolate::lookup_u16;
+
+/// Perceptual brightness correction curve
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve {
+    /// No correction, duty is proportional to the level
+    Linear,
+    /// Power law with exponent 2.2
+    Gamma22,
+    /// CIE 1931 lightness, close to gamma but linear near black
+    Cie1931,
+}
+
+/// Brightness correction scaled to the maximum duty of a PWM channel,
+/// use `get_max_duty()` of the timer as `max_duty`
+#[derive(Clone, Copy, Debug)]
+pub struct Brightness {
+    curve: Curve,
+    max_duty: u16,
+}
+
+impl Brightness {
+    pub const fn new(curve: Curve, max_duty: u16) -> Self {
+        Self { curve, max_duty }
+    }
+
+    pub fn max_duty(&self) -> u16 {
+        self.max_duty
+    }
+
+    /// Duty for the perceived brightness `level` (0 is off, 65535 is full)
+    pub fn duty(&self, level: u16) -> u16 {
+        // Interpolation stops a fraction of the last table step short of the end
+        if level == u16::MAX {
+            return self.max_duty;
+        }
+
+        let corrected = match self.curve {
+            Curve::Linear => level,
+            Curve::Gamma22 => lookup_u16(&GAMMA_2_2, level),
+            Curve::Cie1931 => lookup_u16(&CIE_1931, level),
+        };
+
+        ((corrected as u32*self.max_duty as u32 + 0x7fff) >> 16) as u16
+    }
+}
+
+/// (i/256)^2.2*65535
+const GAMMA_2_2: [u16; 257] = [
+    0, 0, 2, 4, 7, 11, 17, 24,
+    32, 41, 52, 64, 78, 93, 110, 128,
+    147, 168, 191, 215, 240, 267, 296, 327,
+    359, 392, 428, 465, 504, 544, 586, 630,
+    676, 723, 772, 823, 875, 930, 986, 1044,
+    1104, 1165, 1229, 1294, 1361, 1430, 1501, 1574,
+    1648, 1725, 1803, 1884, 1966, 2050, 2136, 2224,
+    2314, 2406, 2500, 2595, 2693, 2793, 2895, 2998,
+    3104, 3212, 3322, 3433, 3547, 3663, 3781, 3900,
+    4022, 4146, 4272, 4400, 4530, 4663, 4797, 4933,
+    5072, 5212, 5355, 5499, 5646, 5795, 5946, 6099,
+    6255, 6412, 6572, 6733, 6897, 7063, 7231, 7402,
+    7574, 7749, 7926, 8105, 8286, 8469, 8655, 8843,
+    9033, 9225, 9419, 9616, 9815, 10016, 10219, 10425,
+    10632, 10842, 11054, 11269, 11486, 11705, 11926, 12149,
+    12375, 12603, 12833, 13066, 13301, 13538, 13777, 14019,
+    14263, 14509, 14758, 15009, 15262, 15517, 15775, 16035,
+    16298, 16563, 16830, 17099, 17371, 17645, 17922, 18201,
+    18482, 18765, 19051, 19339, 19630, 19923, 20218, 20516,
+    20816, 21119, 21424, 21731, 22040, 22352, 22667, 22984,
+    23303, 23624, 23949, 24275, 24604, 24935, 25269, 25605,
+    25943, 26284, 26628, 26973, 27322, 27672, 28026, 28381,
+    28739, 29100, 29462, 29828, 30196, 30566, 30939, 31314,
+    31692, 32072, 32454, 32840, 33227, 33617, 34010, 34405,
+    34802, 35202, 35605, 36010, 36417, 36827, 37240, 37655,
+    38072, 38493, 38915, 39340, 39768, 40198, 40631, 41066,
+    41503, 41944, 42387, 42832, 43280, 43730, 44183, 44639,
+    45097, 45557, 46020, 46486, 46954, 47425, 47899, 48374,
+    48853, 49334, 49818, 50304, 50793, 51284, 51778, 52275,
+    52774, 53276, 53780, 54287, 54796, 55308, 55823, 56341,
+    56860, 57383, 57908, 58436, 58966, 59499, 60035, 60573,
+    61114, 61657, 62203, 62752, 63303, 63857, 64414, 64973,
+    65535,
+];
+
+/// CIE 1931 lightness L* = i/256*100 converted to luminance Y*65535
+const CIE_1931: [u16; 257] = [
+    0, 28, 57, 85, 113, 142, 170, 198,
+    227, 255, 283, 312, 340, 368, 397, 425,
+    453, 482, 510, 538, 567, 595, 625, 655,
+    686, 718, 751, 785, 821, 857, 894, 933,
+    972, 1012, 1054, 1097, 1141, 1186, 1232, 1279,
+    1328, 1378, 1429, 1481, 1535, 1590, 1646, 1703,
+    1762, 1822, 1883, 1946, 2010, 2076, 2143, 2211,
+    2281, 2352, 2425, 2500, 2575, 2653, 2731, 2812,
+    2894, 2977, 3062, 3149, 3237, 3327, 3419, 3512,
+    3607, 3704, 3802, 3902, 4004, 4108, 4213, 4320,
+    4429, 4540, 4652, 4767, 4883, 5001, 5121, 5243,
+    5367, 5493, 5621, 5751, 5882, 6016, 6152, 6289,
+    6429, 6571, 6715, 6861, 7009, 7159, 7312, 7466,
+    7623, 7782, 7943, 8106, 8272, 8439, 8609, 8781,
+    8956, 9133, 9312, 9493, 9677, 9863, 10052, 10243,
+    10436, 10632, 10830, 11030, 11234, 11439, 11647, 11858,
+    12071, 12286, 12504, 12725, 12948, 13174, 13403, 13634,
+    13868, 14104, 14343, 14585, 14830, 15077, 15327, 15579,
+    15835, 16093, 16354, 16618, 16885, 17154, 17426, 17702,
+    17980, 18261, 18545, 18831, 19121, 19414, 19710, 20008,
+    20310, 20615, 20922, 21233, 21547, 21864, 22184, 22507,
+    22833, 23163, 23495, 23831, 24170, 24512, 24857, 25206,
+    25558, 25913, 26271, 26632, 26997, 27366, 27737, 28112,
+    28490, 28872, 29257, 29645, 30037, 30432, 30831, 31233,
+    31639, 32048, 32461, 32877, 33297, 33720, 34147, 34578,
+    35012, 35450, 35891, 36336, 36785, 37237, 37693, 38153,
+    38616, 39083, 39554, 40029, 40507, 40990, 41476, 41966,
+    42460, 42957, 43459, 43964, 44473, 44987, 45504, 46025,
+    46550, 47079, 47612, 48149, 48690, 49235, 49785, 50338,
+    50895, 51457, 52022, 52592, 53166, 53744, 54326, 54912,
+    55503, 56097, 56696, 57300, 57907, 58519, 59135, 59755,
+    60380, 61009, 61642, 62280, 62922, 63569, 64220, 64875,
+    65535,
+];
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    /// Sizes of the duty range, 16000 is `get_max_duty()` of TIM2 at 500 Hz
+    /// with 8 MHz timer clock
+    const MAX_DUTIES: [u16; 6] = [1, 255, 1000, 2666, 16000, 65535];
+
+    fn gamma(x: f64) -> f64 {
+        x.powf(2.2)
+    }
+
+    fn cie(x: f64) -> f64 {
+        let lightness = x*100.0;
+
+        match lightness <= 8.0 {
+            true => lightness/903.3,
+            false => ((lightness + 16.0)/116.0).powi(3),
+        }
+    }
+
+    /// Largest difference from `f64` over all levels in units of the duty
+    fn max_error(curve: Curve, reference: fn(f64) -> f64, max_duty: u16) -> f64 {
+        let brightness = Brightness::new(curve, max_duty);
+
+        (0..=u16::MAX)
+            .map(|level| {
+                let expected = reference(level as f64/65536.0)*max_duty as f64;
+                (brightness.duty(level) as f64 - expected).abs()
+            })
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn gamma_matches_float() {
+        for max_duty in MAX_DUTIES {
+            let error = max_error(Curve::Gamma22, gamma, max_duty);
+            assert!(error <= 0.5 + 4e-5*max_duty as f64, "{} {}", max_duty, error);
+        }
+    }
+
+    #[test]
+    fn cie_matches_float() {
+        for max_duty in MAX_DUTIES {
+            let error = max_error(Curve::Cie1931, cie, max_duty);
+            assert!(error <= 0.5 + 4e-5*max_duty as f64, "{} {}", max_duty, error);
+        }
+    }
+
+    #[test]
+    fn linear_scales_to_max_duty() {
+        for max_duty in MAX_DUTIES {
+            let error = max_error(Curve::Linear, |x| x, max_duty);
+            assert!(error <= 0.5 + max_duty as f64/65536.0, "{} {}", max_duty, error);
+        }
+    }
+
+    #[test]
+    fn curves_cover_the_full_range() {
+        for curve in [Curve::Linear, Curve::Gamma22, Curve::Cie1931] {
+            for max_duty in MAX_DUTIES {
+                let brightness = Brightness::new(curve, max_duty);
+                assert_eq!(brightness.duty(0), 0);
+                assert_eq!(brightness.duty(u16::MAX), max_duty);
+            }
+        }
+    }
+
+    #[test]
+    fn curves_are_monotonic() {
+        for curve in [Curve::Linear, Curve::Gamma22, Curve::Cie1931] {
+            let brightness = Brightness::new(curve, u16::MAX);
+            let duties: std::vec::Vec<u16> = (0..=u16::MAX).map(|level| brightness.duty(level)).collect();
+            assert!(duties.windows(2).all(|pair| pair[0] <= pair[1]));
+        }
+    }
+}
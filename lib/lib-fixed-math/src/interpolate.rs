@@ -0,0 +1,25 @@
+/// Linear interpolation between `a` and `b`, `fraction` is 0-255 (256 would be `b`)
+pub fn lerp_u16(a: u16, b: u16, fraction: u8) -> u16 {
+    let a = a as i32;
+    let b = b as i32;
+    (a + (((b - a)*fraction as i32) >> 8)) as u16
+}
+
+/// Linear interpolation between `a` and `b`, `fraction` is 0-255 (256 would be `b`)
+pub fn lerp_i16(a: i16, b: i16, fraction: u8) -> i16 {
+    let a = a as i32;
+    let b = b as i32;
+    (a + (((b - a)*fraction as i32) >> 8)) as i16
+}
+
+/// Interpolated value of a 257 entry table covering the whole `u16` input range
+pub fn lookup_u16(table: &[u16; 257], input: u16) -> u16 {
+    let index = (input >> 8) as usize;
+    lerp_u16(table[index], table[index + 1], input as u8)
+}
+
+/// Interpolated value of a 257 entry table covering the whole `u16` input range
+pub fn lookup_i16(table: &[i16; 257], input: u16) -> i16 {
+    let index = (input >> 8) as usize;
+    lerp_i16(table[index], table[index + 1], input as u8)
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+//! Fixed-point math for devices without FPU
+//!
+//! Lookup tables with linear interpolation replace float trigonometry
+//! and brightness correction, so no soft-float routines are needed.
+
+mod brightness;
+mod interpolate;
+mod sine;
+
+pub use brightness::{Brightness, Curve};
+pub use interpolate::{lerp_i16, lerp_u16, lookup_i16, lookup_u16};
+pub use sine::{cos_q15, sin_q15, PHASE_FULL, PHASE_HALF, PHASE_QUARTER};
@@ -0,0 +1,19 @@
+#![no_std]
+
+//! I2C1 bus recovery for the STM32F103 and a bus shared by several drivers
+//!
+//! A glitch in the middle of a transfer can leave a slave holding SDA low
+//! while it waits for clock pulses the master never sends, every following
+//! transaction then times out. `recover` clocks the slave out of that state
+//! and resets the peripheral, `RecoveringI2c` does that automatically and
+//! retries the failed transaction.
+//!
+//! `SharedBus` and `InterruptSafeBus` hand out `BusProxy` handles
+//! implementing the blocking I2C traits, so drivers taking the bus by value
+//! can use the same bus as other drivers.
+
+mod recovery;
+mod shared;
+
+pub use recovery::{recover, RecoveringI2c, MAX_CLOCK_PULSES};
+pub use shared::{BusMutex, BusProxy, InterruptSafeBus, SharedBus};
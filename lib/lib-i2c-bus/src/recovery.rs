@@ -0,0 +1,171 @@
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+use stm32f1xx_hal::i2c::{BlockingI2c, Error, Pins};
+use stm32f1xx_hal::pac::gpioa::RegisterBlock;
+use stm32f1xx_hal::pac::{GPIOB, I2C1};
+use stm32f1xx_hal::rcc::Clocks;
+
+/// A slave in the middle of a byte needs at most eight more clock pulses
+/// to finish it and one for the acknowledge, then it releases SDA
+pub const MAX_CLOCK_PULSES: u8 = 9;
+
+/// Half of the clock period during the recovery, 100 kHz
+const HALF_PERIOD_US: u32 = 5;
+
+/// General purpose output, open drain, 2 MHz
+const GPIO_OPEN_DRAIN: u32 = 0b0110;
+
+/// Alternate function output, open drain, 50 MHz, as set up by the HAL
+const ALTERNATE_OPEN_DRAIN: u32 = 0b1111;
+
+/// Free a bus blocked by a slave holding SDA low and reset I2C1
+///
+/// The pins are switched to GPIO, SCL is clocked until the slave releases
+/// SDA, at most `MAX_CLOCK_PULSES` times, and a STOP condition is issued.
+/// The pins are then returned to the peripheral, which is reset, as it may
+/// stay busy after the glitch, and configured again.
+///
+/// `BlockingI2c` of the HAL cannot be released, so instead of re-creating
+/// it the peripheral registers are saved before the reset and restored
+/// afterwards, borrowing the bus makes sure no transaction is running.
+///
+/// Returns true when both lines are high after the recovery.
+pub fn recover<PINS: Pins<I2C1>>(_i2c: &mut BlockingI2c<I2C1, PINS>, clocks: &Clocks) -> bool {
+    // Both peripherals are owned by the bus, only the pins of the bus
+    // are changed and GPIOB configuration is modified atomically
+    let i2c = unsafe { &*I2C1::ptr() };
+    let gpio = unsafe { &*GPIOB::ptr() };
+
+    // PB6 and PB7, PB8 and PB9 when remapped
+    let (scl, sda) = if PINS::REMAP { (8, 9) } else { (6, 7) };
+
+    let cr2 = i2c.cr2.read().bits();
+    let oar1 = i2c.oar1.read().bits();
+    let ccr = i2c.ccr.read().bits();
+    let trise = i2c.trise.read().bits();
+
+    i2c.cr1.modify(|_, w| w.pe().clear_bit());
+
+    // Released lines are pulled high, so they are high before the switch
+    gpio.bsrr.write(|w| unsafe { w.bits((1 << scl) | (1 << sda)) });
+    configure_pins(gpio, scl, sda, GPIO_OPEN_DRAIN);
+
+    // Delay counts cycles of the core, which runs at HCLK
+    let half_period = clocks.hclk().raw()/1_000_000*HALF_PERIOD_US;
+    let set = |pin: u32, high: bool| {
+        let bit = if high { 1 << pin } else { 1 << (pin + 16) };
+        gpio.bsrr.write(|w| unsafe { w.bits(bit) });
+        cortex_m::asm::delay(half_period);
+    };
+    let is_high = |pin: u32| gpio.idr.read().bits() & (1 << pin) != 0;
+
+    for _ in 0..MAX_CLOCK_PULSES {
+        if is_high(sda) {
+            break;
+        }
+
+        set(scl, false);
+        set(scl, true);
+    }
+
+    // STOP condition, SDA rising while SCL is high
+    set(scl, false);
+    set(sda, false);
+    set(scl, true);
+    set(sda, true);
+
+    let free = is_high(scl) && is_high(sda);
+
+    configure_pins(gpio, scl, sda, ALTERNATE_OPEN_DRAIN);
+
+    i2c.cr1.write(|w| w.swrst().set_bit());
+    i2c.cr1.reset();
+
+    i2c.cr2.write(|w| unsafe { w.bits(cr2) });
+    i2c.oar1.write(|w| unsafe { w.bits(oar1) });
+    i2c.ccr.write(|w| unsafe { w.bits(ccr) });
+    i2c.trise.write(|w| unsafe { w.bits(trise) });
+    i2c.cr1.modify(|_, w| w.pe().set_bit());
+
+    free
+}
+
+/// Set the mode of both pins, they are in the same configuration register
+fn configure_pins(gpio: &RegisterBlock, scl: u32, sda: u32, mode: u32) {
+    let (scl_shift, sda_shift) = ((scl % 8)*4, (sda % 8)*4);
+    let mask = (0xF << scl_shift) | (0xF << sda_shift);
+    let bits = (mode << scl_shift) | (mode << sda_shift);
+
+    cortex_m::interrupt::free(|_| {
+        if scl < 8 {
+            gpio.crl.modify(|r, w| unsafe { w.bits((r.bits() & !mask) | bits) });
+        } else {
+            gpio.crh.modify(|r, w| unsafe { w.bits((r.bits() & !mask) | bits) });
+        }
+    });
+}
+
+/// I2C1 bus recovering from errors and retrying the failed transaction
+///
+/// A missing acknowledge is returned right away, it means that no device
+/// answers at the address, not that the bus is stuck. Other errors and
+/// timeouts recover the bus and the transaction is retried once.
+pub struct RecoveringI2c<PINS> {
+    i2c: BlockingI2c<I2C1, PINS>,
+    clocks: Clocks,
+    recoveries: u32,
+}
+
+impl<PINS: Pins<I2C1>> RecoveringI2c<PINS> {
+    pub fn new(i2c: BlockingI2c<I2C1, PINS>, clocks: Clocks) -> Self {
+        Self { i2c, clocks, recoveries: 0 }
+    }
+
+    /// Number of recoveries done so far
+    pub fn recoveries(&self) -> u32 {
+        self.recoveries
+    }
+
+    pub fn release(self) -> BlockingI2c<I2C1, PINS> {
+        self.i2c
+    }
+
+    fn retry<F>(&mut self, mut transaction: F) -> Result<(), nb::Error<Error>>
+    where
+        F: FnMut(&mut BlockingI2c<I2C1, PINS>) -> Result<(), nb::Error<Error>>,
+    {
+        let result = transaction(&mut self.i2c);
+
+        match result {
+            Ok(()) | Err(nb::Error::Other(Error::Acknowledge)) => result,
+            Err(_) => {
+                self.recoveries = self.recoveries.wrapping_add(1);
+                recover(&mut self.i2c, &self.clocks);
+                transaction(&mut self.i2c)
+            }
+        }
+    }
+}
+
+impl<PINS: Pins<I2C1>> Write for RecoveringI2c<PINS> {
+    type Error = nb::Error<Error>;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.retry(|i2c| i2c.write(address, bytes))
+    }
+}
+
+impl<PINS: Pins<I2C1>> Read for RecoveringI2c<PINS> {
+    type Error = nb::Error<Error>;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.retry(|i2c| i2c.read(address, buffer))
+    }
+}
+
+impl<PINS: Pins<I2C1>> WriteRead for RecoveringI2c<PINS> {
+    type Error = nb::Error<Error>;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.retry(|i2c| i2c.write_read(address, bytes, buffer))
+    }
+}
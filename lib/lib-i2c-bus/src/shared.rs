@@ -0,0 +1,234 @@
+use core::cell::RefCell;
+
+use cortex_m::interrupt::{self, Mutex};
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+
+/// Exclusive access to a bus for the time of a single transaction
+pub trait BusMutex {
+    type Bus;
+
+    fn lock<R, F: FnOnce(&mut Self::Bus) -> R>(&self, f: F) -> R;
+}
+
+/// Bus shared by drivers running in the main loop only
+///
+/// Transactions of different proxies cannot interleave, each proxy call
+/// runs a complete transaction, but using the bus from an interrupt while
+/// the main loop uses it panics, `InterruptSafeBus` has to be used then.
+pub struct SharedBus<I2C> {
+    bus: RefCell<I2C>,
+}
+
+impl<I2C> SharedBus<I2C> {
+    pub fn new(i2c: I2C) -> Self {
+        Self { bus: RefCell::new(i2c) }
+    }
+
+    /// Handle implementing the I2C traits, there can be any number of them
+    pub fn acquire(&self) -> BusProxy<'_, Self> {
+        BusProxy { mutex: self }
+    }
+
+    pub fn into_inner(self) -> I2C {
+        self.bus.into_inner()
+    }
+}
+
+impl<I2C> BusMutex for SharedBus<I2C> {
+    type Bus = I2C;
+
+    fn lock<R, F: FnOnce(&mut I2C) -> R>(&self, f: F) -> R {
+        f(&mut self.bus.borrow_mut())
+    }
+}
+
+/// Bus shared by the main loop and interrupt handlers
+///
+/// Interrupts are disabled for the time of every transaction. The I2C
+/// peripheral is created at runtime, so the bus is moved to a static place
+/// by `cortex_m::singleton!` and its `'static` proxies can be handed to
+/// drivers used by interrupt handlers.
+pub struct InterruptSafeBus<I2C> {
+    bus: Mutex<RefCell<I2C>>,
+}
+
+impl<I2C> InterruptSafeBus<I2C> {
+    pub const fn new(i2c: I2C) -> Self {
+        Self { bus: Mutex::new(RefCell::new(i2c)) }
+    }
+
+    pub fn acquire(&self) -> BusProxy<'_, Self> {
+        BusProxy { mutex: self }
+    }
+}
+
+impl<I2C> BusMutex for InterruptSafeBus<I2C> {
+    type Bus = I2C;
+
+    fn lock<R, F: FnOnce(&mut I2C) -> R>(&self, f: F) -> R {
+        interrupt::free(|cs| f(&mut self.bus.borrow(cs).borrow_mut()))
+    }
+}
+
+/// Handle of a shared bus, drivers taking the bus by value as well as those
+/// borrowing it get their own proxy
+pub struct BusProxy<'a, M> {
+    mutex: &'a M,
+}
+
+impl<'a, M> Clone for BusProxy<'a, M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, M> Copy for BusProxy<'a, M> {}
+
+impl<'a, M> Write for BusProxy<'a, M>
+where
+    M: BusMutex,
+    M::Bus: Write,
+{
+    type Error = <M::Bus as Write>::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|i2c| i2c.write(address, bytes))
+    }
+}
+
+impl<'a, M> Read for BusProxy<'a, M>
+where
+    M: BusMutex,
+    M::Bus: Read,
+{
+    type Error = <M::Bus as Read>::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|i2c| i2c.read(address, buffer))
+    }
+}
+
+impl<'a, M> WriteRead for BusProxy<'a, M>
+where
+    M: BusMutex,
+    M::Bus: WriteRead,
+{
+    type Error = <M::Bus as WriteRead>::Error;
+
+    fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.mutex.lock(|i2c| i2c.write_read(address, bytes, buffer))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::convert::Infallible;
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// Complete transactions in the order the bus has seen them
+    #[derive(Debug, PartialEq, Eq)]
+    enum Transaction {
+        Write(u8, Vec<u8>),
+        Read(u8, usize),
+        WriteRead(u8, Vec<u8>, usize),
+    }
+
+    #[derive(Default)]
+    struct MockI2c {
+        log: Vec<Transaction>,
+    }
+
+    impl MockI2c {
+        fn transaction(&mut self, transaction: Transaction) {
+            self.log.push(transaction);
+        }
+    }
+
+    impl Write for MockI2c {
+        type Error = Infallible;
+
+        fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+            self.transaction(Transaction::Write(address, bytes.to_vec()));
+            Ok(())
+        }
+    }
+
+    impl Read for MockI2c {
+        type Error = Infallible;
+
+        fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+            buffer.fill(address);
+            self.transaction(Transaction::Read(address, buffer.len()));
+            Ok(())
+        }
+    }
+
+    impl WriteRead for MockI2c {
+        type Error = Infallible;
+
+        fn write_read(&mut self, address: u8, bytes: &[u8], buffer: &mut [u8]) -> Result<(), Self::Error> {
+            buffer.fill(address);
+            self.transaction(Transaction::WriteRead(address, bytes.to_vec(), buffer.len()));
+            Ok(())
+        }
+    }
+
+    /// Driver owning its proxy, reads a register by a repeated start
+    struct Sensor<I2C> {
+        i2c: I2C,
+        address: u8,
+    }
+
+    impl<I2C: Write + WriteRead> Sensor<I2C> {
+        fn configure(&mut self, value: u8) {
+            let _ = self.i2c.write(self.address, &[0x10, value]);
+        }
+
+        fn read_register(&mut self, register: u8) -> u8 {
+            let mut buffer = [0];
+            let _ = self.i2c.write_read(self.address, &[register], &mut buffer);
+            buffer[0]
+        }
+    }
+
+    #[test]
+    fn proxies_run_whole_transactions() {
+        let bus = SharedBus::new(MockI2c::default());
+        let mut first = Sensor { i2c: bus.acquire(), address: 0x76 };
+        let mut second = Sensor { i2c: bus.acquire(), address: 0x68 };
+        let mut borrowed = bus.acquire();
+
+        first.configure(1);
+        second.configure(2);
+        assert_eq!(first.read_register(0xD0), 0x76);
+        assert_eq!(second.read_register(0x75), 0x68);
+
+        let mut buffer = [0; 3];
+        borrowed.read(0x3C, &mut buffer).unwrap();
+        assert_eq!(buffer, [0x3C; 3]);
+
+        assert_eq!(bus.into_inner().log, [
+            Transaction::Write(0x76, [0x10, 1].to_vec()),
+            Transaction::Write(0x68, [0x10, 2].to_vec()),
+            Transaction::WriteRead(0x76, [0xD0].to_vec(), 1),
+            Transaction::WriteRead(0x68, [0x75].to_vec(), 1),
+            Transaction::Read(0x3C, 3),
+        ]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn nested_transaction_panics_instead_of_interleaving() {
+        let bus = SharedBus::new(MockI2c::default());
+        let mut proxy = bus.acquire();
+
+        bus.lock(|i2c| {
+            i2c.transaction(Transaction::Read(0x76, 1));
+            let _ = proxy.write(0x68, &[0]);
+        });
+    }
+}
@@ -0,0 +1,114 @@
+use embedded_hal::blocking::i2c::WriteRead;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Device {
+    /// Pressure sensor, chip ID 0x55 at 0x77
+    Bmp180,
+    /// Pressure sensor, chip ID 0x56 - 0x58 at 0x76 or 0x77
+    Bmp280,
+    /// Pressure and humidity sensor, chip ID 0x60 at 0x76 or 0x77
+    Bme280,
+    /// Pressure, humidity and gas sensor, chip ID 0x61 at 0x76 or 0x77
+    Bme680,
+    /// Accelerometer and gyroscope, WHO_AM_I 0x68 at 0x68 or 0x69
+    Mpu6050,
+    /// Accelerometer and gyroscope, WHO_AM_I 0x70 at 0x68 or 0x69
+    Mpu6500,
+    /// MPU6500 with a magnetometer, WHO_AM_I 0x71 at 0x68 or 0x69
+    Mpu9250,
+    /// MPU6500 with a magnetometer, WHO_AM_I 0x73 at 0x68 or 0x69
+    Mpu9255,
+    /// Accelerometer, device ID 0xE5 at 0x53 or 0x1D
+    Adxl345,
+    /// Magnetometer, identification "H43" at 0x1E
+    Hmc5883l,
+    /// OLED display controller at 0x3C or 0x3D
+    Ssd1306,
+    /// EEPROM at 0x50 - 0x57
+    At24cxx,
+    /// Real time clock at 0x68, DS1307 uses the same address
+    Ds3231,
+    Unknown,
+}
+
+impl Device {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Device::Bmp180 => "BMP180",
+            Device::Bmp280 => "BMP280",
+            Device::Bme280 => "BME280",
+            Device::Bme680 => "BME680",
+            Device::Mpu6050 => "MPU6050",
+            Device::Mpu6500 => "MPU6500",
+            Device::Mpu9250 => "MPU9250",
+            Device::Mpu9255 => "MPU9255",
+            Device::Adxl345 => "ADXL345",
+            Device::Hmc5883l => "HMC5883L",
+            Device::Ssd1306 => "SSD1306",
+            Device::At24cxx => "AT24Cxx",
+            Device::Ds3231 => "DS3231",
+            Device::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Identity {
+    pub device: Device,
+    /// True when the device answered with its chip ID, false when only
+    /// the address suggests what the device might be
+    pub confirmed: bool,
+}
+
+impl Identity {
+    fn confirmed(device: Device) -> Self {
+        Self { device, confirmed: true }
+    }
+
+    fn guess(device: Device) -> Self {
+        Self { device, confirmed: false }
+    }
+}
+
+/// Identify a device answering at `address` by its chip ID register,
+/// devices without one are guessed from the address only
+pub fn identify<I2C: WriteRead>(i2c: &mut I2C, address: u8) -> Identity {
+    match address {
+        0x76 | 0x77 => match read_register(i2c, address, 0xD0) {
+            Some(0x55) => Identity::confirmed(Device::Bmp180),
+            Some(0x56..=0x58) => Identity::confirmed(Device::Bmp280),
+            Some(0x60) => Identity::confirmed(Device::Bme280),
+            Some(0x61) => Identity::confirmed(Device::Bme680),
+            _ => Identity::guess(Device::Unknown),
+        },
+        0x68 | 0x69 => match read_register(i2c, address, 0x75) {
+            Some(0x68) => Identity::confirmed(Device::Mpu6050),
+            Some(0x70) => Identity::confirmed(Device::Mpu6500),
+            Some(0x71) => Identity::confirmed(Device::Mpu9250),
+            Some(0x73) => Identity::confirmed(Device::Mpu9255),
+            // Clock registers end at 0x12, so it has no WHO_AM_I register
+            _ if address == 0x68 => Identity::guess(Device::Ds3231),
+            _ => Identity::guess(Device::Unknown),
+        },
+        0x1D | 0x53 => match read_register(i2c, address, 0x00) {
+            Some(0xE5) => Identity::confirmed(Device::Adxl345),
+            _ => Identity::guess(Device::Unknown),
+        },
+        0x1E => {
+            let mut id = [0u8; 3];
+            match i2c.write_read(address, &[0x0A], &mut id) {
+                Ok(()) if &id == b"H43" => Identity::confirmed(Device::Hmc5883l),
+                _ => Identity::guess(Device::Unknown),
+            }
+        }
+        0x3C | 0x3D => Identity::guess(Device::Ssd1306),
+        0x50..=0x57 => Identity::guess(Device::At24cxx),
+        _ => Identity::guess(Device::Unknown),
+    }
+}
+
+fn read_register<I2C: WriteRead>(i2c: &mut I2C, address: u8, register: u8) -> Option<u8> {
+    let mut value = [0u8; 1];
+    i2c.write_read(address, &[register], &mut value).ok()?;
+    Some(value[0])
+}
@@ -0,0 +1,13 @@
+#![no_std]
+
+//! I2C bus scanner identifying common sensors and modules
+//!
+//! `scan` probes every 7-bit address and identifies the devices answering,
+//! reading their chip ID registers where they have one, `find` looks for
+//! the address of a particular device.
+
+mod device;
+mod scanner;
+
+pub use device::{identify, Device, Identity};
+pub use scanner::{find, probe, scan, Found, Scan, FIRST_ADDRESS, LAST_ADDRESS};
@@ -0,0 +1,69 @@
+use embedded_hal::blocking::i2c::{Read, WriteRead};
+
+use crate::device::{identify, Device, Identity};
+
+/// Addresses 0x00 - 0x07 are reserved for special purposes
+pub const FIRST_ADDRESS: u8 = 0x08;
+
+/// Addresses 0x78 - 0x7F are reserved for 10-bit addressing
+pub const LAST_ADDRESS: u8 = 0x77;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Found {
+    pub address: u8,
+    pub identity: Identity,
+}
+
+/// True when a device acknowledges its address
+///
+/// A single byte is read, an empty write is not possible with all drivers,
+/// reading does not change the state of common devices.
+pub fn probe<I2C: Read>(i2c: &mut I2C, address: u8) -> bool {
+    let mut buffer = [0u8; 1];
+    i2c.read(address, &mut buffer).is_ok()
+}
+
+/// Devices answering on the bus, in the order of their addresses
+pub struct Scan<'a, I2C> {
+    i2c: &'a mut I2C,
+    next: u8,
+}
+
+impl<'a, I2C> Iterator for Scan<'a, I2C>
+where
+    I2C: Read + WriteRead,
+{
+    type Item = Found;
+
+    fn next(&mut self) -> Option<Found> {
+        while self.next <= LAST_ADDRESS {
+            let address = self.next;
+            self.next += 1;
+
+            if probe(self.i2c, address) {
+                let identity = identify(self.i2c, address);
+                return Some(Found { address, identity });
+            }
+        }
+
+        None
+    }
+}
+
+/// Probe all addresses from `FIRST_ADDRESS` to `LAST_ADDRESS`, a missing
+/// device does not acknowledge its address, so the whole scan takes
+/// only about 15 ms at 100 kHz
+pub fn scan<I2C>(i2c: &mut I2C) -> Scan<'_, I2C>
+where
+    I2C: Read + WriteRead,
+{
+    Scan { i2c, next: FIRST_ADDRESS }
+}
+
+/// Find the first device of any of `devices` with a confirmed chip ID
+pub fn find<I2C>(i2c: &mut I2C, devices: &[Device]) -> Option<Found>
+where
+    I2C: Read + WriteRead,
+{
+    scan(i2c).find(|found| found.identity.confirmed && devices.contains(&found.identity.device))
+}
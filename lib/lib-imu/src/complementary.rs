@@ -0,0 +1,136 @@
+use crate::math::{div_round, sin_cos};
+use crate::orientation::{millidegrees, tilt, Fusion, Orientation};
+use crate::units::Axes;
+
+/// Full circle in microdegrees
+const FULL_CIRCLE: i64 = 360_000_000;
+
+/// Cosine of 89 degrees, 15 fractional bits, the rates are limited
+/// there instead of growing without bounds
+const MIN_COS_PITCH: i64 = 572;
+
+/// Angles integrated from the angular rate, pulled towards the tilt
+/// measured by the accelerometer
+///
+/// The gyroscope is precise over a short time but drifts, the
+/// accelerometer does not drift but every movement disturbs it. Changes
+/// faster than the time constant come from the gyroscope, slower ones
+/// from the accelerometer.
+pub struct Complementary {
+    sample_rate_hz: i64,
+    /// Weight of the gyroscope angle, 16 fractional bits
+    gyro_weight: i64,
+    /// Roll, pitch and yaw in microdegrees, `None` before the first sample
+    angles: Option<[i64; 3]>,
+}
+
+impl Complementary {
+    /// Filter updated `sample_rate_hz` times a second, 500 - 2000 ms
+    /// is a good `time_constant_ms` for a hand held device
+    pub fn new(sample_rate_hz: u32, time_constant_ms: u32) -> Self {
+        let period = time_constant_ms as i64*sample_rate_hz as i64;
+
+        Self {
+            sample_rate_hz: sample_rate_hz.max(1) as i64,
+            gyro_weight: (period << 16)/(period + 1000),
+            angles: None,
+        }
+    }
+}
+
+impl Fusion for Complementary {
+    fn update(&mut self, acceleration: &Axes, angular_rate: &Axes) {
+        let (roll, pitch) = tilt(acceleration);
+        let has_gravity = *acceleration != Axes::default();
+
+        let angles = match (self.angles, has_gravity) {
+            (Some(angles), _) => angles,
+            (None, true) => {
+                self.angles = Some([roll, pitch, 0]);
+                return;
+            }
+            (None, false) => return,
+        };
+
+        // Angular rate about the axes of the sensor to the rates of the
+        // angles, the yaw and the roll are undefined at 90 degrees pitch
+        let (sin_roll, cos_roll) = sin_cos(angles[0]);
+        let (sin_pitch, cos_pitch) = sin_cos(angles[1]);
+        let cos_pitch = cos_pitch.max(MIN_COS_PITCH);
+        let (x, y, z) = (angular_rate.x as i64, angular_rate.y as i64, angular_rate.z as i64);
+
+        let vertical = (y*sin_roll + z*cos_roll) >> 15;
+        let rates = [
+            x + vertical*sin_pitch/cos_pitch,
+            (y*cos_roll - z*sin_roll) >> 15,
+            (vertical << 15)/cos_pitch,
+        ];
+
+        let step = |rate: i64| div_round(rate*1000, self.sample_rate_hz);
+        let mut angles = [0, 1, 2].map(|index| wrap(angles[index] + step(rates[index])));
+
+        // Nothing to pull towards in a free fall
+        if has_gravity {
+            let accel_weight = (1 << 16) - self.gyro_weight;
+            angles[0] = wrap(angles[0] + ((wrap(roll - angles[0])*accel_weight) >> 16));
+            angles[1] = wrap(angles[1] + ((wrap(pitch - angles[1])*accel_weight) >> 16));
+        }
+
+        self.angles = Some(angles);
+    }
+
+    fn orientation(&self) -> Orientation {
+        let angles = self.angles.unwrap_or_default();
+
+        Orientation {
+            roll: millidegrees(angles[0]),
+            pitch: millidegrees(angles[1]),
+            yaw: millidegrees(angles[2]),
+        }
+    }
+}
+
+/// Angle in microdegrees wrapped to -180 (exclusive) to 180 degrees
+fn wrap(angle: i64) -> i64 {
+    let angle = angle.rem_euclid(FULL_CIRCLE);
+
+    match angle > FULL_CIRCLE/2 {
+        true => angle - FULL_CIRCLE,
+        false => angle,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orientation::scenarios::{self, RATE_HZ};
+
+    fn filter() -> Complementary {
+        Complementary::new(RATE_HZ, 1000)
+    }
+
+    #[test]
+    fn at_rest() {
+        scenarios::at_rest(filter);
+    }
+
+    #[test]
+    fn yaw_spin() {
+        scenarios::yaw_spin(&mut filter());
+    }
+
+    #[test]
+    fn tilt_step() {
+        scenarios::tilt_step(&mut filter(), 60);
+    }
+
+    #[test]
+    fn free_fall() {
+        scenarios::free_fall(&mut filter());
+    }
+
+    #[test]
+    fn recorded() {
+        scenarios::recorded(&mut filter());
+    }
+}
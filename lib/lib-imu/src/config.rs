@@ -0,0 +1,141 @@
+use crate::math::div_round;
+
+/// Accelerometer full scale, a wider range lowers the resolution
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccelRange {
+    G2 = 0,
+    G4 = 1,
+    G8 = 2,
+    G16 = 3,
+}
+
+impl AccelRange {
+    /// Full scale in g
+    pub fn g(&self) -> u32 {
+        2 << *self as u32
+    }
+
+    /// Raw value to milli-g, 16384 LSB/g at +-2 g, rounded
+    pub fn milli_g(&self, raw: i16) -> i32 {
+        div_round((raw as i64*1000) << *self as u32, 16_384) as i32
+    }
+}
+
+/// Gyroscope full scale, a wider range lowers the resolution
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GyroRange {
+    Dps250 = 0,
+    Dps500 = 1,
+    Dps1000 = 2,
+    Dps2000 = 3,
+}
+
+impl GyroRange {
+    /// Full scale in degrees per second
+    pub fn dps(&self) -> u32 {
+        250 << *self as u32
+    }
+
+    /// Raw value to millidegrees per second, 131 LSB/dps at +-250 dps,
+    /// rounded
+    pub fn millidegrees_per_second(&self, raw: i16) -> i32 {
+        div_round((raw as i64*1000) << *self as u32, 131) as i32
+    }
+}
+
+/// Digital low pass filter of both the accelerometer and the gyroscope,
+/// the bandwidth of the accelerometer, the gyroscope one is about the same
+///
+/// A lower bandwidth filters out vibrations, but delays the signal
+/// by up to 19 ms.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LowPass {
+    /// Filter off, the gyroscope is sampled at 8 kHz
+    Hz260 = 0,
+    Hz184 = 1,
+    Hz94 = 2,
+    Hz44 = 3,
+    Hz21 = 4,
+    Hz10 = 5,
+    Hz5 = 6,
+}
+
+impl LowPass {
+    pub fn hz(&self) -> u32 {
+        match self {
+            LowPass::Hz260 => 260,
+            LowPass::Hz184 => 184,
+            LowPass::Hz94 => 94,
+            LowPass::Hz44 => 44,
+            LowPass::Hz21 => 21,
+            LowPass::Hz10 => 10,
+            LowPass::Hz5 => 5,
+        }
+    }
+
+    /// Rate the gyroscope is sampled at, the sample rate divider
+    /// divides it
+    pub fn gyro_rate_hz(&self) -> u32 {
+        match self {
+            LowPass::Hz260 => 8000,
+            _ => 1000,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    pub accel_range: AccelRange,
+    pub gyro_range: GyroRange,
+    pub low_pass: LowPass,
+    /// Sample rate is the gyroscope rate divided by `1 + divider`,
+    /// the accelerometer is sampled at 1 kHz at most
+    pub sample_rate_divider: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self::orientation()
+    }
+}
+
+impl Config {
+    /// Finest resolution, slow movements filtered at 44 Hz and sampled
+    /// at 100 Hz, good for measuring the orientation of the board
+    pub const fn orientation() -> Self {
+        Self {
+            accel_range: AccelRange::G2,
+            gyro_range: GyroRange::Dps250,
+            low_pass: LowPass::Hz44,
+            sample_rate_divider: 9,
+        }
+    }
+
+    /// Widest ranges, unfiltered and sampled at 1 kHz, for fast
+    /// movements and shocks
+    pub const fn motion() -> Self {
+        Self {
+            accel_range: AccelRange::G16,
+            gyro_range: GyroRange::Dps2000,
+            low_pass: LowPass::Hz260,
+            sample_rate_divider: 7,
+        }
+    }
+
+    /// Rate new values are available at
+    pub fn sample_rate_hz(&self) -> u32 {
+        self.low_pass.gyro_rate_hz()/(1 + self.sample_rate_divider as u32)
+    }
+
+    pub(crate) fn config(&self) -> u8 {
+        self.low_pass as u8
+    }
+
+    pub(crate) fn gyro_config(&self) -> u8 {
+        (self.gyro_range as u8) << 3
+    }
+
+    pub(crate) fn accel_config(&self) -> u8 {
+        (self.accel_range as u8) << 3
+    }
+}
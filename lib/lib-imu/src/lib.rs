@@ -0,0 +1,35 @@
+#![no_std]
+
+//! MPU6050 accelerometer and gyroscope and orientation sensor fusion
+//!
+//! `detect` probes both I2C addresses of the sensor for its WHO_AM_I
+//! register, `Mpu6050` configures the full scale ranges, the digital low
+//! pass filter and the sample rate and reads `Measurement`s converted to
+//! milli-g, millidegrees per second and hundredths of a degree Celsius,
+//! all in fixed point arithmetic.
+//!
+//! `Complementary`, `Madgwick` and `Mahony` filters implement `Fusion`,
+//! they combine the angular rate with the measured gravity sampled at
+//! a fixed rate into a stable roll, pitch and relative yaw `Orientation`.
+//! They depend on the `Axes` only, so any other sensor can be used.
+
+mod complementary;
+mod config;
+mod madgwick;
+mod mahony;
+mod math;
+mod mpu6050;
+mod orientation;
+mod quaternion;
+mod registers;
+mod units;
+
+pub use complementary::Complementary;
+pub use config::{AccelRange, Config, GyroRange, LowPass};
+pub use madgwick::Madgwick;
+pub use mahony::Mahony;
+pub use mpu6050::{detect, Error, Measurement, Mpu6050};
+pub use orientation::{Fusion, Orientation};
+pub use quaternion::Quaternion;
+pub use registers::{ADDRESS_AD0_HIGH, ADDRESS_AD0_LOW, WHO_AM_I_MPU6050};
+pub use units::{Axes, Temperature};
@@ -0,0 +1,112 @@
+use crate::math::{mul, normalize};
+use crate::orientation::{Fusion, Orientation};
+use crate::quaternion::{half_angle_scale, half_angles, Quaternion};
+use crate::units::Axes;
+
+/// Madgwick gradient descent filter
+///
+/// The quaternion is rotated by the angular rate and stepped against the
+/// gradient of the difference between the gravity it predicts and the
+/// measured one. The gain `beta` is the rate of the correction, higher
+/// gains converge faster but follow the accelerometer noise more.
+pub struct Madgwick {
+    quaternion: Option<Quaternion>,
+    half_angle_scale: i64,
+    /// `beta` times the sample period, 30 fractional bits
+    beta_step: i64,
+}
+
+impl Madgwick {
+    /// Filter updated `sample_rate_hz` times a second with the gain in
+    /// thousandths of a radian per second, 30 - 100 is typical
+    pub fn new(sample_rate_hz: u32, beta_milli: u32) -> Self {
+        Self {
+            quaternion: None,
+            half_angle_scale: half_angle_scale(sample_rate_hz),
+            beta_step: ((beta_milli as i64) << 30)/(1000*sample_rate_hz.max(1) as i64),
+        }
+    }
+
+    pub fn quaternion(&self) -> Quaternion {
+        self.quaternion.unwrap_or_default()
+    }
+}
+
+impl Fusion for Madgwick {
+    fn update(&mut self, acceleration: &Axes, angular_rate: &Axes) {
+        let quaternion = match self.quaternion {
+            Some(quaternion) => quaternion,
+            None => {
+                self.quaternion = Quaternion::from_acceleration(acceleration);
+                return;
+            }
+        };
+
+        let mut step = quaternion.derivative(half_angles(angular_rate, self.half_angle_scale));
+        let gravity = [acceleration.x, acceleration.y, acceleration.z].map(|value| value as i64);
+
+        // Nothing to correct with in a free fall
+        if let Some(measured) = normalize(gravity) {
+            let [w, x, y, z] = quaternion.components();
+            let predicted = quaternion.gravity();
+            let f = [0, 1, 2].map(|axis| predicted[axis] - measured[axis]);
+
+            // Half of the gradient, the transposed Jacobian of the
+            // predicted gravity times the difference
+            let gradient = [
+                -mul(y, f[0]) + mul(x, f[1]),
+                mul(z, f[0]) + mul(w, f[1]) - 2*mul(x, f[2]),
+                -mul(w, f[0]) + mul(z, f[1]) - 2*mul(y, f[2]),
+                mul(x, f[0]) + mul(y, f[1]),
+            ];
+
+            if let Some(gradient) = normalize(gradient) {
+                for (value, gradient) in step.iter_mut().zip(gradient) {
+                    *value -= mul(self.beta_step, gradient);
+                }
+            }
+        }
+
+        let components = quaternion.components();
+        self.quaternion = Some(Quaternion::normalized([0, 1, 2, 3].map(|index| components[index] + step[index])));
+    }
+
+    fn orientation(&self) -> Orientation {
+        self.quaternion().orientation()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orientation::scenarios::{self, RATE_HZ};
+
+    fn filter() -> Madgwick {
+        Madgwick::new(RATE_HZ, 60)
+    }
+
+    #[test]
+    fn at_rest() {
+        scenarios::at_rest(filter);
+    }
+
+    #[test]
+    fn yaw_spin() {
+        scenarios::yaw_spin(&mut filter());
+    }
+
+    #[test]
+    fn tilt_step() {
+        scenarios::tilt_step(&mut filter(), 60);
+    }
+
+    #[test]
+    fn free_fall() {
+        scenarios::free_fall(&mut filter());
+    }
+
+    #[test]
+    fn recorded() {
+        scenarios::recorded(&mut filter());
+    }
+}
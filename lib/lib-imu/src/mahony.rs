@@ -0,0 +1,122 @@
+use crate::math::{mul, normalize, ONE};
+use crate::orientation::{Fusion, Orientation};
+use crate::quaternion::{half_angle_scale, half_angles, Quaternion};
+use crate::units::Axes;
+
+/// Limit of the integral, 1 rad/s is far above any gyroscope offset,
+/// it keeps the integral from winding up while the sensor is shaken
+const MAX_INTEGRAL: i64 = ONE;
+
+/// Mahony complementary filter on the rotation group
+///
+/// The error between the measured and the predicted gravity, their cross
+/// product, is fed back to the angular rate by a PI controller. The
+/// integral term learns the gyroscope offset of the roll and pitch axes.
+pub struct Mahony {
+    quaternion: Option<Quaternion>,
+    half_angle_scale: i64,
+    /// Proportional gain in thousandths
+    kp_milli: i64,
+    /// Integral gain times the sample period, 30 fractional bits
+    ki_step: i64,
+    /// Integral of the error in radians per second, 30 fractional bits
+    integral: [i64; 3],
+    /// Half of the sample period, 30 fractional bits
+    half_period: i64,
+}
+
+impl Mahony {
+    /// Filter updated `sample_rate_hz` times a second with the gains in
+    /// thousandths, `kp` 500 - 2000 and `ki` 0 - 100 are typical
+    pub fn new(sample_rate_hz: u32, kp_milli: u32, ki_milli: u32) -> Self {
+        let sample_rate_hz = sample_rate_hz.max(1) as i64;
+
+        Self {
+            quaternion: None,
+            half_angle_scale: half_angle_scale(sample_rate_hz as u32),
+            kp_milli: kp_milli as i64,
+            ki_step: ((ki_milli as i64) << 30)/(1000*sample_rate_hz),
+            integral: [0; 3],
+            half_period: (1 << 30)/(2*sample_rate_hz),
+        }
+    }
+
+    pub fn quaternion(&self) -> Quaternion {
+        self.quaternion.unwrap_or_default()
+    }
+}
+
+impl Fusion for Mahony {
+    fn update(&mut self, acceleration: &Axes, angular_rate: &Axes) {
+        let quaternion = match self.quaternion {
+            Some(quaternion) => quaternion,
+            None => {
+                self.quaternion = Quaternion::from_acceleration(acceleration);
+                return;
+            }
+        };
+
+        let mut h = half_angles(angular_rate, self.half_angle_scale);
+        let gravity = [acceleration.x, acceleration.y, acceleration.z].map(|value| value as i64);
+
+        // Nothing to correct with in a free fall
+        if let Some(a) = normalize(gravity) {
+            let v = quaternion.gravity();
+            let error = [
+                mul(a[1], v[2]) - mul(a[2], v[1]),
+                mul(a[2], v[0]) - mul(a[0], v[2]),
+                mul(a[0], v[1]) - mul(a[1], v[0]),
+            ];
+
+            for axis in 0..3 {
+                let integral = self.integral[axis] + mul(self.ki_step, error[axis]);
+                self.integral[axis] = integral.clamp(-MAX_INTEGRAL, MAX_INTEGRAL);
+                let correction = error[axis]*self.kp_milli/1000 + self.integral[axis];
+                h[axis] += mul(correction, self.half_period);
+            }
+        }
+
+        let components = quaternion.components();
+        let step = quaternion.derivative(h);
+        self.quaternion = Some(Quaternion::normalized([0, 1, 2, 3].map(|index| components[index] + step[index])));
+    }
+
+    fn orientation(&self) -> Orientation {
+        self.quaternion().orientation()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orientation::scenarios::{self, RATE_HZ};
+
+    fn filter() -> Mahony {
+        Mahony::new(RATE_HZ, 1000, 50)
+    }
+
+    #[test]
+    fn at_rest() {
+        scenarios::at_rest(filter);
+    }
+
+    #[test]
+    fn yaw_spin() {
+        scenarios::yaw_spin(&mut filter());
+    }
+
+    #[test]
+    fn tilt_step() {
+        scenarios::tilt_step(&mut filter(), 60);
+    }
+
+    #[test]
+    fn free_fall() {
+        scenarios::free_fall(&mut filter());
+    }
+
+    #[test]
+    fn recorded() {
+        scenarios::recorded(&mut filter());
+    }
+}
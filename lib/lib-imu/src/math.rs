@@ -0,0 +1,208 @@
+use lib_fixed_math::{cos_q15, sin_q15, PHASE_FULL};
+
+/// Fixed point format of quaternions and unit vectors, 30 fractional bits
+pub const ONE: i64 = 1 << 30;
+
+/// `atan(2^-i)` in microdegrees, the CORDIC rotation angles
+const ATAN_MICRODEGREES: [i64; 27] = [
+    45_000_000, 26_565_051, 14_036_243, 7_125_016, 3_576_334, 1_789_911, 895_174, 447_614,
+    223_811, 111_906, 55_953, 27_976, 13_988, 6_994, 3_497, 1_749,
+    874, 437, 219, 109, 55, 27, 14, 7,
+    3, 2, 1,
+];
+
+/// Angle of the vector (`x`, `y`) from the X axis in microdegrees,
+/// from -180 (exclusive) to 180 degrees, computed by CORDIC
+pub fn atan2(y: i64, x: i64) -> i32 {
+    if x == 0 && y == 0 {
+        return 0;
+    }
+
+    // Vectors pointing to the left are rotated by 180 degrees, CORDIC
+    // converges within 90 degrees only
+    let (mut x, mut y, mut angle) = match x < 0 {
+        true if y >= 0 => (-x, -y, 180_000_000),
+        true => (-x, -y, -180_000_000),
+        false => (x, y, 0),
+    };
+
+    // Scaled to 38 bits, the length grows by 1.65 during the rotations
+    let bits = 64 - x.max(y.abs()).leading_zeros() as i32;
+    match bits <= 38 {
+        true => (x, y) = (x << (38 - bits), y << (38 - bits)),
+        false => (x, y) = (x >> (bits - 38), y >> (bits - 38)),
+    }
+
+    // Vector is rotated towards the X axis by ever smaller angles,
+    // the angle is their sum
+    for (i, step) in ATAN_MICRODEGREES.iter().enumerate() {
+        let (dx, dy) = (y >> i, x >> i);
+
+        match y {
+            0 => break,
+            y if y > 0 => angle += step,
+            _ => angle -= step,
+        }
+
+        match y > 0 {
+            true => (x, y) = (x + dx, y - dy),
+            false => (x, y) = (x - dx, y + dy),
+        }
+    }
+
+    angle as i32
+}
+
+/// Sine and cosine of an angle in microdegrees, 15 fractional bits
+pub fn sin_cos(microdegrees: i64) -> (i64, i64) {
+    let phase = (microdegrees.rem_euclid(360_000_000)*PHASE_FULL as i64 + 180_000_000)/360_000_000;
+    let phase = phase as u16;
+    (sin_q15(phase) as i64, cos_q15(phase) as i64)
+}
+
+/// Integer square root, rounded down
+pub fn sqrt(value: u64) -> u64 {
+    let mut root = 0;
+    let mut bit = match value {
+        0 => return 0,
+        value => 1_u64 << ((63 - value.leading_zeros()) & !1),
+    };
+    let mut rest = value;
+
+    while bit != 0 {
+        if rest >= root + bit {
+            rest -= root + bit;
+            root = (root >> 1) + bit;
+        } else {
+            root >>= 1;
+        }
+
+        bit >>= 2;
+    }
+
+    root
+}
+
+/// Vector of the same direction with the length `ONE`, `None` for
+/// the zero vector
+pub fn normalize<const N: usize>(vector: [i64; N]) -> Option<[i64; N]> {
+    let max = vector.iter().map(|value| value.unsigned_abs()).max().unwrap_or(0);
+
+    if max == 0 {
+        return None;
+    }
+
+    // Largest component is scaled to 30 bits, so the sum of the squares
+    // fits and the root keeps the precision
+    let bits = 64 - max.leading_zeros() as i32;
+    let scaled = vector.map(|value| match bits <= 30 {
+        true => value << (30 - bits),
+        false => value >> (bits - 30),
+    });
+
+    let length = sqrt(scaled.iter().map(|value| (value*value) as u64).sum()) as i64;
+    Some(scaled.map(|value| value*ONE/length))
+}
+
+/// Product of two values with 30 fractional bits
+pub fn mul(a: i64, b: i64) -> i64 {
+    (a*b) >> 30
+}
+
+/// Division rounding to the nearest, `divisor` has to be positive
+pub fn div_round(value: i64, divisor: i64) -> i64 {
+    match value >= 0 {
+        true => (value + divisor/2)/divisor,
+        false => (value - divisor/2)/divisor,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use super::*;
+
+    /// Difference of two angles in degrees wrapped to -180 - 180
+    fn angle_error(microdegrees: i32, degrees: f64) -> f64 {
+        (microdegrees as f64/1e6 - degrees + 540.0).rem_euclid(360.0) - 180.0
+    }
+
+    #[test]
+    fn atan2_matches_float_over_the_full_circle() {
+        for length in [1000_f64, 1e6, 1e9, 1e15] {
+            for tenths in -1800..1800 {
+                let angle = (tenths as f64/10.0).to_radians();
+                let (y, x) = ((angle.sin()*length).round(), (angle.cos()*length).round());
+                let expected = y.atan2(x).to_degrees();
+
+                // Short vectors are rounded to whole units
+                let tolerance = 1e-5 + 1.0/length*60.0;
+                let error = angle_error(atan2(y as i64, x as i64), expected);
+                assert!(error.abs() < tolerance, "{} {} {}", length, tenths, error);
+            }
+        }
+    }
+
+    #[test]
+    fn atan2_edges() {
+        let near = |microdegrees: i32, expected: i32| (microdegrees - expected).abs() <= 2;
+
+        assert_eq!(atan2(0, 0), 0);
+        assert_eq!(atan2(0, 1000), 0);
+        assert!(near(atan2(1000, 0), 90_000_000));
+        assert!(near(atan2(-1000, 0), -90_000_000));
+        assert_eq!(atan2(0, -1000), 180_000_000);
+
+        // Just below the negative X axis the angle flips to -180 degrees
+        assert!(atan2(-1, -1_000_000) < -179_999_000);
+        assert!(atan2(1, -1_000_000) > 179_999_000);
+
+        // Pitch of a vertical sensor, gravity along the X axis only
+        assert!(near(atan2(-1000, sqrt(0) as i64), -90_000_000));
+        assert!(near(atan2(i32::MAX as i64, 1), 90_000_000));
+    }
+
+    #[test]
+    fn sin_cos_matches_float_over_the_full_circle() {
+        for millidegrees in (-720_000..720_000).step_by(37) {
+            let (sin, cos) = sin_cos(millidegrees*1000);
+            let angle = (millidegrees as f64/1000.0).to_radians();
+
+            assert!((sin as f64 - angle.sin()*32767.0).abs() < 3.0, "{}", millidegrees);
+            assert!((cos as f64 - angle.cos()*32767.0).abs() < 3.0, "{}", millidegrees);
+        }
+    }
+
+    #[test]
+    fn sin_cos_edges() {
+        assert_eq!(sin_cos(0), (0, 32767));
+        assert_eq!(sin_cos(90_000_000), (32767, 0));
+        assert_eq!(sin_cos(-90_000_000), (-32767, 0));
+        assert_eq!(sin_cos(180_000_000), (0, -32767));
+        assert_eq!(sin_cos(-180_000_000), (0, -32767));
+        assert_eq!(sin_cos(360_000_000), (0, 32767));
+    }
+
+    #[test]
+    fn sqrt_rounds_down() {
+        for value in (0..100_000).chain([u64::MAX, 1 << 62, (1 << 62) - 1]) {
+            let root = sqrt(value);
+            assert!(root*root <= value, "{}", value);
+            assert!((root + 1).checked_mul(root + 1).is_none_or(|square| square > value), "{}", value);
+        }
+    }
+
+    #[test]
+    fn normalize_keeps_the_direction() {
+        assert_eq!(normalize([0, 0, 0]), None);
+        assert_eq!(normalize([0, 0, 5]), Some([0, 0, ONE]));
+        assert_eq!(normalize([-3, 0, 0, 0]), Some([-ONE, 0, 0, 0]));
+
+        for vector in [[3, 4, 0], [1000, -1000, 707], [i32::MAX as i64, 1, -5], [1, 1, 1]] {
+            let unit = normalize(vector).unwrap();
+            let length = unit.iter().map(|value| (value*value) as f64).sum::<f64>().sqrt();
+            assert!((length/ONE as f64 - 1.0).abs() < 1e-8, "{:?}", vector);
+        }
+    }
+}
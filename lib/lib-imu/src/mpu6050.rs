@@ -0,0 +1,146 @@
+use embedded_hal::blocking::i2c;
+
+use crate::config::Config;
+use crate::registers::{
+    read, write, ACCEL_CONFIG, ADDRESS_AD0_HIGH, ADDRESS_AD0_LOW, CONFIG, DATA, GYRO_CONFIG,
+    POWER_MANAGEMENT, SAMPLE_RATE_DIVIDER, WHO_AM_I, WHO_AM_I_MPU6050,
+};
+use crate::units::{Axes, Temperature};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Communication with the sensor failed, or no sensor answered
+    Bus,
+    /// WHO_AM_I register contains an ID of another device
+    UnknownDevice(u8),
+}
+
+/// Converted values of a single sample
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Measurement {
+    /// Milli-g, the axis pointing up reads about 1000 at rest
+    pub acceleration: Axes,
+    /// Millidegrees per second, counterclockwise when looking
+    /// against the axis
+    pub angular_rate: Axes,
+    pub temperature: Temperature,
+}
+
+/// MPU6050 sampling continuously, every read returns the latest sample
+///
+/// The sensor does not keep the bus, it is passed to every call, so it
+/// can share the bus with other devices.
+pub struct Mpu6050 {
+    address: u8,
+    config: Config,
+}
+
+impl Mpu6050 {
+    /// Check the WHO_AM_I register, wake the sensor up and configure it
+    pub fn new<I2C>(i2c: &mut I2C, address: u8, config: Config) -> Result<Self, Error>
+    where
+        I2C: i2c::WriteRead + i2c::Write,
+    {
+        check_id(i2c, address)?;
+
+        // Leave the sleep mode, the gyroscope X clock is more stable
+        // than the internal oscillator
+        write(i2c, address, POWER_MANAGEMENT, 0x01)?;
+
+        let mpu = Self { address, config };
+        mpu.configure(i2c)?;
+        Ok(mpu)
+    }
+
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    pub fn set_config<I2C>(&mut self, i2c: &mut I2C, config: Config) -> Result<(), Error>
+    where
+        I2C: i2c::Write,
+    {
+        self.config = config;
+        self.configure(i2c)
+    }
+
+    /// Read the latest sample and convert it according to the ranges
+    pub fn read<I2C>(&self, i2c: &mut I2C) -> Result<Measurement, Error>
+    where
+        I2C: i2c::WriteRead,
+    {
+        let raw = self.read_raw(i2c)?;
+        let accel = self.config.accel_range;
+        let gyro = self.config.gyro_range;
+
+        Ok(Measurement {
+            acceleration: Axes::new(accel.milli_g(raw[0]), accel.milli_g(raw[1]), accel.milli_g(raw[2])),
+            angular_rate: Axes::new(
+                gyro.millidegrees_per_second(raw[4]),
+                gyro.millidegrees_per_second(raw[5]),
+                gyro.millidegrees_per_second(raw[6]),
+            ),
+            temperature: Temperature::from_raw(raw[3]),
+        })
+    }
+
+    /// Raw values of the latest sample, acceleration X, Y, Z, temperature
+    /// and angular rate X, Y, Z
+    pub fn read_raw<I2C>(&self, i2c: &mut I2C) -> Result<[i16; 7], Error>
+    where
+        I2C: i2c::WriteRead,
+    {
+        // Burst read makes sure all values come from the same sample
+        let mut data = [0u8; 14];
+        read(i2c, self.address, DATA, &mut data)?;
+
+        let mut raw = [0i16; 7];
+
+        for (value, bytes) in raw.iter_mut().zip(data.chunks_exact(2)) {
+            *value = i16::from_be_bytes([bytes[0], bytes[1]]);
+        }
+
+        Ok(raw)
+    }
+
+    fn configure<I2C>(&self, i2c: &mut I2C) -> Result<(), Error>
+    where
+        I2C: i2c::Write,
+    {
+        write(i2c, self.address, SAMPLE_RATE_DIVIDER, self.config.sample_rate_divider)?;
+        write(i2c, self.address, CONFIG, self.config.config())?;
+        write(i2c, self.address, GYRO_CONFIG, self.config.gyro_config())?;
+        write(i2c, self.address, ACCEL_CONFIG, self.config.accel_config())
+    }
+}
+
+/// Probe both addresses for the WHO_AM_I register, returns the address
+/// of the sensor, fails with `Error::Bus` when no sensor answers
+pub fn detect<I2C: i2c::WriteRead>(i2c: &mut I2C) -> Result<u8, Error> {
+    let mut result = Err(Error::Bus);
+
+    for address in [ADDRESS_AD0_LOW, ADDRESS_AD0_HIGH] {
+        match check_id(i2c, address) {
+            Ok(()) => return Ok(address),
+            // Another device answering is more telling than no answer
+            Err(error @ Error::UnknownDevice(_)) => result = Err(error),
+            Err(Error::Bus) => {}
+        }
+    }
+
+    result
+}
+
+fn check_id<I2C: i2c::WriteRead>(i2c: &mut I2C, address: u8) -> Result<(), Error> {
+    let mut id = [0u8; 1];
+    read(i2c, address, WHO_AM_I, &mut id)?;
+
+    match id[0] {
+        WHO_AM_I_MPU6050 => Ok(()),
+        id => Err(Error::UnknownDevice(id)),
+    }
+}
@@ -0,0 +1,250 @@
+use crate::math::{atan2, div_round, sqrt};
+use crate::units::Axes;
+
+/// Angles in millidegrees, yaw about the Z axis applied first, then
+/// pitch about the Y axis and roll about the X axis
+///
+/// Roll is from -180 to 180 degrees, pitch from -90 to 90 degrees. Yaw is
+/// relative to the start, gravity does not tell where north is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Orientation {
+    pub roll: i32,
+    pub pitch: i32,
+    pub yaw: i32,
+}
+
+impl Orientation {
+    /// Roll and pitch from the direction of gravity only, valid while
+    /// the device is not accelerating, yaw is zero
+    pub fn from_acceleration(acceleration: &Axes) -> Self {
+        let (roll, pitch) = tilt(acceleration);
+
+        Self {
+            roll: millidegrees(roll),
+            pitch: millidegrees(pitch),
+            yaw: 0,
+        }
+    }
+}
+
+/// Filter fusing the accelerometer and the gyroscope sampled at the rate
+/// the filter was created with
+pub trait Fusion {
+    /// Add a sample, acceleration in milli-g and angular rate in
+    /// millidegrees per second, the first one sets the initial tilt
+    fn update(&mut self, acceleration: &Axes, angular_rate: &Axes);
+
+    fn orientation(&self) -> Orientation;
+}
+
+/// Roll and pitch in microdegrees from the direction of gravity
+pub(crate) fn tilt(acceleration: &Axes) -> (i64, i64) {
+    let (x, y, z) = (acceleration.x as i64, acceleration.y as i64, acceleration.z as i64);
+    let roll = atan2(y, z);
+    let pitch = atan2(-x, sqrt((y*y + z*z) as u64) as i64);
+    (roll as i64, pitch as i64)
+}
+
+pub(crate) fn millidegrees(microdegrees: i64) -> i32 {
+    div_round(microdegrees, 1000) as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tilt_from_acceleration() {
+        let flat = Orientation::from_acceleration(&Axes::new(0, 0, 1000));
+        assert_eq!(flat, Orientation::default());
+
+        let upside_down = Orientation::from_acceleration(&Axes::new(0, 0, -1000));
+        assert_eq!((upside_down.roll, upside_down.pitch), (180_000, 0));
+
+        let on_side = Orientation::from_acceleration(&Axes::new(0, 1000, 0));
+        assert_eq!((on_side.roll, on_side.pitch), (90_000, 0));
+
+        // Nose up and down, roll is undefined and taken as zero
+        let nose_up = Orientation::from_acceleration(&Axes::new(-1000, 0, 0));
+        assert_eq!((nose_up.roll, nose_up.pitch), (0, 90_000));
+
+        let nose_down = Orientation::from_acceleration(&Axes::new(1000, 0, 0));
+        assert_eq!((nose_down.roll, nose_down.pitch), (0, -90_000));
+
+        let tilted = Orientation::from_acceleration(&Axes::new(-500, 500, 707));
+        let roll = 500_f64.atan2(707.0).to_degrees();
+        // Length of the YZ projection is an integer square root, 865
+        let pitch = 500_f64.atan2(865.0).to_degrees();
+        assert!((tilted.roll as f64/1000.0 - roll).abs() < 0.001, "{:?}", tilted);
+        assert!((tilted.pitch as f64/1000.0 - pitch).abs() < 0.001, "{:?}", tilted);
+    }
+}
+
+/// Synthetic motions every filter is checked with
+#[cfg(test)]
+pub(crate) mod scenarios {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+    use crate::config::Config;
+
+    pub const RATE_HZ: u32 = 100;
+
+    const STILL: Axes = Axes::new(0, 0, 0);
+
+    /// Acceleration of a sensor at rest with the roll and pitch in degrees
+    pub fn gravity(roll: f64, pitch: f64) -> Axes {
+        let (roll, pitch) = (roll.to_radians(), pitch.to_radians());
+
+        Axes::new(
+            (-pitch.sin()*1000.0).round() as i32,
+            (roll.sin()*pitch.cos()*1000.0).round() as i32,
+            (roll.cos()*pitch.cos()*1000.0).round() as i32,
+        )
+    }
+
+    /// Difference of the angles in degrees, wrapped to -180 - 180
+    fn error(millidegrees: i32, degrees: f64) -> f64 {
+        (millidegrees as f64/1000.0 - degrees + 540.0).rem_euclid(360.0) - 180.0
+    }
+
+    fn assert_near(filter: &impl Fusion, roll: f64, pitch: f64, yaw: f64, tolerance: f64) {
+        let orientation = filter.orientation();
+        let errors = [error(orientation.roll, roll), error(orientation.pitch, pitch), error(orientation.yaw, yaw)];
+        assert!(errors.iter().all(|error| error.abs() <= tolerance), "{:?} {:?}", orientation, errors);
+    }
+
+    fn run(filter: &mut impl Fusion, seconds: u32, acceleration: Axes, angular_rate: Axes) {
+        for _ in 0..seconds*RATE_HZ {
+            filter.update(&acceleration, &angular_rate);
+        }
+    }
+
+    /// Sensor lying still, flat and tilted, the first sample sets the tilt
+    pub fn at_rest<F: Fusion>(new: impl Fn() -> F) {
+        let mut filter = new();
+        run(&mut filter, 10, gravity(0.0, 0.0), STILL);
+        assert_near(&filter, 0.0, 0.0, 0.0, 0.05);
+
+        let mut filter = new();
+        filter.update(&gravity(30.0, -20.0), &STILL);
+        assert_near(&filter, 30.0, -20.0, 0.0, 0.2);
+
+        run(&mut filter, 10, gravity(30.0, -20.0), STILL);
+        assert_near(&filter, 30.0, -20.0, 0.0, 0.2);
+    }
+
+    /// Flat sensor spinning about the vertical axis at 45 degrees per
+    /// second, yaw follows the integrated rate through the wrap at 180
+    pub fn yaw_spin(filter: &mut impl Fusion) {
+        let rate = Axes::new(0, 0, 45_000);
+        filter.update(&gravity(0.0, 0.0), &STILL);
+
+        for sample in 1..=10*RATE_HZ {
+            filter.update(&gravity(0.0, 0.0), &rate);
+            assert_near(filter, 0.0, 0.0, 45.0*sample as f64/RATE_HZ as f64, 0.2);
+        }
+    }
+
+    /// Rotation by 90 degrees about the X axis tracked by the gyroscope,
+    /// then a step back by 90 degrees the gyroscope did not see, the
+    /// filter converges to the tilt measured by the accelerometer
+    pub fn tilt_step(filter: &mut impl Fusion, seconds: u32) {
+        filter.update(&gravity(0.0, 0.0), &STILL);
+
+        for sample in 1..=RATE_HZ {
+            let roll = 90.0*sample as f64/RATE_HZ as f64;
+            filter.update(&gravity(roll, 0.0), &Axes::new(90_000, 0, 0));
+        }
+
+        // Accelerometer is a sample ahead of the state it corrects, the
+        // gradient filters run ahead by up to their correction per second
+        assert_near(filter, 90.0, 0.0, 0.0, 1.5);
+
+        run(filter, seconds, gravity(0.0, 0.0), STILL);
+        assert_near(filter, 0.0, 0.0, 0.0, 1.0);
+    }
+
+    /// No gravity to correct the angles, the gyroscope is integrated alone
+    pub fn free_fall(filter: &mut impl Fusion) {
+        run(filter, 1, gravity(20.0, 10.0), STILL);
+        assert_near(filter, 20.0, 10.0, 0.0, 0.2);
+
+        run(filter, 1, STILL, STILL);
+        assert_near(filter, 20.0, 10.0, 0.0, 0.2);
+
+        // Spin about the vertical axis of the world, 45 degrees per second
+        // along the direction of gravity in the sensor axes
+        let vertical = gravity(20.0, 10.0);
+        run(filter, 1, STILL, Axes::new(vertical.x*45, vertical.y*45, vertical.z*45));
+        assert_near(filter, 20.0, 10.0, 45.0, 0.5);
+    }
+
+    /// Raw samples in the format of `Mpu6050::read_raw`, one per line
+    const CAPTURE: &str = include_str!("../testdata/rest_and_motion.csv");
+
+    /// Samples of the capture converted like `Mpu6050::read` does
+    fn capture() -> Vec<(Axes, Axes)> {
+        let config = Config::orientation();
+        let (accel, gyro) = (config.accel_range, config.gyro_range);
+
+        CAPTURE.lines()
+            .filter(|line| !line.starts_with('#'))
+            .map(|line| {
+                let raw: Vec<i16> = line.split(',').map(|value| value.parse().unwrap()).collect();
+                (
+                    Axes::new(accel.milli_g(raw[0]), accel.milli_g(raw[1]), accel.milli_g(raw[2])),
+                    Axes::new(
+                        gyro.millidegrees_per_second(raw[4]),
+                        gyro.millidegrees_per_second(raw[5]),
+                        gyro.millidegrees_per_second(raw[6]),
+                    ),
+                )
+            })
+            .collect()
+    }
+
+    /// Tilt of the averaged acceleration, the reference at rest
+    fn average_tilt(samples: &[(Axes, Axes)]) -> Orientation {
+        let count = samples.len() as i32;
+        let sum = |axis: fn(&Axes) -> i32| samples.iter().map(|(acceleration, _)| axis(acceleration)).sum::<i32>()/count;
+        Orientation::from_acceleration(&Axes::new(sum(|a| a.x), sum(|a| a.y), sum(|a| a.z)))
+    }
+
+    /// Noisy samples with gyroscope offsets, 6 s at rest, 6 s of motion
+    /// and 4 s at rest again. Roll and pitch stay at the tilt measured by
+    /// the accelerometer while at rest and come back to it after the
+    /// motion, yaw drifts only by the offset of the gyroscope Z axis.
+    pub fn recorded(filter: &mut impl Fusion) {
+        let samples = capture();
+        let rate = RATE_HZ as usize;
+        let (first_rest, last_rest) = (&samples[..6*rate], &samples[13*rate..]);
+
+        let reference = average_tilt(first_rest);
+        for (sample, (acceleration, angular_rate)) in first_rest.iter().enumerate() {
+            filter.update(acceleration, angular_rate);
+
+            if sample >= rate {
+                let (roll, pitch) = (reference.roll as f64/1000.0, reference.pitch as f64/1000.0);
+                let orientation = filter.orientation();
+                assert!(error(orientation.roll, roll).abs() <= 1.0, "{:?} {:?}", orientation, reference);
+                assert!(error(orientation.pitch, pitch).abs() <= 1.0, "{:?} {:?}", orientation, reference);
+            }
+        }
+
+        for (acceleration, angular_rate) in &samples[6*rate..] {
+            filter.update(acceleration, angular_rate);
+        }
+
+        // Motion returns to the starting yaw, what is left is the offset
+        // integrated over the whole capture, offsets of the other axes
+        // add to it while the sensor is tilted
+        let offset = first_rest.iter().map(|(_, rate)| rate.z as f64).sum::<f64>()/first_rest.len() as f64;
+        let drift = offset/1000.0*samples.len() as f64/RATE_HZ as f64;
+
+        let reference = average_tilt(last_rest);
+        assert_near(filter, reference.roll as f64/1000.0, reference.pitch as f64/1000.0, drift, 2.0);
+    }
+}
@@ -0,0 +1,183 @@
+use crate::math::{atan2, mul, normalize, sin_cos, sqrt, ONE};
+use crate::orientation::{millidegrees, tilt, Orientation};
+use crate::units::Axes;
+
+/// `pi*2^46`, converts millidegrees to radians with 30 fractional bits
+/// after dividing by 180000 and shifting by 16 bits
+const PI_Q46: i64 = 221_069_929_750_889;
+
+/// Unit quaternion rotating the earth frame to the sensor frame,
+/// components with 30 fractional bits
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Quaternion {
+    pub w: i32,
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Quaternion {
+    pub const IDENTITY: Quaternion = Quaternion { w: 1 << 30, x: 0, y: 0, z: 0 };
+
+    /// Roll and pitch from the measured gravity with zero yaw, `None`
+    /// in a free fall
+    pub fn from_acceleration(acceleration: &Axes) -> Option<Self> {
+        if *acceleration == Axes::default() {
+            return None;
+        }
+
+        // Rotations by the half angles about the X and Y axes combined
+        let (roll, pitch) = tilt(acceleration);
+        let (sin_roll, cos_roll) = sin_cos(roll/2);
+        let (sin_pitch, cos_pitch) = sin_cos(pitch/2);
+
+        Some(Self::normalized([
+            cos_roll*cos_pitch,
+            sin_roll*cos_pitch,
+            cos_roll*sin_pitch,
+            -sin_roll*sin_pitch,
+        ]))
+    }
+
+    pub fn orientation(&self) -> Orientation {
+        let [w, x, y, z] = self.components();
+
+        let roll = atan2(2*(mul(w, x) + mul(y, z)), ONE - 2*(mul(x, x) + mul(y, y)));
+        let yaw = atan2(2*(mul(w, z) + mul(x, y)), ONE - 2*(mul(y, y) + mul(z, z)));
+
+        // asin(s) is atan2(s, sqrt(1 - s^2))
+        let sine = (2*(mul(w, y) - mul(x, z))).clamp(-ONE, ONE);
+        let cosine = sqrt((ONE*ONE - sine*sine) as u64) as i64;
+        let pitch = atan2(sine, cosine);
+
+        Orientation {
+            roll: millidegrees(roll as i64),
+            pitch: millidegrees(pitch as i64),
+            yaw: millidegrees(yaw as i64),
+        }
+    }
+
+    pub(crate) fn components(&self) -> [i64; 4] {
+        [self.w as i64, self.x as i64, self.y as i64, self.z as i64]
+    }
+
+    pub(crate) fn from_components(components: [i64; 4]) -> Self {
+        let [w, x, y, z] = components.map(|value| value as i32);
+        Self { w, x, y, z }
+    }
+
+    /// Quaternion of the same rotation with the length `ONE`, small
+    /// rounding errors of every update would add up otherwise
+    pub(crate) fn normalized(components: [i64; 4]) -> Self {
+        normalize(components).map(Self::from_components).unwrap_or(Self::IDENTITY)
+    }
+
+    /// Direction of gravity in the sensor frame
+    pub(crate) fn gravity(&self) -> [i64; 3] {
+        let [w, x, y, z] = self.components();
+
+        [
+            2*(mul(x, z) - mul(w, y)),
+            2*(mul(w, x) + mul(y, z)),
+            mul(w, w) - mul(x, x) - mul(y, y) + mul(z, z),
+        ]
+    }
+
+    /// Change of the quaternion rotated by the half angles `h` about the
+    /// axes of the sensor, `q*(exp(0, h) - 1)` up to the third order, so
+    /// fast rotations are not shortened
+    pub(crate) fn derivative(&self, h: [i64; 3]) -> [i64; 4] {
+        let [w, x, y, z] = self.components();
+
+        // Half angles are limited to a radian, far more than the sensor
+        // measures at 10 Hz, exp(0, h) is (cos |h|, sin |h|*h/|h|)
+        let h = h.map(|value| value.clamp(-ONE, ONE));
+        let square = mul(h[0], h[0]) + mul(h[1], h[1]) + mul(h[2], h[2]);
+        let real = -square/2;
+        let h = h.map(|value| mul(value, ONE - square/6));
+
+        [
+            mul(w, real) - mul(x, h[0]) - mul(y, h[1]) - mul(z, h[2]),
+            mul(x, real) + mul(w, h[0]) + mul(y, h[2]) - mul(z, h[1]),
+            mul(y, real) + mul(w, h[1]) - mul(x, h[2]) + mul(z, h[0]),
+            mul(z, real) + mul(w, h[2]) + mul(x, h[1]) - mul(y, h[0]),
+        ]
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+/// Converts the angular rate in millidegrees per second to half of the
+/// angle turned during a sample period in radians, 16 fractional bits
+pub(crate) fn half_angle_scale(sample_rate_hz: u32) -> i64 {
+    PI_Q46/(360_000*sample_rate_hz.max(1) as i64)
+}
+
+/// Half angles turned during a sample period with 30 fractional bits
+pub(crate) fn half_angles(angular_rate: &Axes, scale: i64) -> [i64; 3] {
+    [angular_rate.x, angular_rate.y, angular_rate.z].map(|rate| (rate as i64*scale) >> 16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::orientation::scenarios::gravity;
+
+    #[test]
+    fn identity_is_level() {
+        assert_eq!(Quaternion::default().orientation(), Orientation::default());
+        assert_eq!(Quaternion::IDENTITY.gravity(), [0, 0, ONE]);
+    }
+
+    #[test]
+    fn tilt_round_trip() {
+        assert_eq!(Quaternion::from_acceleration(&Axes::default()), None);
+
+        for (roll, pitch) in [(0.0, 0.0), (30.0, -20.0), (-120.0, 45.0), (179.0, 10.0), (10.0, 89.0)] {
+            let acceleration = gravity(roll, pitch);
+            let quaternion = Quaternion::from_acceleration(&acceleration).unwrap();
+
+            let expected = Orientation::from_acceleration(&acceleration);
+            let orientation = quaternion.orientation();
+            assert!((orientation.roll - expected.roll).abs() <= 5, "{:?} {:?}", orientation, expected);
+            assert!((orientation.pitch - expected.pitch).abs() <= 5, "{:?} {:?}", orientation, expected);
+            assert_eq!(orientation.yaw, 0);
+
+            // Gravity of the rotation points along the measured acceleration
+            let measured = [acceleration.x, acceleration.y, acceleration.z].map(|value| value as i64);
+            let measured = crate::math::normalize(measured).unwrap();
+            for (axis, value) in quaternion.gravity().iter().zip(measured) {
+                assert!((axis - value).abs() < ONE/1000, "{:?} {:?}", quaternion.gravity(), measured);
+            }
+        }
+    }
+
+    #[test]
+    fn derivative_turns_by_the_half_angles() {
+        // 90 degrees about Z in a hundred steps
+        let scale = half_angle_scale(100);
+        let h = half_angles(&Axes::new(0, 0, 90_000), scale);
+        let mut quaternion = Quaternion::IDENTITY;
+
+        for _ in 0..100 {
+            let change = quaternion.derivative(h);
+            let components = quaternion.components();
+            quaternion = Quaternion::normalized(core::array::from_fn(|i| components[i] + change[i]));
+        }
+
+        let orientation = quaternion.orientation();
+        assert_eq!((orientation.roll, orientation.pitch), (0, 0));
+        assert!((orientation.yaw - 90_000).abs() <= 5, "{:?}", orientation);
+
+        // Quarter turn in a single step is not shortened, the third
+        // order series overshoots it by about a degree
+        let h = half_angles(&Axes::new(90_000, 0, 0), half_angle_scale(1));
+        let change = Quaternion::IDENTITY.derivative(h);
+        let turned = Quaternion::normalized(core::array::from_fn(|i| Quaternion::IDENTITY.components()[i] + change[i]));
+        assert!((turned.orientation().roll - 90_000).abs() <= 1500, "{:?}", turned.orientation());
+    }
+}
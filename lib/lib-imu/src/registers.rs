@@ -0,0 +1,36 @@
+use embedded_hal::blocking::i2c;
+
+use crate::mpu6050::Error;
+
+/// I2C address with the AD0 pin connected to ground, most modules
+/// have it pulled down
+pub const ADDRESS_AD0_LOW: u8 = 0x68;
+
+/// I2C address with the AD0 pin connected to the supply voltage
+pub const ADDRESS_AD0_HIGH: u8 = 0x69;
+
+/// WHO_AM_I register value, the same for both addresses
+pub const WHO_AM_I_MPU6050: u8 = 0x68;
+
+pub(crate) const SAMPLE_RATE_DIVIDER: u8 = 0x19;
+pub(crate) const CONFIG: u8 = 0x1A;
+pub(crate) const GYRO_CONFIG: u8 = 0x1B;
+pub(crate) const ACCEL_CONFIG: u8 = 0x1C;
+pub(crate) const DATA: u8 = 0x3B;
+pub(crate) const POWER_MANAGEMENT: u8 = 0x6B;
+pub(crate) const WHO_AM_I: u8 = 0x75;
+
+/// Read consecutive registers starting at `register`
+pub(crate) fn read<I2C>(i2c: &mut I2C, address: u8, register: u8, buffer: &mut [u8]) -> Result<(), Error>
+where
+    I2C: i2c::WriteRead,
+{
+    i2c.write_read(address, &[register], buffer).map_err(|_| Error::Bus)
+}
+
+pub(crate) fn write<I2C>(i2c: &mut I2C, address: u8, register: u8, value: u8) -> Result<(), Error>
+where
+    I2C: i2c::Write,
+{
+    i2c.write(address, &[register, value]).map_err(|_| Error::Bus)
+}
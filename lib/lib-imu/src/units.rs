@@ -0,0 +1,51 @@
+use core::fmt;
+
+/// Values of the three axes of the sensor, in the units of the quantity
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Axes {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Axes {
+    pub const fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+}
+
+/// Temperature of the die in hundredths of a degree Celsius, it is a few
+/// degrees above the ambient temperature
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct Temperature(i32);
+
+impl Temperature {
+    pub const fn from_centidegrees(centidegrees: i32) -> Self {
+        Self(centidegrees)
+    }
+
+    /// Raw value of the sensor, 340 LSB/C with 0 at 36.53 C
+    pub const fn from_raw(raw: i16) -> Self {
+        let value = raw as i32*5;
+        let rounded = match value >= 0 {
+            true => (value + 8)/17,
+            false => (value - 8)/17,
+        };
+
+        Self(rounded + 3653)
+    }
+
+    pub const fn centidegrees(&self) -> i32 {
+        self.0
+    }
+}
+
+/// Formatted as °C with two decimals, `-0.05` keeps the sign even
+/// when the integer part is zero
+impl fmt::Display for Temperature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let value = self.0.unsigned_abs();
+        write!(f, "{}{}.{:02}", sign, value/100, value % 100)
+    }
+}
@@ -0,0 +1,16 @@
+#![no_std]
+
+//! Frequency and duty cycle measurement using timers in PWM input mode
+//!
+//! Rising edges of the signal reset the timer, so the first channel
+//! captures the period and the second one the high time of the signal.
+//! The prescaler is adjusted automatically to keep the period within
+//! the 16-bit counter with a good resolution.
+
+mod measurement;
+mod meter;
+mod ranging;
+
+pub use measurement::Measurement;
+pub use meter::{Error, FrequencyMeter};
+pub use ranging::{Ranging, MAX_COUNTS, MIN_COUNTS};
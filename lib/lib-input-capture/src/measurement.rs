@@ -0,0 +1,46 @@
+/// Single period of the measured signal
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    /// Length of the period in timer clock ticks (before the prescaler)
+    pub period_ticks: u32,
+    /// Length of the high level in timer clock ticks
+    pub high_ticks: u32,
+    /// Frequency of the timer clock
+    pub clock_hz: u32,
+}
+
+impl Measurement {
+    pub fn new(period_ticks: u32, high_ticks: u32, clock_hz: u32) -> Self {
+        Self { period_ticks, high_ticks, clock_hz }
+    }
+
+    /// Frequency in thousandths of Hz, enough to show slow signals
+    /// with a sub-hertz resolution
+    pub fn frequency_millihertz(&self) -> u32 {
+        match self.period_ticks {
+            0 => 0,
+            ticks => (self.clock_hz as u64*1000/ticks as u64).min(u32::MAX as u64) as u32,
+        }
+    }
+
+    /// Frequency rounded to whole Hz
+    pub fn frequency_hz(&self) -> u32 {
+        (self.frequency_millihertz() + 500)/1000
+    }
+
+    /// Period in microseconds
+    pub fn period_us(&self) -> u32 {
+        match self.clock_hz {
+            0 => 0,
+            clock => (self.period_ticks as u64*1_000_000/clock as u64) as u32,
+        }
+    }
+
+    /// Duty cycle in tenths of percent (0 - 1000)
+    pub fn duty_permille(&self) -> u16 {
+        match self.period_ticks {
+            0 => 0,
+            ticks => (self.high_ticks.min(ticks) as u64*1000/ticks as u64) as u16,
+        }
+    }
+}
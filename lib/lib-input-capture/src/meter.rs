@@ -0,0 +1,157 @@
+use core::ops::Deref;
+
+use stm32f1xx_hal::afio::MAPR;
+use stm32f1xx_hal::pac::tim2::RegisterBlock;
+use stm32f1xx_hal::rcc::Clocks;
+use stm32f1xx_hal::timer::{CPin, Instance, Remap, Timer, C1};
+
+use crate::measurement::Measurement;
+use crate::ranging::Ranging;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// No edge was detected even with the largest prescaler
+    NoSignal,
+}
+
+/// Frequency and duty cycle meter on the first channel of a general
+/// purpose timer (TIM2, TIM3 or TIM4)
+pub struct FrequencyMeter<TIM, PIN> {
+    tim: TIM,
+    pin: PIN,
+    clock_hz: u32,
+    ranging: Ranging,
+    /// Captures to be dropped after the prescaler changed
+    skip: u8,
+    last: Option<Measurement>,
+}
+
+impl<TIM, PIN> FrequencyMeter<TIM, PIN>
+where
+    TIM: Instance + Deref<Target = RegisterBlock>,
+{
+    /// Start measuring the signal on `pin`, which is the first channel input
+    /// of the timer, signals slower than `min_frequency_hz` are reported
+    /// as missing
+    pub fn new<REMAP>(
+        tim: TIM,
+        pin: PIN,
+        mapr: &mut MAPR,
+        clocks: &Clocks,
+        min_frequency_hz: u32,
+    ) -> Self
+    where
+        REMAP: Remap<Periph = TIM>,
+        PIN: CPin<REMAP, C1>,
+    {
+        REMAP::remap(mapr);
+
+        let clock_hz = TIM::timer_clock(clocks).raw();
+
+        // Enable and reset the timer
+        let tim = Timer::new(tim, clocks).release();
+        let ranging = Ranging::new(Ranging::max_prescaler_for(clock_hz, min_frequency_hz));
+
+        // Disable capture during the setup, the first channel captures
+        // rising edges, the second one falling edges, both from TI1
+        tim.ccer.modify(|_, w| {
+            w.cc1e().clear_bit().cc2e().clear_bit().cc1p().clear_bit().cc2p().set_bit()
+        });
+
+        tim.ccmr1_input().modify(|_, w| w.cc1s().ti1().cc2s().ti1());
+
+        // Rising edge on the filtered TI1 resets the counter
+        tim.smcr.modify(|_, w| unsafe { w.ts().bits(0b101).sms().bits(0b100) });
+
+        // Only counter overflows set the update flag, resets by the
+        // trigger do not, so an overflow means that no edge came in time
+        tim.cr1.modify(|_, w| w.urs().set_bit());
+        tim.arr.write(|w| w.arr().bits(u16::MAX));
+
+        let mut meter = Self { tim, pin, clock_hz, ranging, skip: 0, last: None };
+        meter.apply_prescaler();
+
+        meter.tim.ccer.modify(|_, w| w.cc1e().set_bit().cc2e().set_bit());
+        meter.tim.cr1.modify(|_, w| w.cen().set_bit());
+        meter
+    }
+
+    /// Check for a new capture, returns `WouldBlock` until a full period
+    /// is measured with a stable prescaler
+    pub fn read(&mut self) -> nb::Result<Measurement, Error> {
+        let status = self.tim.sr.read();
+
+        if status.uif().bit_is_set() {
+            // Capture made after an overflow does not contain the whole period
+            self.tim.sr.modify(|_, w| w.uif().clear_bit().cc1if().clear_bit());
+
+            if self.ranging.overflow() {
+                self.apply_prescaler();
+                return Err(nb::Error::WouldBlock);
+            }
+
+            self.last = None;
+            return Err(nb::Error::Other(Error::NoSignal));
+        }
+
+        if status.cc1if().bit_is_clear() {
+            return Err(nb::Error::WouldBlock);
+        }
+
+        // Reading the first capture register clears the capture flag
+        let period = self.tim.ccr1.read().bits() + 1;
+        let high = self.tim.ccr2.read().bits() + 1;
+
+        if self.skip > 0 {
+            self.skip -= 1;
+            return Err(nb::Error::WouldBlock);
+        }
+
+        if self.ranging.captured(period) {
+            self.apply_prescaler();
+            return Err(nb::Error::WouldBlock);
+        }
+
+        let divider = self.ranging.divider();
+
+        let measurement = Measurement::new(
+            period.saturating_mul(divider),
+            high.saturating_mul(divider),
+            self.clock_hz,
+        );
+
+        self.last = Some(measurement);
+        Ok(measurement)
+    }
+
+    /// Last successful measurement, `None` if the signal disappeared
+    pub fn last(&self) -> Option<Measurement> {
+        self.last
+    }
+
+    /// Current prescaler selected by the automatic ranging
+    pub fn prescaler(&self) -> u16 {
+        self.ranging.prescaler()
+    }
+
+    /// Frequency of the timer clock
+    pub fn clock_hz(&self) -> u32 {
+        self.clock_hz
+    }
+
+    /// Stop the timer and give back the timer and the pin
+    pub fn release(self) -> (TIM, PIN) {
+        self.tim.cr1.modify(|_, w| w.cen().clear_bit());
+        (self.tim, self.pin)
+    }
+
+    fn apply_prescaler(&mut self) {
+        let prescaler = self.ranging.prescaler();
+        self.tim.psc.write(|w| w.psc().bits(prescaler));
+
+        // Load the prescaler immediately, this also resets the counter,
+        // so the next capture measures only a part of the period
+        self.tim.egr.write(|w| w.ug().set_bit());
+        self.skip = 1;
+    }
+}
@@ -0,0 +1,79 @@
+/// Periods shorter than this count of timer ticks are measured with
+/// a smaller prescaler, if possible
+pub const MIN_COUNTS: u32 = 8192;
+
+/// Prescaler is chosen to get periods of about this count of ticks,
+/// leaving a margin for slower signals below the 16-bit counter limit
+pub const MAX_COUNTS: u32 = 32768;
+
+/// Automatic choice of the timer prescaler from the measured periods
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ranging {
+    prescaler: u16,
+    max_prescaler: u16,
+}
+
+impl Ranging {
+    /// Ranging up to `max_prescaler`, which limits the lowest frequency
+    /// measured before the signal is considered missing
+    pub fn new(max_prescaler: u16) -> Self {
+        Self { prescaler: 0, max_prescaler }
+    }
+
+    /// Largest prescaler value that still measures `min_frequency_hz`
+    /// with a `clock_hz` timer clock
+    pub fn max_prescaler_for(clock_hz: u32, min_frequency_hz: u32) -> u16 {
+        let ticks = clock_hz/min_frequency_hz.max(1);
+        (ticks/(u16::MAX as u32 + 1)).min(u16::MAX as u32) as u16
+    }
+
+    /// Value of the prescaler register, the timer counts once
+    /// per `prescaler + 1` clock ticks
+    pub fn prescaler(&self) -> u16 {
+        self.prescaler
+    }
+
+    /// Divider of the timer clock
+    pub fn divider(&self) -> u32 {
+        self.prescaler as u32 + 1
+    }
+
+    pub fn is_max(&self) -> bool {
+        self.prescaler >= self.max_prescaler
+    }
+
+    /// Counter overflowed without any edge, returns true if the prescaler
+    /// changed, false if it is already at the maximum
+    pub fn overflow(&mut self) -> bool {
+        if self.is_max() {
+            return false;
+        }
+
+        let prescaler = (self.divider()*4 - 1).min(self.max_prescaler as u32);
+        self.prescaler = prescaler as u16;
+        true
+    }
+
+    /// Period of `counts` was captured, returns true if the prescaler
+    /// changed to get a better resolution or to avoid overflows
+    pub fn captured(&mut self, counts: u32) -> bool {
+        if (MIN_COUNTS..=MAX_COUNTS*3/2).contains(&counts) {
+            return false;
+        }
+
+        if counts < MIN_COUNTS && self.prescaler == 0 {
+            return false;
+        }
+
+        let ticks = counts as u64*self.divider() as u64;
+        let prescaler = (ticks/MAX_COUNTS as u64).min(self.max_prescaler as u64) as u16;
+
+        match prescaler != self.prescaler {
+            true => {
+                self.prescaler = prescaler;
+                true
+            }
+            false => false,
+        }
+    }
+}
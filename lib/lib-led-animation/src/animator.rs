@@ -0,0 +1,126 @@
+use lib_fixed_math::Brightness;
+
+use crate::sequence::Sequence;
+
+/// Sequence assigned to a single PWM channel
+#[derive(Clone, Copy, Debug)]
+pub struct ChannelAnimation<'a> {
+    pub sequence: Sequence<'a>,
+    /// Time shift of the channel, positive offset makes the channel run ahead
+    pub offset_ms: u32,
+    /// Brightness correction and maximum duty of the channel
+    pub brightness: Brightness,
+}
+
+impl<'a> ChannelAnimation<'a> {
+    pub const fn new(sequence: Sequence<'a>, offset_ms: u32, brightness: Brightness) -> Self {
+        Self { sequence, offset_ms, brightness }
+    }
+
+    pub fn duty_at(&self, time_ms: u64) -> u16 {
+        let time_ms = self.sequence.wrap_time(time_ms + self.offset_ms as u64);
+        let level = self.sequence.level_at(time_ms);
+        self.brightness.duty(level)
+    }
+}
+
+/// Animation of `N` channels advanced by a single frame on every `tick`
+pub struct Animator<'a, const N: usize> {
+    channels: [ChannelAnimation<'a>; N],
+    frame_rate: u32,
+    frame: u64,
+}
+
+impl<'a, const N: usize> Animator<'a, N> {
+    /// `frame_rate` is the frequency of `tick` calls in Hz
+    pub fn new(channels: [ChannelAnimation<'a>; N], frame_rate: u32) -> Self {
+        Self { channels, frame_rate: frame_rate.max(1), frame: 0 }
+    }
+
+    /// Time since the start of the animation, kept in `u64` so looping
+    /// sequences do not jump when 32 bits of milliseconds overflow
+    pub fn time_ms(&self) -> u64 {
+        self.frame*1000/self.frame_rate as u64
+    }
+
+    /// Advance by a single frame and return duties of all channels
+    pub fn tick(&mut self) -> [u16; N] {
+        self.frame = self.frame.wrapping_add(1);
+        self.duties()
+    }
+
+    /// Duties of all channels in the current frame
+    pub fn duties(&self) -> [u16; N] {
+        let time_ms = self.time_ms();
+        let mut duties = [0; N];
+
+        for (duty, channel) in duties.iter_mut().zip(self.channels.iter()) {
+            *duty = channel.duty_at(time_ms);
+        }
+
+        duties
+    }
+
+    /// Start the animation from the beginning
+    pub fn restart(&mut self) {
+        self.frame = 0;
+    }
+
+    pub fn channel_mut(&mut self, index: usize) -> Option<&mut ChannelAnimation<'a>> {
+        self.channels.get_mut(index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lib_fixed_math::Curve;
+
+    use super::*;
+    use crate::{Easing, Keyframe, MAX_LEVEL};
+
+    static TRIANGLE: [Keyframe; 2] = [
+        Keyframe::new(MAX_LEVEL, 1500, Easing::Linear),
+        Keyframe::new(0, 1500, Easing::Linear),
+    ];
+
+    fn animator(looping: bool) -> Animator<'static, 1> {
+        let channel = ChannelAnimation::new(
+            Sequence::new(&TRIANGLE, looping),
+            0,
+            Brightness::new(Curve::Linear, 1000),
+        );
+
+        Animator::new([channel], 100)
+    }
+
+    #[test]
+    fn looping_animation_continues_past_u32_milliseconds() {
+        let mut animator = animator(true);
+
+        // Last frame before 2^32 ms and the following frames
+        animator.frame = (u32::MAX as u64 + 1)/10 - 1;
+        let time_ms = animator.time_ms();
+        let expected = |time_ms: u64| match time_ms % 3000 {
+            time @ 0..=1499 => (time*1000/1500) as i32,
+            time => ((3000 - time)*1000/1500) as i32,
+        };
+
+        for _ in 0..10 {
+            let duty = animator.tick()[0] as i32;
+            assert!(animator.time_ms() > time_ms);
+            assert!((duty - expected(animator.time_ms())).abs() <= 1);
+        }
+    }
+
+    #[test]
+    fn single_shot_animation_holds_the_last_level() {
+        let mut animator = animator(false);
+        assert_eq!(animator.duties(), [0]);
+
+        animator.frame = 1 << 40;
+        assert_eq!(animator.duties(), [0]);
+
+        animator.frame = 150;
+        assert_eq!(animator.duties(), [1000]);
+    }
+}
@@ -0,0 +1,38 @@
+use lib_fixed_math::cos_q15;
+
+/// Shape of the transition between two levels, all functions map
+/// progress 0-65535 to 0-65535
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing {
+    /// Constant speed
+    Linear,
+    /// Slow start and slow end (smoothstep, 3t^2 - 2t^3)
+    EaseInOut,
+    /// Goes to the target level and back within the same transition,
+    /// following (1 - cos(2*PI*t))/2
+    Breathing,
+}
+
+impl Easing {
+    pub fn apply(&self, progress: u16) -> u16 {
+        let t = progress as u32;
+
+        match self {
+            Easing::Linear => progress,
+            Easing::EaseInOut => {
+                let t2 = (t*t) >> 16;
+                let t3 = (t2*t) >> 16;
+                (3*t2).saturating_sub(2*t3).min(0xffff) as u16
+            },
+            Easing::Breathing => {
+                let cos = cos_q15(progress) as i32;
+                ((32767 - cos) as u32*0xffff/65534) as u16
+            },
+        }
+    }
+
+    /// Easing ends at the target level, `Breathing` returns back to the start
+    pub fn ends_at_target(&self) -> bool {
+        !matches!(self, Easing::Breathing)
+    }
+}
@@ -0,0 +1,14 @@
+#![no_std]
+
+//! Keyframe animation of LED brightness with easing curves
+//!
+//! Time is counted in frames, so an animation advanced from a periodic timer
+//! interrupt runs at the same speed regardless of the system clock.
+
+mod animator;
+mod easing;
+mod sequence;
+
+pub use animator::{Animator, ChannelAnimation};
+pub use easing::Easing;
+pub use sequence::{Keyframe, Sequence, MAX_LEVEL};
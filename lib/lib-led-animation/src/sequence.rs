@@ -0,0 +1,88 @@
+use crate::easing::Easing;
+
+/// Full brightness level
+pub const MAX_LEVEL: u16 = 0xffff;
+
+/// Transition to `level` lasting `duration_ms` shaped by `easing`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Keyframe {
+    pub level: u16,
+    pub duration_ms: u32,
+    pub easing: Easing,
+}
+
+impl Keyframe {
+    pub const fn new(level: u16, duration_ms: u32, easing: Easing) -> Self {
+        Self { level, duration_ms, easing }
+    }
+
+    fn end_level(&self, start: u16) -> u16 {
+        match self.easing.ends_at_target() {
+            true => self.level,
+            false => start,
+        }
+    }
+
+    fn level_at(&self, start: u16, time_ms: u32) -> u16 {
+        let progress = ((time_ms as u64) << 16)/self.duration_ms as u64;
+        let eased = self.easing.apply(progress.min(0xffff) as u16) as i64;
+        let start = start as i64;
+        (start + (((self.level as i64 - start)*eased) >> 16)) as u16
+    }
+}
+
+/// Keyframes played one after another, a looping sequence starts over
+/// after the last keyframe, otherwise the last level is held forever
+#[derive(Clone, Copy, Debug)]
+pub struct Sequence<'a> {
+    keyframes: &'a [Keyframe],
+    looping: bool,
+}
+
+impl<'a> Sequence<'a> {
+    pub const fn new(keyframes: &'a [Keyframe], looping: bool) -> Self {
+        Self { keyframes, looping }
+    }
+
+    /// Duration of all keyframes
+    pub fn duration_ms(&self) -> u32 {
+        self.keyframes.iter().map(|keyframe| keyframe.duration_ms).sum()
+    }
+
+    /// Level at `time_ms` since the start of the sequence, the first
+    /// transition starts from zero, or from the final level when looping
+    pub fn level_at(&self, time_ms: u32) -> u16 {
+        let duration = self.duration_ms();
+
+        let (mut time, mut level) = match self.looping && duration > 0 {
+            true => (time_ms % duration, self.final_level()),
+            false => (time_ms, 0),
+        };
+
+        for keyframe in self.keyframes {
+            if time < keyframe.duration_ms {
+                return keyframe.level_at(level, time);
+            }
+
+            time -= keyframe.duration_ms;
+            level = keyframe.end_level(level);
+        }
+
+        level
+    }
+
+    /// Time since the start reduced to the range of `level_at`, a looping
+    /// sequence wraps modulo its duration, otherwise the time saturates
+    pub fn wrap_time(&self, time_ms: u64) -> u32 {
+        let duration = self.duration_ms() as u64;
+
+        match self.looping && duration > 0 {
+            true => (time_ms % duration) as u32,
+            false => time_ms.min(u32::MAX as u64) as u32,
+        }
+    }
+
+    fn final_level(&self) -> u16 {
+        self.keyframes.iter().fold(0, |level, keyframe| keyframe.end_level(level))
+    }
+}
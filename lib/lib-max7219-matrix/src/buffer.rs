@@ -0,0 +1,129 @@
+use max7219::{connectors::Connector, DataError, MAX7219};
+
+/// Width and height of a single matrix module in pixels
+pub const MODULE_SIZE: usize = 8;
+
+/// Rotation of the LED matrix relative to the MAX7219 digit registers,
+/// modules from different vendors are soldered in different ways, so if
+/// the text appears rotated or mirrored, try another orientation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Orientation {
+    /// Each digit register drives one column, bit 0 is the top row
+    Rotate0,
+    /// Each digit register drives one row, bit 7 is the leftmost column
+    Rotate90,
+    /// Each digit register drives one column from the right, bit 0 is the bottom row
+    Rotate180,
+    /// Each digit register drives one row from the bottom, bit 0 is the leftmost column
+    Rotate270,
+}
+
+/// Position of the modules in the chain, `max7219` addresses the module
+/// farthest from the MCU as 0
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChainOrder {
+    /// Module with address 0 is the leftmost one
+    LeftToRight,
+    /// Module with address 0 is the rightmost one
+    RightToLeft,
+}
+
+/// Column oriented frame buffer for a chain of `N` 8x8 modules, every byte
+/// holds a single column of pixels with bit 0 at the top row
+pub struct MatrixBuffer<const N: usize> {
+    modules: [[u8; MODULE_SIZE]; N],
+}
+
+impl<const N: usize> MatrixBuffer<N> {
+    pub const fn new() -> Self {
+        Self { modules: [[0; MODULE_SIZE]; N] }
+    }
+
+    /// Width of the whole chain in pixels
+    pub const fn width(&self) -> usize {
+        N*MODULE_SIZE
+    }
+
+    pub fn clear(&mut self) {
+        self.modules.iter_mut().for_each(|module| *module = [0; MODULE_SIZE]);
+    }
+
+    /// Pixels of the column `x`, columns outside of the buffer are empty
+    pub fn column(&self, x: usize) -> u8 {
+        match self.modules.get(x/MODULE_SIZE) {
+            Some(module) => module[x % MODULE_SIZE],
+            None => 0,
+        }
+    }
+
+    /// Set pixels of the column `x`, columns outside of the buffer are ignored
+    pub fn set_column(&mut self, x: usize, pixels: u8) {
+        if let Some(module) = self.modules.get_mut(x/MODULE_SIZE) {
+            module[x % MODULE_SIZE] = pixels;
+        }
+    }
+
+    pub fn pixel(&self, x: usize, y: usize) -> bool {
+        y < MODULE_SIZE && self.column(x) & (1 << y) != 0
+    }
+
+    pub fn set_pixel(&mut self, x: usize, y: usize, on: bool) {
+        if y < MODULE_SIZE {
+            let column = self.column(x);
+            self.set_column(x, column & !(1 << y) | ((on as u8) << y));
+        }
+    }
+
+    /// Values of the eight digit registers of the `module` (counted from the left)
+    pub fn module_registers(&self, module: usize, orientation: Orientation) -> [u8; MODULE_SIZE] {
+        let mut registers = [0; MODULE_SIZE];
+        let columns = match self.modules.get(module) {
+            Some(columns) => columns,
+            None => return registers,
+        };
+
+        for (x, pixels) in columns.iter().enumerate() {
+            for y in 0..MODULE_SIZE {
+                if pixels & (1 << y) == 0 {
+                    continue;
+                }
+
+                let (digit, bit) = match orientation {
+                    Orientation::Rotate0 => (x, y),
+                    Orientation::Rotate90 => (y, 7 - x),
+                    Orientation::Rotate180 => (7 - x, 7 - y),
+                    Orientation::Rotate270 => (7 - y, x),
+                };
+
+                registers[digit] |= 1 << bit;
+            }
+        }
+
+        registers
+    }
+
+    /// Send the whole buffer to the chain of modules
+    pub fn flush<C: Connector>(
+        &self,
+        display: &mut MAX7219<C>,
+        orientation: Orientation,
+        order: ChainOrder,
+    ) -> Result<(), DataError> {
+        for module in 0..N {
+            let address = match order {
+                ChainOrder::LeftToRight => module,
+                ChainOrder::RightToLeft => N - 1 - module,
+            };
+
+            display.write_raw(address, &self.module_registers(module, orientation))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> Default for MatrixBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
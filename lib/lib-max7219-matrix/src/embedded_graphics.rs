@@ -0,0 +1,25 @@
+use embedded_graphics_core::{prelude::*, pixelcolor::BinaryColor, Pixel};
+use crate::buffer::{MatrixBuffer, MODULE_SIZE};
+
+impl<const N: usize> DrawTarget for MatrixBuffer<N> {
+    type Color = BinaryColor;
+
+    type Error = core::convert::Infallible;
+
+    fn draw_iter<I>(&mut self, pixels: I) -> Result<(), Self::Error>
+    where I: IntoIterator<Item = Pixel<Self::Color>> {
+        for Pixel(coord, color) in pixels.into_iter() {
+            if coord.x >= 0 && coord.x < self.width() as i32 && coord.y >= 0 && coord.y < MODULE_SIZE as i32 {
+                self.set_pixel(coord.x as usize, coord.y as usize, color.is_on());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<const N: usize> OriginDimensions for MatrixBuffer<N> {
+    fn size(&self) -> Size {
+        Size::new(self.width() as u32, MODULE_SIZE as u32)
+    }
+}
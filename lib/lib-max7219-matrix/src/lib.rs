@@ -0,0 +1,13 @@
+#![no_std]
+
+//! Frame buffer, proportional font and scrolling marquee for chains
+//! of MAX7219 8x8 LED matrix modules
+
+mod buffer;
+mod embedded_graphics;
+mod font;
+mod marquee;
+
+pub use buffer::{ChainOrder, MatrixBuffer, Orientation, MODULE_SIZE};
+pub use font::{glyph, text_width, GLYPH_SPACING};
+pub use marquee::Marquee;
@@ -0,0 +1,97 @@
+use crate::buffer::MatrixBuffer;
+use crate::font::{glyph, text_width, GLYPH_SPACING};
+
+const DEFAULT_PIXELS_PER_SECOND: u32 = 20;
+
+/// Text scrolling from right to left by single pixels, the text enters
+/// at the right edge and the cycle starts again once it fully leaves
+/// the left edge
+pub struct Marquee<'a> {
+    text: &'a str,
+    cycle: usize,
+    width: usize,
+    offset: usize,
+    step_ms: u32,
+    elapsed_ms: u32,
+}
+
+impl<'a> Marquee<'a> {
+    /// Marquee for a display `width` pixels wide
+    pub fn new(text: &'a str, width: usize) -> Self {
+        let mut marquee = Self {
+            text: "",
+            cycle: 0,
+            width,
+            offset: 0,
+            step_ms: 0,
+            elapsed_ms: 0,
+        };
+
+        marquee.set_text(text);
+        marquee.set_speed(DEFAULT_PIXELS_PER_SECOND);
+        marquee
+    }
+
+    pub fn with_speed(mut self, pixels_per_second: u32) -> Self {
+        self.set_speed(pixels_per_second);
+        self
+    }
+
+    /// Scrolling speed in pixels per second, zero stops the scrolling
+    pub fn set_speed(&mut self, pixels_per_second: u32) {
+        self.step_ms = match pixels_per_second {
+            0 => 0,
+            speed => (1000/speed).max(1),
+        };
+    }
+
+    /// Replace the text and restart scrolling from the right edge
+    pub fn set_text(&mut self, text: &'a str) {
+        self.text = text;
+        self.cycle = self.width + text_width(text) + GLYPH_SPACING;
+        self.offset = 0;
+        self.elapsed_ms = 0;
+    }
+
+    /// Advance the animation by `elapsed_ms` milliseconds, returns true
+    /// if the text moved and the buffer needs to be rendered again
+    pub fn update(&mut self, elapsed_ms: u32) -> bool {
+        if self.step_ms == 0 {
+            return false;
+        }
+
+        self.elapsed_ms += elapsed_ms;
+        let steps = self.elapsed_ms/self.step_ms;
+        self.elapsed_ms %= self.step_ms;
+        self.offset = (self.offset + steps as usize) % self.cycle;
+        steps > 0
+    }
+
+    /// Draw the current state of the marquee into a cleared buffer
+    pub fn render<const N: usize>(&self, buffer: &mut MatrixBuffer<N>) {
+        buffer.clear();
+
+        let width = buffer.width() as isize;
+        let mut x = self.width as isize - self.offset as isize;
+
+        for character in self.text.chars() {
+            if x >= width {
+                break;
+            }
+
+            let columns = glyph(character);
+
+            if x + (columns.len() as isize) > 0 {
+                for (index, pixels) in columns.iter().enumerate() {
+                    let column = x + index as isize;
+
+                    if column >= 0 {
+                        buffer.set_column(column as usize, *pixels);
+                    }
+                }
+            }
+
+            x += (columns.len() + GLYPH_SPACING) as isize;
+        }
+    }
+}
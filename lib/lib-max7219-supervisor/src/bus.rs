@@ -0,0 +1,97 @@
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+/// Maximum number of devices connected in series
+pub const MAX_DEVICES: usize = 8;
+
+pub(crate) const DIGITS: usize = 8;
+
+pub(crate) const NOOP: u8 = 0x00;
+pub(crate) const DIGIT_0: u8 = 0x01;
+pub(crate) const DECODE_MODE: u8 = 0x09;
+pub(crate) const INTENSITY: u8 = 0x0a;
+pub(crate) const SCAN_LIMIT: u8 = 0x0b;
+pub(crate) const SHUTDOWN: u8 = 0x0c;
+pub(crate) const DISPLAY_TEST: u8 = 0x0f;
+
+/// Error raised when communication with the MAX7219 chain fails
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// An error occurred when working with SPI
+    Spi,
+    /// An error occurred when working with the CS pin
+    Pin,
+}
+
+/// Register level access to a chain of `devices` MAX7219 chips, device 0
+/// is the last one in the chain (same as in the `max7219` crate)
+pub(crate) struct Bus<SPI, CS> {
+    spi: SPI,
+    cs: CS,
+    devices: usize,
+    buffer: [u8; MAX_DEVICES*2],
+}
+
+impl<SPI, CS> Bus<SPI, CS>
+where SPI: spi::Write<u8>, CS: OutputPin {
+    pub fn new(spi: SPI, cs: CS, devices: usize) -> Self {
+        Self { spi, cs, devices: devices.clamp(1, MAX_DEVICES), buffer: [0; MAX_DEVICES*2] }
+    }
+
+    pub fn devices(&self) -> usize {
+        self.devices
+    }
+
+    /// Write the same register of all devices in a single transaction
+    pub fn write_all(&mut self, register: u8, data: u8) -> Result<(), Error> {
+        for device in 0..self.devices {
+            self.buffer[device*2] = register;
+            self.buffer[device*2 + 1] = data;
+        }
+
+        self.send()
+    }
+
+    /// Write the same register of all devices, each with its own value
+    pub fn write_each<F>(&mut self, register: u8, data: F) -> Result<(), Error>
+    where F: Fn(usize) -> u8 {
+        for device in 0..self.devices {
+            self.buffer[device*2] = register;
+            self.buffer[device*2 + 1] = data(device);
+        }
+
+        self.send()
+    }
+
+    fn send(&mut self) -> Result<(), Error> {
+        self.cs.set_low().map_err(|_| Error::Pin)?;
+        let result = self.spi.write(&self.buffer[..self.devices*2]).map_err(|_| Error::Spi);
+        self.cs.set_high().map_err(|_| Error::Pin)?;
+        result
+    }
+
+    pub fn release(self) -> (SPI, CS) {
+        (self.spi, self.cs)
+    }
+}
+
+impl<SPI, CS> Bus<SPI, CS>
+where SPI: spi::Write<u8> + spi::Transfer<u8>, CS: OutputPin {
+    /// Shift a marker through the chain and read it back from DOUT of the
+    /// last device, returns false if the marker did not come back intact
+    pub fn loopback(&mut self, marker: u8) -> Result<bool, Error> {
+        self.write_each(NOOP, |_| marker)?;
+
+        for device in 0..self.devices {
+            self.buffer[device*2] = NOOP;
+            self.buffer[device*2 + 1] = 0;
+        }
+
+        self.cs.set_low().map_err(|_| Error::Pin)?;
+        let result = self.spi.transfer(&mut self.buffer[..self.devices*2]).map_err(|_| Error::Spi);
+        self.cs.set_high().map_err(|_| Error::Pin)?;
+
+        let received = result?;
+        Ok(received.chunks(2).all(|frame| frame[0] == NOOP && frame[1] == marker))
+    }
+}
@@ -0,0 +1,13 @@
+#![no_std]
+
+//! MAX7219 driver that keeps the display in a known state
+//!
+//! MAX7219 modules tend to latch garbage into their registers after supply
+//! noise, the supervisor keeps a copy of all registers and periodically
+//! writes them again, so a long running display recovers without a power cycle.
+
+mod bus;
+mod supervisor;
+
+pub use bus::{Error, MAX_DEVICES};
+pub use supervisor::{Config, Health, Stats, Supervisor};
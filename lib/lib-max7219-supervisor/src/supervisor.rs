@@ -0,0 +1,445 @@
+use embedded_hal::blocking::spi;
+use embedded_hal::digital::v2::OutputPin;
+
+use crate::bus::{
+    Bus, Error, DECODE_MODE, DIGITS, DIGIT_0, DISPLAY_TEST, INTENSITY, MAX_DEVICES,
+    SCAN_LIMIT, SHUTDOWN,
+};
+
+/// Marker shifted through the chain when checking the loopback
+const LOOPBACK_MARKER: u8 = 0xa5;
+
+/// Register values and timing of the supervisor
+#[derive(Clone, Copy, Debug)]
+pub struct Config {
+    /// Brightness 0-15
+    pub intensity: u8,
+    /// Index of the last scanned digit 0-7
+    pub scan_limit: u8,
+    /// Code B decoding bit mask, zero means raw segment data
+    pub decode_mode: u8,
+    /// Period of rewriting all registers
+    pub refresh_ms: u32,
+    /// Period of the display test cycles, zero disables them
+    pub test_interval_ms: u32,
+    /// How long the display test stays on during the test cycle
+    pub test_duration_ms: u32,
+    /// Period of the loopback checks run by `update_verified`, zero
+    /// disables them
+    pub verify_interval_ms: u32,
+    /// Number of consecutive failures after which the display is reported as failed
+    pub failure_threshold: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            intensity: 0x02,
+            scan_limit: 0x07,
+            decode_mode: 0x00,
+            refresh_ms: 1000,
+            test_interval_ms: 0,
+            test_duration_ms: 100,
+            verify_interval_ms: 0,
+            failure_threshold: 5,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Health {
+    /// All registers were written successfully
+    Ok,
+    /// Last attempt failed or a suspicious state was detected, the display
+    /// is being initialized again on every update
+    Recovering,
+    /// Attempts failed `failure_threshold` times in a row
+    Failed,
+}
+
+/// Counters of the supervisor activity
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Stats {
+    /// Number of successful rewrites of all registers
+    pub refreshes: u32,
+    /// Number of completed display test cycles
+    pub test_cycles: u32,
+    /// Number of completed loopback checks
+    pub verifications: u32,
+    /// Number of failed SPI or CS pin operations
+    pub errors: u32,
+    /// Number of loopback checks that did not read the marker back
+    pub loopback_mismatches: u32,
+    /// Number of times the display returned to the `Ok` state
+    pub recoveries: u32,
+}
+
+/// Supervised chain of `devices` MAX7219 chips
+///
+/// Digit data are kept in a shadow copy and written again together with
+/// the scan limit, decode mode, intensity and shutdown registers every
+/// `refresh_ms`. Optional display test cycles turn the display test on
+/// for `test_duration_ms` every `test_interval_ms`, the supervisor writes
+/// all registers after the test and checks that the writes succeeded, so
+/// a display stuck in the test mode or with garbage in its control
+/// registers is reset. With MISO connected, `update_verified` also checks
+/// the chain by a loopback every `verify_interval_ms`.
+pub struct Supervisor<SPI, CS> {
+    bus: Bus<SPI, CS>,
+    config: Config,
+    digits: [[u8; DIGITS]; MAX_DEVICES],
+    refresh_elapsed_ms: u32,
+    test_elapsed_ms: u32,
+    test_active: bool,
+    verify_elapsed_ms: u32,
+    needs_init: bool,
+    consecutive_failures: u8,
+    health: Health,
+    stats: Stats,
+}
+
+impl<SPI, CS> Supervisor<SPI, CS>
+where SPI: spi::Write<u8>, CS: OutputPin {
+    /// Create the supervisor, the display is initialized on the first `update`
+    /// or by calling `init`
+    pub fn new(spi: SPI, cs: CS, devices: usize, config: Config) -> Self {
+        Self {
+            bus: Bus::new(spi, cs, devices),
+            config,
+            digits: [[0; DIGITS]; MAX_DEVICES],
+            refresh_elapsed_ms: 0,
+            test_elapsed_ms: 0,
+            test_active: false,
+            verify_elapsed_ms: 0,
+            needs_init: true,
+            consecutive_failures: 0,
+            health: Health::Recovering,
+            stats: Stats::default(),
+        }
+    }
+
+    /// Write all registers immediately
+    pub fn init(&mut self) -> Result<(), Error> {
+        let result = self.refresh();
+        self.record(result)
+    }
+
+    /// Store digit registers of the `device` (digit 0 first) and send them
+    pub fn write_digits(&mut self, device: usize, digits: &[u8; DIGITS]) -> Result<(), Error> {
+        if device >= self.bus.devices() {
+            return Ok(());
+        }
+
+        self.digits[device] = *digits;
+        let result = self.send_digits();
+        self.record(result)
+    }
+
+    pub fn set_intensity(&mut self, intensity: u8) -> Result<(), Error> {
+        self.config.intensity = intensity & 0x0f;
+        let result = self.bus.write_all(INTENSITY, self.config.intensity);
+        self.record(result)
+    }
+
+    /// Advance the schedule by `elapsed_ms` milliseconds, rewrites registers
+    /// and runs the display test cycle when they are due
+    pub fn update(&mut self, elapsed_ms: u32) -> Health {
+        self.refresh_elapsed_ms = self.refresh_elapsed_ms.saturating_add(elapsed_ms);
+        self.test_elapsed_ms = self.test_elapsed_ms.saturating_add(elapsed_ms);
+
+        let result = self.run_schedule();
+        let _ = self.record(result);
+        self.health
+    }
+
+    pub fn health(&self) -> Health {
+        self.health
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Give back the SPI and CS pin
+    pub fn release(self) -> (SPI, CS) {
+        self.bus.release()
+    }
+
+    fn run_schedule(&mut self) -> Result<(), Error> {
+        if self.test_active {
+            if self.test_elapsed_ms >= self.config.test_duration_ms {
+                self.test_active = false;
+                self.test_elapsed_ms = 0;
+                self.stats.test_cycles = self.stats.test_cycles.wrapping_add(1);
+                return self.refresh();
+            }
+
+            return Ok(());
+        }
+
+        if self.needs_init || self.refresh_elapsed_ms >= self.config.refresh_ms {
+            return self.refresh();
+        }
+
+        if self.config.test_interval_ms != 0 && self.test_elapsed_ms >= self.config.test_interval_ms {
+            self.test_elapsed_ms = 0;
+            self.test_active = true;
+
+            // All registers are written again once the test is over
+            return self.bus.write_all(DISPLAY_TEST, 0x01);
+        }
+
+        Ok(())
+    }
+
+    fn refresh(&mut self) -> Result<(), Error> {
+        self.refresh_elapsed_ms = 0;
+        self.needs_init = true;
+
+        self.bus.write_all(DISPLAY_TEST, 0x00)?;
+        self.bus.write_all(SCAN_LIMIT, self.config.scan_limit & 0x07)?;
+        self.bus.write_all(DECODE_MODE, self.config.decode_mode)?;
+        self.bus.write_all(INTENSITY, self.config.intensity & 0x0f)?;
+        self.send_digits()?;
+        self.bus.write_all(SHUTDOWN, 0x01)?;
+
+        self.needs_init = false;
+        self.stats.refreshes = self.stats.refreshes.wrapping_add(1);
+        Ok(())
+    }
+
+    fn send_digits(&mut self) -> Result<(), Error> {
+        let digits = &self.digits;
+
+        for (digit, register) in (DIGIT_0..).take(DIGITS).enumerate() {
+            self.bus.write_each(register, |device| digits[device][digit])?;
+        }
+
+        Ok(())
+    }
+
+    fn record(&mut self, result: Result<(), Error>) -> Result<(), Error> {
+        match result {
+            Ok(()) if !self.needs_init => {
+                if self.consecutive_failures > 0 {
+                    self.stats.recoveries = self.stats.recoveries.wrapping_add(1);
+                }
+
+                self.consecutive_failures = 0;
+                self.health = Health::Ok;
+            },
+            Ok(()) => {},
+            Err(_) => {
+                self.stats.errors = self.stats.errors.wrapping_add(1);
+                self.mark_suspicious();
+            },
+        }
+
+        result
+    }
+
+    fn mark_suspicious(&mut self) {
+        self.needs_init = true;
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+
+        self.health = match self.consecutive_failures >= self.config.failure_threshold {
+            true => Health::Failed,
+            false => Health::Recovering,
+        };
+    }
+}
+
+impl<SPI, CS> Supervisor<SPI, CS>
+where SPI: spi::Write<u8> + spi::Transfer<u8>, CS: OutputPin {
+    /// `update` followed by the loopback check when it is due, an optional
+    /// extra check, requires MISO connected to DOUT of the last device
+    pub fn update_verified(&mut self, elapsed_ms: u32) -> Health {
+        self.verify_elapsed_ms = self.verify_elapsed_ms.saturating_add(elapsed_ms);
+        self.update(elapsed_ms);
+
+        if self.config.verify_interval_ms != 0 && self.verify_elapsed_ms >= self.config.verify_interval_ms {
+            self.verify_elapsed_ms = 0;
+            let _ = self.verify_chain();
+        }
+
+        self.health
+    }
+
+    /// Check the chain integrity, requires MISO connected to DOUT of the
+    /// last device. A marker that does not come back intact forces a full
+    /// initialization on the next update.
+    pub fn verify_chain(&mut self) -> Result<bool, Error> {
+        let result = self.bus.loopback(LOOPBACK_MARKER);
+        self.stats.verifications = self.stats.verifications.wrapping_add(1);
+
+        match result {
+            Ok(true) => Ok(true),
+            Ok(false) => {
+                self.stats.loopback_mismatches = self.stats.loopback_mismatches.wrapping_add(1);
+                self.mark_suspicious();
+                Ok(false)
+            },
+            Err(error) => {
+                self.stats.errors = self.stats.errors.wrapping_add(1);
+                self.mark_suspicious();
+                Err(error)
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use core::cell::Cell;
+    use core::convert::Infallible;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use std::vec::Vec;
+
+    use super::*;
+
+    /// Shift registers of a chain, bytes written to DIN come out of DOUT
+    /// of the last device two bytes per device later
+    struct Chain {
+        shift: VecDeque<u8>,
+        broken: Rc<Cell<bool>>,
+        written: Vec<u8>,
+    }
+
+    impl Chain {
+        fn new(devices: usize, broken: Rc<Cell<bool>>) -> Self {
+            Self { shift: core::iter::repeat_n(0, devices*2).collect(), broken, written: Vec::new() }
+        }
+
+        fn shift(&mut self, byte: u8) -> u8 {
+            self.written.push(byte);
+            self.shift.push_back(byte);
+            let out = self.shift.pop_front().unwrap();
+
+            match self.broken.get() {
+                true => 0xff,
+                false => out,
+            }
+        }
+    }
+
+    impl spi::Write<u8> for Chain {
+        type Error = Infallible;
+
+        fn write(&mut self, words: &[u8]) -> Result<(), Self::Error> {
+            for &word in words {
+                self.shift(word);
+            }
+
+            Ok(())
+        }
+    }
+
+    impl spi::Transfer<u8> for Chain {
+        type Error = Infallible;
+
+        fn transfer<'w>(&mut self, words: &'w mut [u8]) -> Result<&'w [u8], Self::Error> {
+            for word in words.iter_mut() {
+                *word = self.shift(*word);
+            }
+
+            Ok(words)
+        }
+    }
+
+    struct Pin;
+
+    impl OutputPin for Pin {
+        type Error = Infallible;
+
+        fn set_low(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+
+        fn set_high(&mut self) -> Result<(), Self::Error> {
+            Ok(())
+        }
+    }
+
+    fn supervisor(devices: usize, broken: &Rc<Cell<bool>>) -> Supervisor<Chain, Pin> {
+        let config = Config { refresh_ms: 1000, verify_interval_ms: 500, ..Config::default() };
+        Supervisor::new(Chain::new(devices, broken.clone()), Pin, devices, config)
+    }
+
+    #[test]
+    fn intact_chain_passes_the_loopback() {
+        for devices in 1..=3 {
+            let mut display = supervisor(devices, &Rc::default());
+            display.init().unwrap();
+            assert_eq!(display.verify_chain(), Ok(true));
+            assert_eq!(display.health(), Health::Ok);
+        }
+    }
+
+    #[test]
+    fn broken_chain_is_detected_and_recovered() {
+        let broken = Rc::new(Cell::new(false));
+        let mut display = supervisor(2, &broken);
+        assert_eq!(display.update_verified(100), Health::Ok);
+
+        broken.set(true);
+        assert_eq!(display.update_verified(400), Health::Recovering);
+        assert_eq!(display.stats().loopback_mismatches, 1);
+
+        broken.set(false);
+        let refreshes = display.stats().refreshes;
+        assert_eq!(display.update_verified(100), Health::Ok);
+        assert_eq!(display.stats().refreshes, refreshes + 1);
+        assert_eq!(display.stats().recoveries, 1);
+    }
+
+    #[test]
+    fn loopback_runs_every_verify_interval() {
+        let mut display = supervisor(1, &Rc::default());
+
+        for _ in 0..20 {
+            display.update_verified(50);
+        }
+
+        assert_eq!(display.stats().verifications, 2);
+        assert_eq!(display.stats().loopback_mismatches, 0);
+    }
+
+    #[test]
+    fn display_test_cycle_rewrites_registers() {
+        let config = Config { refresh_ms: 540, test_interval_ms: 500, test_duration_ms: 100, ..Config::default() };
+        let mut display = Supervisor::new(Chain::new(1, Rc::default()), Pin, 1, config);
+        assert_eq!(display.update(0), Health::Ok);
+
+        // Test starts, the refresh due meanwhile waits until it is over
+        display.update(500);
+        display.update(60);
+        assert_eq!(display.stats().test_cycles, 0);
+        assert_eq!(display.stats().refreshes, 1);
+
+        assert_eq!(display.update(50), Health::Ok);
+        assert_eq!(display.stats().test_cycles, 1);
+        assert_eq!(display.stats().refreshes, 2);
+
+        let (chain, _) = display.release();
+        let tests: Vec<u8> = chain.written.chunks(2)
+            .filter(|command| command[0] == DISPLAY_TEST)
+            .map(|command| command[1])
+            .collect();
+        assert_eq!(tests, [0, 1, 0]);
+    }
+
+    #[test]
+    fn display_test_disabled_by_default() {
+        let mut display = Supervisor::new(Chain::new(1, Rc::default()), Pin, 1, Config::default());
+
+        for _ in 0..100 {
+            display.update(1000);
+        }
+
+        assert_eq!(display.stats().test_cycles, 0);
+        assert_eq!(display.stats().refreshes, 100);
+    }
+}
@@ -0,0 +1,134 @@
+use crate::temperature::temperature_rgb;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rgb {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Rgb {
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        Self { r, g, b }
+    }
+}
+
+/// Colour with a separate white channel
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Rgbw {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub w: u8,
+}
+
+impl Rgbw {
+    pub const OFF: Rgbw = Rgbw::new(0, 0, 0, 0);
+
+    pub const fn new(r: u8, g: u8, b: u8, w: u8) -> Self {
+        Self { r, g, b, w }
+    }
+
+    /// White of the colour temperature `kelvin` mixed from the white LED
+    /// of the temperature `white_kelvin` and the RGB channels
+    ///
+    /// The white LED provides as much of the light as possible, the RGB
+    /// channels add the remaining tint. `level` scales the result.
+    pub fn white(kelvin: u16, level: u8, white_kelvin: u16) -> Self {
+        let target = temperature_rgb(kelvin);
+        let native = temperature_rgb(white_kelvin);
+
+        // Largest amount of the white LED that does not exceed the target in any channel
+        let white = [(target.r, native.r), (target.g, native.g), (target.b, native.b)]
+            .iter()
+            .filter(|(_, native)| *native > 0)
+            .map(|(target, native)| *target as u32*255/(*native as u32))
+            .min()
+            .unwrap_or(0)
+            .min(255);
+
+        let tint = |target: u8, native: u8| {
+            (target as u32).saturating_sub(native as u32*white/255)
+        };
+
+        Rgbw::new(
+            scale(tint(target.r, native.r), level),
+            scale(tint(target.g, native.g), level),
+            scale(tint(target.b, native.b), level),
+            scale(white, level),
+        )
+    }
+
+    /// Linear interpolation from `self` to `other`, `fraction` is 0-65535
+    pub fn lerp(&self, other: &Rgbw, fraction: u16) -> Rgbw {
+        let mix = |a: u8, b: u8| {
+            (a as i32 + (((b as i32 - a as i32)*fraction as i32) >> 16)) as u8
+        };
+
+        Rgbw::new(
+            mix(self.r, other.r),
+            mix(self.g, other.g),
+            mix(self.b, other.b),
+            mix(self.w, other.w),
+        )
+    }
+}
+
+impl From<Rgb> for Rgbw {
+    fn from(color: Rgb) -> Self {
+        Rgbw::new(color.r, color.g, color.b, 0)
+    }
+}
+
+impl From<Hsv> for Rgbw {
+    fn from(color: Hsv) -> Self {
+        Rgb::from(color).into()
+    }
+}
+
+/// Hue in degrees 0-359, saturation and value 0-255
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Hsv {
+    pub h: u16,
+    pub s: u8,
+    pub v: u8,
+}
+
+impl Hsv {
+    pub const fn new(h: u16, s: u8, v: u8) -> Self {
+        Self { h, s, v }
+    }
+}
+
+impl From<Hsv> for Rgb {
+    fn from(color: Hsv) -> Self {
+        let v = color.v as u32;
+        let s = color.s as u32;
+
+        if s == 0 {
+            return Rgb::new(color.v, color.v, color.v);
+        }
+
+        let hue = (color.h % 360) as u32;
+        let region = hue/60;
+        let remainder = (hue % 60)*255/60;
+
+        let p = (v*(255 - s)/255) as u8;
+        let q = (v*(255 - s*remainder/255)/255) as u8;
+        let t = (v*(255 - s*(255 - remainder)/255)/255) as u8;
+        let v = color.v;
+
+        match region {
+            0 => Rgb::new(v, t, p),
+            1 => Rgb::new(q, v, p),
+            2 => Rgb::new(p, v, t),
+            3 => Rgb::new(p, q, v),
+            4 => Rgb::new(t, p, v),
+            _ => Rgb::new(v, p, q),
+        }
+    }
+}
+
+fn scale(value: u32, level: u8) -> u8 {
+    (value.min(255)*level as u32/255) as u8
+}
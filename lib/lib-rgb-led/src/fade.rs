@@ -0,0 +1,40 @@
+use crate::color::Rgbw;
+
+/// Linear cross-fade between two colours
+#[derive(Clone, Copy, Debug)]
+pub struct Fade {
+    from: Rgbw,
+    to: Rgbw,
+    duration_ms: u32,
+    elapsed_ms: u32,
+}
+
+impl Fade {
+    pub fn new(from: Rgbw, to: Rgbw, duration_ms: u32) -> Self {
+        Self { from, to, duration_ms, elapsed_ms: 0 }
+    }
+
+    /// Advance the fade by `elapsed_ms` milliseconds and return the current colour
+    pub fn update(&mut self, elapsed_ms: u32) -> Rgbw {
+        self.elapsed_ms = self.elapsed_ms.saturating_add(elapsed_ms).min(self.duration_ms);
+        self.color()
+    }
+
+    pub fn color(&self) -> Rgbw {
+        if self.is_done() {
+            return self.to;
+        }
+
+        let fraction = ((self.elapsed_ms as u64) << 16)/self.duration_ms as u64;
+        self.from.lerp(&self.to, fraction as u16)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.elapsed_ms >= self.duration_ms
+    }
+
+    /// Target colour of the fade
+    pub fn target(&self) -> Rgbw {
+        self.to
+    }
+}
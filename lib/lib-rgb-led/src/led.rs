@@ -0,0 +1,115 @@
+use embedded_hal::PwmPin;
+use lib_fixed_math::{Brightness, Curve};
+
+use crate::color::{Hsv, Rgb, Rgbw};
+
+/// Per mille value of the full duty
+const FULL: u16 = 1000;
+
+/// Relative efficiency of the LED channels, each factor is the per mille
+/// of the maximum duty used for the full channel value, so channels of
+/// brighter LEDs can be limited to get a balanced colour
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Calibration {
+    pub r: u16,
+    pub g: u16,
+    pub b: u16,
+    pub w: u16,
+    /// Colour temperature of the white LED
+    pub white_kelvin: u16,
+}
+
+impl Calibration {
+    pub const fn new(r: u16, g: u16, b: u16, w: u16, white_kelvin: u16) -> Self {
+        Self { r, g, b, w, white_kelvin }
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::new(FULL, FULL, FULL, FULL, 6500)
+    }
+}
+
+/// RGBW LED driven by four PWM channels
+pub struct RgbwLed<R, G, B, W> {
+    r: R,
+    g: G,
+    b: B,
+    w: W,
+    brightness: [Brightness; 4],
+    white_kelvin: u16,
+    color: Rgbw,
+}
+
+impl<R, G, B, W> RgbwLed<R, G, B, W>
+where
+    R: PwmPin<Duty = u16>,
+    G: PwmPin<Duty = u16>,
+    B: PwmPin<Duty = u16>,
+    W: PwmPin<Duty = u16>,
+{
+    /// Channels are expected to be enabled, the LED is turned off
+    pub fn new(r: R, g: G, b: B, w: W, calibration: Calibration, curve: Curve) -> Self {
+        let channel = |max_duty: u16, factor: u16| {
+            let factor = factor.min(FULL) as u32;
+            Brightness::new(curve, (max_duty as u32*factor/FULL as u32) as u16)
+        };
+
+        let brightness = [
+            channel(r.get_max_duty(), calibration.r),
+            channel(g.get_max_duty(), calibration.g),
+            channel(b.get_max_duty(), calibration.b),
+            channel(w.get_max_duty(), calibration.w),
+        ];
+
+        let mut led = Self {
+            r, g, b, w, brightness, white_kelvin: calibration.white_kelvin, color: Rgbw::OFF
+        };
+
+        led.set(Rgbw::OFF);
+        led
+    }
+
+    /// Currently shown colour
+    pub fn color(&self) -> Rgbw {
+        self.color
+    }
+
+    pub fn set(&mut self, color: Rgbw) {
+        self.color = color;
+        self.r.set_duty(self.brightness[0].duty(level(color.r)));
+        self.g.set_duty(self.brightness[1].duty(level(color.g)));
+        self.b.set_duty(self.brightness[2].duty(level(color.b)));
+        self.w.set_duty(self.brightness[3].duty(level(color.w)));
+    }
+
+    /// Colour using only RGB channels, the white channel is turned off
+    pub fn set_rgb(&mut self, color: Rgb) {
+        self.set(color.into());
+    }
+
+    pub fn set_hsv(&mut self, color: Hsv) {
+        self.set(color.into());
+    }
+
+    /// White light of the colour temperature `kelvin`
+    pub fn set_white(&mut self, kelvin: u16, level: u8) {
+        self.set(Rgbw::white(kelvin, level, self.white_kelvin));
+    }
+
+    /// White of the colour temperature `kelvin` using the calibrated white LED
+    pub fn white(&self, kelvin: u16, level: u8) -> Rgbw {
+        Rgbw::white(kelvin, level, self.white_kelvin)
+    }
+
+    /// Give back the PWM channels
+    pub fn release(self) -> (R, G, B, W) {
+        (self.r, self.g, self.b, self.w)
+    }
+}
+
+/// Channel value 0-255 converted to brightness level 0-65535
+fn level(value: u8) -> u16 {
+    value as u16*257
+}
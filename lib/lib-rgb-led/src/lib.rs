@@ -0,0 +1,13 @@
+#![no_std]
+
+//! RGB(W) LED colour mixing over four PWM channels in integer arithmetic
+
+mod color;
+mod fade;
+mod led;
+mod temperature;
+
+pub use color::{Hsv, Rgb, Rgbw};
+pub use fade::Fade;
+pub use led::{Calibration, RgbwLed};
+pub use temperature::{temperature_rgb, MAX_KELVIN, MIN_KELVIN};
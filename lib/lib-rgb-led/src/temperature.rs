@@ -0,0 +1,47 @@
+use crate::color::Rgb;
+
+pub const MIN_KELVIN: u16 = 1000;
+pub const MAX_KELVIN: u16 = 10000;
+
+const STEP_KELVIN: u16 = 500;
+
+/// Black body colour from 1000 K to 10000 K in 500 K steps
+/// (Tanner Helland approximation of the Mitchell Charity data)
+const TEMPERATURE: [(u8, u8, u8); 19] = [
+    (255, 68, 0),    // 1000 K
+    (255, 108, 0),   // 1500 K
+    (255, 137, 14),  // 2000 K
+    (255, 159, 70),  // 2500 K
+    (255, 177, 110), // 3000 K
+    (255, 193, 141), // 3500 K
+    (255, 206, 166), // 4000 K
+    (255, 218, 187), // 4500 K
+    (255, 228, 206), // 5000 K
+    (255, 237, 222), // 5500 K
+    (255, 246, 237), // 6000 K
+    (255, 254, 250), // 6500 K
+    (243, 242, 255), // 7000 K
+    (230, 235, 255), // 7500 K
+    (221, 230, 255), // 8000 K
+    (215, 226, 255), // 8500 K
+    (210, 223, 255), // 9000 K
+    (205, 220, 255), // 9500 K
+    (202, 218, 255), // 10000 K
+];
+
+/// Colour of a black body at the temperature `kelvin`, temperatures
+/// outside of 1000 - 10000 K are clamped
+pub fn temperature_rgb(kelvin: u16) -> Rgb {
+    let kelvin = kelvin.clamp(MIN_KELVIN, MAX_KELVIN) - MIN_KELVIN;
+    let index = (kelvin/STEP_KELVIN) as usize;
+    let fraction = (kelvin % STEP_KELVIN) as i32;
+
+    let (r0, g0, b0) = TEMPERATURE[index];
+    let (r1, g1, b1) = TEMPERATURE[(index + 1).min(TEMPERATURE.len() - 1)];
+
+    let mix = |a: u8, b: u8| {
+        (a as i32 + (b as i32 - a as i32)*fraction/STEP_KELVIN as i32) as u8
+    };
+
+    Rgb::new(mix(r0, r1), mix(g0, g1), mix(b0, b1))
+}
@@ -0,0 +1,43 @@
+use crate::servo::Servo;
+
+/// `N` servos moved together, typically the four channels of one timer
+pub struct ServoBank<const N: usize> {
+    servos: [Servo; N],
+}
+
+impl<const N: usize> ServoBank<N> {
+    pub fn new(servos: [Servo; N]) -> Self {
+        Self { servos }
+    }
+
+    /// Advance all moves by `elapsed_ms` and return pulse widths of all servos
+    pub fn update(&mut self, elapsed_ms: u32) -> [u16; N] {
+        let mut pulses = [0; N];
+
+        for (pulse, servo) in pulses.iter_mut().zip(self.servos.iter_mut()) {
+            *pulse = servo.update(elapsed_ms);
+        }
+
+        pulses
+    }
+
+    /// Move all servos to their `angles` at `speed` degrees per second
+    pub fn move_to(&mut self, angles: [u16; N], speed: u16) {
+        for (servo, angle) in self.servos.iter_mut().zip(angles) {
+            servo.move_to(angle, speed);
+        }
+    }
+
+    /// True while any of the servos did not reach its target
+    pub fn is_moving(&self) -> bool {
+        self.servos.iter().any(|servo| servo.is_moving())
+    }
+
+    pub fn servo(&self, index: usize) -> Option<&Servo> {
+        self.servos.get(index)
+    }
+
+    pub fn servo_mut(&mut self, index: usize) -> Option<&mut Servo> {
+        self.servos.get_mut(index)
+    }
+}
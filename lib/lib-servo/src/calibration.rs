@@ -0,0 +1,52 @@
+/// Servo frame period of 50 Hz
+pub const PERIOD_US: u32 = 20_000;
+
+/// Pulse widths at the end positions of a servo, most servos accept
+/// 1000 - 2000 us, many go further to 500 - 2500 us
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Calibration {
+    /// Pulse width at angle 0
+    pub min_pulse_us: u16,
+    /// Pulse width at `max_angle`
+    pub max_pulse_us: u16,
+    /// Angle range of the servo in degrees
+    pub max_angle: u16,
+}
+
+impl Calibration {
+    pub const fn new(min_pulse_us: u16, max_pulse_us: u16, max_angle: u16) -> Self {
+        Self { min_pulse_us, max_pulse_us, max_angle }
+    }
+
+    /// Pulse width of an angle in millidegrees, angles out of range are clamped
+    pub fn pulse_us(&self, angle_mdeg: u32) -> u16 {
+        let max_mdeg = self.max_angle as u32*1000;
+
+        if max_mdeg == 0 {
+            return self.min_pulse_us;
+        }
+
+        let angle_mdeg = angle_mdeg.min(max_mdeg) as i32;
+        let range = self.max_pulse_us as i32 - self.min_pulse_us as i32;
+        let offset = (range as i64*angle_mdeg as i64/max_mdeg as i64) as i32;
+
+        (self.min_pulse_us as i32 + offset) as u16
+    }
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self::new(1000, 2000, 180)
+    }
+}
+
+/// PWM duty of a pulse, `max_duty` corresponds to the whole `period_us`,
+/// a timer configured by `pwm_us` with a 20 ms period has the duty equal
+/// to the pulse width
+pub fn duty(pulse_us: u16, max_duty: u16, period_us: u32) -> u16 {
+    if period_us == 0 {
+        return 0;
+    }
+
+    (pulse_us as u32*max_duty as u32/period_us).min(max_duty as u32) as u16
+}
@@ -0,0 +1,15 @@
+#![no_std]
+
+//! Hobby servo positioning with per-servo calibration and speed limited moves
+//!
+//! Servos are pure state, `update` returns pulse widths in microseconds
+//! that are converted to PWM duties by `duty`, so the same code drives
+//! servos on any timer channels.
+
+mod bank;
+mod calibration;
+mod servo;
+
+pub use bank::ServoBank;
+pub use calibration::{duty, Calibration, PERIOD_US};
+pub use servo::Servo;
@@ -0,0 +1,101 @@
+use crate::calibration::Calibration;
+
+/// Position of a single servo moving to its target at a limited speed
+#[derive(Clone, Copy, Debug)]
+pub struct Servo {
+    calibration: Calibration,
+    /// Current and target positions in millidegrees
+    position: u32,
+    target: u32,
+    /// Speed in degrees per second, zero means jump to the target
+    speed: u16,
+    enabled: bool,
+}
+
+impl Servo {
+    /// Servo starting at `angle` degrees
+    pub fn new(calibration: Calibration, angle: u16) -> Self {
+        let position = clamp(angle, &calibration);
+
+        Self { calibration, position, target: position, speed: 0, enabled: true }
+    }
+
+    pub fn calibration(&self) -> &Calibration {
+        &self.calibration
+    }
+
+    pub fn set_calibration(&mut self, calibration: Calibration) {
+        self.calibration = calibration;
+        self.position = self.position.min(calibration.max_angle as u32*1000);
+        self.target = self.target.min(calibration.max_angle as u32*1000);
+    }
+
+    /// Current angle in degrees
+    pub fn angle(&self) -> u16 {
+        ((self.position + 500)/1000) as u16
+    }
+
+    /// Target angle in degrees
+    pub fn target(&self) -> u16 {
+        ((self.target + 500)/1000) as u16
+    }
+
+    /// Jump to `angle` immediately
+    pub fn set_angle(&mut self, angle: u16) {
+        self.position = clamp(angle, &self.calibration);
+        self.target = self.position;
+    }
+
+    /// Move to `angle` at `speed` degrees per second, zero speed moves
+    /// the servo as fast as it can
+    pub fn move_to(&mut self, angle: u16, speed: u16) {
+        self.target = clamp(angle, &self.calibration);
+        self.speed = speed;
+
+        if speed == 0 {
+            self.position = self.target;
+        }
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.position != self.target
+    }
+
+    /// Stop generating pulses, most servos stop holding the position
+    pub fn disable(&mut self) {
+        self.enabled = false;
+    }
+
+    pub fn enable(&mut self) {
+        self.enabled = true;
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Advance the move by `elapsed_ms` and return the pulse width
+    pub fn update(&mut self, elapsed_ms: u32) -> u16 {
+        // Speed in degrees per second equals millidegrees per millisecond
+        let step = (self.speed as u32).saturating_mul(elapsed_ms);
+
+        self.position = match self.position < self.target {
+            true => self.position.saturating_add(step).min(self.target),
+            false => self.position.saturating_sub(step).max(self.target),
+        };
+
+        self.pulse_us()
+    }
+
+    /// Pulse width of the current position, zero if disabled
+    pub fn pulse_us(&self) -> u16 {
+        match self.enabled {
+            true => self.calibration.pulse_us(self.position),
+            false => 0,
+        }
+    }
+}
+
+fn clamp(angle: u16, calibration: &Calibration) -> u32 {
+    angle.min(calibration.max_angle) as u32*1000
+}
@@ -0,0 +1,141 @@
+//! Segment bits use the MAX7219 no-decode order: DP A B C D E F G from
+//! the most significant bit
+
+pub const SEGMENT_DP: u8 = 0x80;
+
+/// Best available 7-segment pattern for a character, letters that can
+/// be shown in both cases keep the case of the input, characters without
+/// any reasonable pattern are blank
+pub fn glyph(character: char) -> u8 {
+    match character {
+        '0' | 'O' | 'D' => 0x7e,
+        '1' => 0x30,
+        '2' | 'Z' | 'z' => 0x6d,
+        '3' => 0x79,
+        '4' => 0x33,
+        '5' | 'S' | 's' => 0x5b,
+        '6' => 0x5f,
+        '7' => 0x70,
+        '8' | 'B' => 0x7f,
+        '9' | 'g' => 0x7b,
+        'A' | 'a' => 0x77,
+        'b' => 0x1f,
+        'C' => 0x4e,
+        'c' => 0x0d,
+        'd' => 0x3d,
+        'E' | 'e' => 0x4f,
+        'F' | 'f' => 0x47,
+        'G' => 0x5e,
+        'H' | 'X' | 'x' => 0x37,
+        'h' => 0x17,
+        'I' | 'l' => 0x06,
+        'i' => 0x10,
+        'J' | 'j' => 0x3c,
+        'K' | 'k' => 0x57,
+        'L' => 0x0e,
+        'M' | 'm' => 0x54,
+        'N' => 0x76,
+        'n' => 0x15,
+        'o' => 0x1d,
+        'P' | 'p' => 0x67,
+        'Q' | 'q' => 0x73,
+        'R' | 'r' => 0x05,
+        'T' | 't' => 0x0f,
+        'U' | 'V' => 0x3e,
+        'u' | 'v' => 0x1c,
+        'W' | 'w' => 0x2a,
+        'Y' | 'y' => 0x3b,
+        '-' => 0x01,
+        '_' => 0x08,
+        '=' => 0x09,
+        '"' => 0x22,
+        '\'' | '`' => 0x02,
+        '[' | '(' => 0x4e,
+        ']' | ')' => 0x78,
+        '?' => 0x65,
+        '°' => 0x63,
+        '.' | ',' => SEGMENT_DP,
+        _ => 0x00,
+    }
+}
+
+fn is_dot(character: char) -> bool {
+    character == '.' || character == ','
+}
+
+/// Segment patterns of the text, one item per display digit
+///
+/// A dot or comma following a character is folded into the decimal point
+/// of the previous digit, so "12.5" takes three digits only. A dot without
+/// a preceding character (or following another dot) takes its own digit.
+pub fn cells(text: &str) -> impl Iterator<Item = u8> + Clone + '_ {
+    let mut chars = text.chars().peekable();
+
+    core::iter::from_fn(move || {
+        let character = chars.next()?;
+        let mut segments = glyph(character);
+
+        if !is_dot(character) && chars.peek().copied().is_some_and(is_dot) {
+            chars.next();
+            segments |= SEGMENT_DP;
+        }
+
+        Some(segments)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn collect<const N: usize>(text: &str) -> [u8; N] {
+        let mut result = [0xff; N];
+        let mut count = 0;
+
+        for (slot, segments) in result.iter_mut().zip(cells(text)) {
+            *slot = segments;
+            count += 1;
+        }
+
+        assert_eq!(count, N);
+        assert_eq!(cells(text).count(), N);
+        result
+    }
+
+    #[test]
+    fn glyphs() {
+        assert_eq!(glyph('0'), 0x7e);
+        assert_eq!(glyph('8'), 0x7f);
+        assert_eq!(glyph('A'), glyph('a'));
+        assert_eq!(glyph('b'), 0x1f);
+        assert_eq!(glyph('-'), 0x01);
+        assert_eq!(glyph('.'), SEGMENT_DP);
+        assert_eq!(glyph(','), SEGMENT_DP);
+        assert_eq!(glyph(' '), 0x00);
+        assert_eq!(glyph('~'), 0x00);
+    }
+
+    #[test]
+    fn dot_is_folded_into_previous_digit() {
+        assert_eq!(collect::<3>("12.5"), [0x30, 0x6d | SEGMENT_DP, 0x5b]);
+        assert_eq!(collect::<2>("1,5"), [0x30 | SEGMENT_DP, 0x5b]);
+        assert_eq!(collect::<1>("8."), [0x7f | SEGMENT_DP]);
+    }
+
+    #[test]
+    fn leading_dot_takes_own_digit() {
+        assert_eq!(collect::<2>(".5"), [SEGMENT_DP, 0x5b]);
+        assert_eq!(collect::<1>("."), [SEGMENT_DP]);
+    }
+
+    #[test]
+    fn double_dot_takes_own_digit() {
+        assert_eq!(collect::<3>("1..2"), [0x30 | SEGMENT_DP, SEGMENT_DP, 0x6d]);
+        assert_eq!(collect::<2>(".."), [SEGMENT_DP, SEGMENT_DP]);
+    }
+
+    #[test]
+    fn empty_text() {
+        assert_eq!(cells("").count(), 0);
+    }
+}
@@ -0,0 +1,12 @@
+#![no_std]
+
+//! Text rendering and scrolling for 7-segment displays driven by MAX7219
+//!
+//! Does not depend on any hardware crate, so it can be built and run
+//! on the host as well.
+
+mod glyph;
+mod scroller;
+
+pub use glyph::{cells, glyph, SEGMENT_DP};
+pub use scroller::{ScrollMode, Scroller, DIGITS};
@@ -0,0 +1,276 @@
+use crate::glyph::cells;
+
+/// Number of digits on the display
+pub const DIGITS: usize = 8;
+
+const DEFAULT_STEP_MS: u32 = 300;
+const DEFAULT_DWELL_MS: u32 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ScrollMode {
+    /// Text enters at the right edge, leaves at the left edge and starts
+    /// over after a blank gap as wide as the display
+    Wrap,
+    /// Text longer than the display moves left until its end is visible
+    /// and then back, shorter text stays still
+    Bounce,
+}
+
+/// Scrolling text for an 8-digit display
+///
+/// The scroller holds the position for `dwell` milliseconds when the
+/// start of the text is aligned to the left edge, and in the bounce mode
+/// also when the end is aligned to the right edge.
+pub struct Scroller<'a> {
+    text: &'a str,
+    len: usize,
+    mode: ScrollMode,
+    step_ms: u32,
+    dwell_ms: u32,
+    position: usize,
+    forward: bool,
+    elapsed_ms: u32,
+}
+
+impl<'a> Scroller<'a> {
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            len: cells(text).count(),
+            mode: ScrollMode::Wrap,
+            step_ms: DEFAULT_STEP_MS,
+            dwell_ms: DEFAULT_DWELL_MS,
+            position: 0,
+            forward: true,
+            elapsed_ms: 0,
+        }
+    }
+
+    pub fn with_mode(mut self, mode: ScrollMode) -> Self {
+        self.mode = mode;
+        self.restart();
+        self
+    }
+
+    /// Time between two single digit steps, zero stops the scrolling
+    pub fn with_step(mut self, step_ms: u32) -> Self {
+        self.step_ms = step_ms;
+        self
+    }
+
+    /// Additional time the text is held when aligned to the display edge
+    pub fn with_dwell(mut self, dwell_ms: u32) -> Self {
+        self.dwell_ms = dwell_ms;
+        self
+    }
+
+    /// Replace the text and restart scrolling
+    pub fn set_text(&mut self, text: &'a str) {
+        self.text = text;
+        self.len = cells(text).count();
+        self.restart();
+    }
+
+    pub fn restart(&mut self) {
+        self.position = 0;
+        self.forward = true;
+        self.elapsed_ms = 0;
+    }
+
+    /// Advance the animation by `elapsed_ms` milliseconds, returns true
+    /// if the text moved and the display needs to be updated
+    pub fn update(&mut self, elapsed_ms: u32) -> bool {
+        if self.step_ms == 0 || !self.is_scrolling() {
+            return false;
+        }
+
+        self.elapsed_ms = self.elapsed_ms.saturating_add(elapsed_ms);
+        let mut moved = false;
+
+        loop {
+            let hold_ms = match self.is_dwell_position() {
+                true => self.step_ms + self.dwell_ms,
+                false => self.step_ms,
+            };
+
+            if self.elapsed_ms < hold_ms {
+                break;
+            }
+
+            self.elapsed_ms -= hold_ms;
+            self.advance();
+            moved = true;
+        }
+
+        moved
+    }
+
+    /// Segment patterns of the visible digits ordered from left to right
+    pub fn frame(&self) -> [u8; DIGITS] {
+        let mut frame = [0; DIGITS];
+
+        match self.mode {
+            ScrollMode::Wrap => {
+                // Virtual tape of blank digits followed by the text
+                let period = self.len + DIGITS;
+
+                for (index, segments) in frame.iter_mut().enumerate() {
+                    let tape = (self.position + index) % period;
+
+                    if tape >= DIGITS {
+                        *segments = cells(self.text).nth(tape - DIGITS).unwrap_or(0);
+                    }
+                }
+            },
+            ScrollMode::Bounce => {
+                for (segments, cell) in frame.iter_mut().zip(cells(self.text).skip(self.position)) {
+                    *segments = cell;
+                }
+            },
+        }
+
+        frame
+    }
+
+    fn is_scrolling(&self) -> bool {
+        match self.mode {
+            ScrollMode::Wrap => true,
+            ScrollMode::Bounce => self.len > DIGITS,
+        }
+    }
+
+    fn is_dwell_position(&self) -> bool {
+        match self.mode {
+            ScrollMode::Wrap => self.position == DIGITS,
+            ScrollMode::Bounce => self.position == 0 || self.position == self.len - DIGITS,
+        }
+    }
+
+    fn advance(&mut self) {
+        match self.mode {
+            ScrollMode::Wrap => {
+                self.position = (self.position + 1) % (self.len + DIGITS);
+            },
+            ScrollMode::Bounce => {
+                if self.forward {
+                    self.position += 1;
+                    self.forward = self.position < self.len - DIGITS;
+                } else {
+                    self.position -= 1;
+                    self.forward = self.position == 0;
+                }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::glyph::glyph;
+
+    const A: u8 = 0x77;
+    const B: u8 = 0x1f;
+
+    #[test]
+    fn wrap_enters_from_the_right() {
+        let mut scroller = Scroller::new("Ab").with_step(100).with_dwell(1000);
+        assert_eq!(scroller.frame(), [0; DIGITS]);
+
+        assert!(!scroller.update(99));
+        assert!(scroller.update(1));
+        assert_eq!(scroller.frame(), [0, 0, 0, 0, 0, 0, 0, A]);
+
+        assert!(scroller.update(100));
+        assert_eq!(scroller.frame(), [0, 0, 0, 0, 0, 0, A, B]);
+    }
+
+    #[test]
+    fn wrap_dwells_at_the_left_edge() {
+        let mut scroller = Scroller::new("Ab").with_step(100).with_dwell(1000);
+
+        // Eight steps align the text to the left edge
+        assert!(scroller.update(800));
+        assert_eq!(scroller.frame(), [A, B, 0, 0, 0, 0, 0, 0]);
+
+        assert!(!scroller.update(1099));
+        assert!(scroller.update(1));
+        assert_eq!(scroller.frame(), [B, 0, 0, 0, 0, 0, 0, 0]);
+
+        // Text leaves at the left edge and the tape starts over blank
+        assert!(scroller.update(100));
+        assert_eq!(scroller.frame(), [0; DIGITS]);
+        assert!(scroller.update(100));
+        assert_eq!(scroller.frame(), [0, 0, 0, 0, 0, 0, 0, A]);
+    }
+
+    #[test]
+    fn large_elapsed_time_takes_several_steps() {
+        let mut scroller = Scroller::new("Ab").with_step(100).with_dwell(0);
+        assert!(scroller.update(350));
+        assert_eq!(scroller.frame(), [0, 0, 0, 0, 0, A, B, 0]);
+
+        // The remaining 50 ms count towards the next step
+        assert!(scroller.update(50));
+        assert_eq!(scroller.frame(), [0, 0, 0, 0, A, B, 0, 0]);
+    }
+
+    #[test]
+    fn bounce_short_text_stays_still() {
+        let mut scroller = Scroller::new("Ab").with_mode(ScrollMode::Bounce).with_step(100);
+        assert!(!scroller.update(10_000));
+        assert_eq!(scroller.frame(), [A, B, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn bounce_dwells_at_both_ends() {
+        let text = "0123456789";
+        let digits = |first: usize| {
+            let mut frame = [0; DIGITS];
+            for (segments, character) in frame.iter_mut().zip(text.chars().skip(first)) {
+                *segments = glyph(character);
+            }
+            frame
+        };
+
+        let mut scroller = Scroller::new(text).with_mode(ScrollMode::Bounce).with_step(100).with_dwell(500);
+        assert_eq!(scroller.frame(), digits(0));
+
+        // Start is held for the step and the dwell
+        assert!(!scroller.update(599));
+        assert!(scroller.update(1));
+        assert_eq!(scroller.frame(), digits(1));
+
+        assert!(scroller.update(100));
+        assert_eq!(scroller.frame(), digits(2));
+
+        // End is held too, then the text moves back
+        assert!(!scroller.update(599));
+        assert!(scroller.update(1));
+        assert_eq!(scroller.frame(), digits(1));
+
+        assert!(scroller.update(100));
+        assert_eq!(scroller.frame(), digits(0));
+
+        assert!(!scroller.update(599));
+        assert!(scroller.update(1));
+        assert_eq!(scroller.frame(), digits(1));
+    }
+
+    #[test]
+    fn zero_step_stops_scrolling() {
+        let mut scroller = Scroller::new("Ab").with_step(0);
+        assert!(!scroller.update(10_000));
+        assert_eq!(scroller.frame(), [0; DIGITS]);
+    }
+
+    #[test]
+    fn set_text_restarts() {
+        let mut scroller = Scroller::new("Ab").with_step(100).with_dwell(0);
+        scroller.update(800);
+        scroller.set_text("bA");
+        assert_eq!(scroller.frame(), [0; DIGITS]);
+        scroller.update(800);
+        assert_eq!(scroller.frame(), [B, A, 0, 0, 0, 0, 0, 0]);
+    }
+}
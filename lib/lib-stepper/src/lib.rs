@@ -0,0 +1,13 @@
+#![no_std]
+
+//! Stepper motors with trapezoidal speed profiles
+//!
+//! `Stepper::tick` is called from a periodic timer interrupt, it plans
+//! the speed and emits steps through a `StepOutput`, which is either
+//! a STEP/DIR driver or a unipolar motor switched by GPIO pins.
+
+mod output;
+mod stepper;
+
+pub use output::{Direction, HalfStep, StepDir, StepOutput};
+pub use stepper::{Config, Stepper};
@@ -0,0 +1,154 @@
+use embedded_hal::digital::v2::OutputPin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Forward,
+    Reverse,
+}
+
+/// Hardware making the steps, all methods are called from `Stepper::tick`
+pub trait StepOutput {
+    type Error;
+
+    /// Called at least one tick before the first step in a new direction
+    fn set_direction(&mut self, direction: Direction) -> Result<(), Self::Error>;
+
+    /// Make a single step in the direction set before
+    fn step(&mut self) -> Result<(), Self::Error>;
+
+    /// Called on the tick following a step, steps never come on two
+    /// consecutive ticks
+    fn idle(&mut self) -> Result<(), Self::Error>;
+}
+
+/// STEP/DIR driver (A4988, DRV8825), the step pulse takes one tick
+pub struct StepDir<STEP, DIR> {
+    step: STEP,
+    dir: DIR,
+}
+
+impl<STEP, DIR, E> StepDir<STEP, DIR>
+where
+    STEP: OutputPin<Error = E>,
+    DIR: OutputPin<Error = E>,
+{
+    pub fn new(mut step: STEP, dir: DIR) -> Result<Self, E> {
+        step.set_low()?;
+        Ok(Self { step, dir })
+    }
+
+    pub fn release(self) -> (STEP, DIR) {
+        (self.step, self.dir)
+    }
+}
+
+impl<STEP, DIR, E> StepOutput for StepDir<STEP, DIR>
+where
+    STEP: OutputPin<Error = E>,
+    DIR: OutputPin<Error = E>,
+{
+    type Error = E;
+
+    fn set_direction(&mut self, direction: Direction) -> Result<(), Self::Error> {
+        match direction {
+            Direction::Forward => self.dir.set_high(),
+            Direction::Reverse => self.dir.set_low(),
+        }
+    }
+
+    fn step(&mut self) -> Result<(), Self::Error> {
+        self.step.set_high()
+    }
+
+    fn idle(&mut self) -> Result<(), Self::Error> {
+        self.step.set_low()
+    }
+}
+
+/// Coils energized in each of the eight half steps (IN1 - IN4 in bits 0 - 3)
+const HALF_STEPS: [u8; 8] = [
+    0b0001, 0b0011, 0b0010, 0b0110, 0b0100, 0b1100, 0b1000, 0b1001,
+];
+
+/// Unipolar motor (28BYJ-48) switched by a ULN2003 driver using half
+/// steps, 4096 half steps make a single turn of the 28BYJ-48 output shaft
+pub struct HalfStep<IN1, IN2, IN3, IN4> {
+    in1: IN1,
+    in2: IN2,
+    in3: IN3,
+    in4: IN4,
+    phase: usize,
+    direction: Direction,
+}
+
+impl<IN1, IN2, IN3, IN4, E> HalfStep<IN1, IN2, IN3, IN4>
+where
+    IN1: OutputPin<Error = E>,
+    IN2: OutputPin<Error = E>,
+    IN3: OutputPin<Error = E>,
+    IN4: OutputPin<Error = E>,
+{
+    /// Coils stay off until the first step
+    pub fn new(in1: IN1, in2: IN2, in3: IN3, in4: IN4) -> Result<Self, E> {
+        let mut output = Self { in1, in2, in3, in4, phase: 0, direction: Direction::Forward };
+        output.power_off()?;
+        Ok(output)
+    }
+
+    /// Turn all coils off, the motor does not hold its position,
+    /// but it does not heat up either
+    pub fn power_off(&mut self) -> Result<(), E> {
+        self.write(0)
+    }
+
+    /// Energize coils of the current half step to hold the position
+    pub fn hold(&mut self) -> Result<(), E> {
+        self.write(HALF_STEPS[self.phase])
+    }
+
+    pub fn release(self) -> (IN1, IN2, IN3, IN4) {
+        (self.in1, self.in2, self.in3, self.in4)
+    }
+
+    fn write(&mut self, coils: u8) -> Result<(), E> {
+        set(&mut self.in1, coils & 0b0001 != 0)?;
+        set(&mut self.in2, coils & 0b0010 != 0)?;
+        set(&mut self.in3, coils & 0b0100 != 0)?;
+        set(&mut self.in4, coils & 0b1000 != 0)
+    }
+}
+
+impl<IN1, IN2, IN3, IN4, E> StepOutput for HalfStep<IN1, IN2, IN3, IN4>
+where
+    IN1: OutputPin<Error = E>,
+    IN2: OutputPin<Error = E>,
+    IN3: OutputPin<Error = E>,
+    IN4: OutputPin<Error = E>,
+{
+    type Error = E;
+
+    fn set_direction(&mut self, direction: Direction) -> Result<(), Self::Error> {
+        self.direction = direction;
+        Ok(())
+    }
+
+    fn step(&mut self) -> Result<(), Self::Error> {
+        self.phase = match self.direction {
+            Direction::Forward => (self.phase + 1) % HALF_STEPS.len(),
+            Direction::Reverse => (self.phase + HALF_STEPS.len() - 1) % HALF_STEPS.len(),
+        };
+
+        self.hold()
+    }
+
+    fn idle(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+fn set<P: OutputPin>(pin: &mut P, high: bool) -> Result<(), P::Error> {
+    match high {
+        true => pin.set_high(),
+        false => pin.set_low(),
+    }
+}
@@ -0,0 +1,230 @@
+use crate::output::{Direction, StepOutput};
+
+/// Speeds are kept in thousandths of a step per second
+const SCALE: u32 = 1000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// Cruise speed in steps per second, limited to half of the tick rate
+    pub max_speed: u32,
+    /// Acceleration and deceleration in steps per second squared,
+    /// zero means starting and stopping at the full speed
+    pub acceleration: u32,
+    /// Speed the motor starts from and stops at, a motor can usually
+    /// start at a non-zero speed without losing steps
+    pub start_speed: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self { max_speed: 1000, acceleration: 2000, start_speed: 100 }
+    }
+}
+
+/// Stepper motor moving to absolute positions with a trapezoidal speed
+/// profile, `tick` is called `tick_rate` times per second
+pub struct Stepper<O> {
+    output: O,
+    config: Config,
+    tick_rate: u32,
+    position: i32,
+    target: i32,
+    /// Current speed, zero while stopped
+    speed: u32,
+    /// Step is made when the phase reaches `tick_rate*SCALE`
+    phase: u32,
+    /// Remainder of the acceleration not yet added to the speed
+    acceleration_remainder: u32,
+    /// Direction of the movement, `None` while stopped
+    direction: Option<Direction>,
+    stepped: bool,
+}
+
+impl<O: StepOutput> Stepper<O> {
+    pub fn new(output: O, config: Config, tick_rate: u32) -> Self {
+        Self {
+            output,
+            config,
+            tick_rate: tick_rate.max(1),
+            position: 0,
+            target: 0,
+            speed: 0,
+            phase: 0,
+            acceleration_remainder: 0,
+            direction: None,
+            stepped: false,
+        }
+    }
+
+    pub fn config(&self) -> &Config {
+        &self.config
+    }
+
+    /// New limits apply to the current move as well
+    pub fn set_config(&mut self, config: Config) {
+        self.config = config;
+    }
+
+    /// Current position in steps
+    pub fn position(&self) -> i32 {
+        self.position
+    }
+
+    pub fn target(&self) -> i32 {
+        self.target
+    }
+
+    /// Current speed in steps per second
+    pub fn speed(&self) -> u32 {
+        self.speed/SCALE
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.direction.is_some() || self.position != self.target
+    }
+
+    /// Redefine the current position, ignored while moving
+    pub fn set_position(&mut self, position: i32) {
+        if !self.is_moving() {
+            self.position = position;
+            self.target = position;
+        }
+    }
+
+    /// Move to an absolute position, the current move is smoothly
+    /// redirected to the new target
+    pub fn move_to(&mut self, position: i32) {
+        self.target = position;
+    }
+
+    /// Move relative to the current target
+    pub fn move_by(&mut self, steps: i32) {
+        self.target = self.target.saturating_add(steps);
+    }
+
+    /// Decelerate and stop as soon as possible
+    pub fn stop(&mut self) {
+        let distance = self.stopping_distance().min(i32::MAX as u64) as i32;
+
+        self.target = match self.direction {
+            Some(Direction::Forward) => self.position.saturating_add(distance),
+            Some(Direction::Reverse) => self.position.saturating_sub(distance),
+            None => self.position,
+        };
+    }
+
+    pub fn output_mut(&mut self) -> &mut O {
+        &mut self.output
+    }
+
+    pub fn release(self) -> O {
+        self.output
+    }
+
+    /// Advance the motion by a single tick
+    pub fn tick(&mut self) -> Result<(), O::Error> {
+        if self.stepped {
+            self.stepped = false;
+            self.output.idle()?;
+        }
+
+        let remaining = self.target as i64 - self.position as i64;
+
+        let direction = match self.direction {
+            Some(direction) => direction,
+            None if remaining == 0 => return Ok(()),
+            None => {
+                // Direction is set a tick ahead of the first step
+                let direction = match remaining > 0 {
+                    true => Direction::Forward,
+                    false => Direction::Reverse,
+                };
+
+                self.output.set_direction(direction)?;
+                self.direction = Some(direction);
+                self.speed = self.start_speed();
+                self.phase = 0;
+                return Ok(());
+            }
+        };
+
+        // Distance to the target in the direction of the movement,
+        // negative when the target is behind
+        let ahead = match direction {
+            Direction::Forward => remaining,
+            Direction::Reverse => -remaining,
+        };
+
+        if ahead == 0 || (ahead < 0 && self.speed <= self.start_speed()) {
+            // Target reached, or stopped before turning back
+            self.halt();
+            return Ok(());
+        }
+
+        if ahead < 0 || ahead as u64 <= self.stopping_distance() {
+            self.speed = self.speed.saturating_sub(self.speed_step()).max(self.start_speed());
+        } else {
+            self.speed = self.speed.saturating_add(self.speed_step()).min(self.max_speed());
+        }
+
+        self.phase += self.speed;
+
+        if self.phase >= self.tick_rate*SCALE {
+            self.phase -= self.tick_rate*SCALE;
+            self.output.step()?;
+            self.stepped = true;
+
+            self.position = match direction {
+                Direction::Forward => self.position.wrapping_add(1),
+                Direction::Reverse => self.position.wrapping_sub(1),
+            };
+
+            if self.position == self.target {
+                self.halt();
+            }
+        }
+
+        Ok(())
+    }
+
+    fn halt(&mut self) {
+        self.direction = None;
+        self.speed = 0;
+        self.acceleration_remainder = 0;
+    }
+
+    /// Steps can not come on two consecutive ticks
+    fn max_speed(&self) -> u32 {
+        self.config.max_speed.min(self.tick_rate/2).max(1)*SCALE
+    }
+
+    fn start_speed(&self) -> u32 {
+        self.config.start_speed.saturating_mul(SCALE).clamp(1, self.max_speed())
+    }
+
+    /// Speed change during a single tick
+    fn speed_step(&mut self) -> u32 {
+        if self.config.acceleration == 0 {
+            return self.max_speed();
+        }
+
+        self.acceleration_remainder = self.acceleration_remainder
+            .saturating_add(self.config.acceleration.saturating_mul(SCALE));
+        let step = self.acceleration_remainder/self.tick_rate;
+        self.acceleration_remainder %= self.tick_rate;
+        step
+    }
+
+    /// Steps needed to decelerate from the current speed to the start speed
+    fn stopping_distance(&self) -> u64 {
+        if self.config.acceleration == 0 {
+            return 0;
+        }
+
+        let speed = self.speed as u64;
+        let start = self.start_speed() as u64;
+        let scale = SCALE as u64;
+
+        (speed*speed).saturating_sub(start*start)/(2*self.config.acceleration as u64*scale*scale)
+    }
+}
@@ -0,0 +1,86 @@
+/// Maximum number of segment bytes in a single command, TM1637 has six
+/// digit registers
+pub const MAX_SEGMENTS: usize = 6;
+
+/// Maximum number of bytes sent between a start and a stop condition
+pub(crate) const MAX_BYTES: usize = MAX_SEGMENTS + 1;
+
+const ADDRESS_AUTO_INCREMENT_1_MODE: u8 = 0x40;
+
+const ADDRESS_COMMAND_BITS: u8 = 0xc0;
+const ADDRESS_COMMAND_MASK: u8 = 0x0f;
+
+const DISPLAY_CONTROL_BRIGHTNESS_BITS: u8 = 0x88;
+const DISPLAY_CONTROL_BRIGHTNESS_MASK: u8 = 0x07;
+const DISPLAY_CONTROL_OFF: u8 = 0x80;
+
+/// Segment patterns of hexadecimal digits 0-F
+pub const DIGITS: [u8; 16] = [
+    0x3f, 0x06, 0x5b, 0x4f, //
+    0x66, 0x6d, 0x7d, 0x07, //
+    0x7f, 0x6f, 0x77, 0x7c, //
+    0x39, 0x5e, 0x79, 0x71, //
+];
+
+/// Single TM1637 command transmitted in one start - stop transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Command {
+    /// Select the address auto increment mode, send once after power up
+    Init,
+    /// Turn the display on with brightness level 0-7
+    Brightness(u8),
+    /// Turn the display off
+    DisplayOff,
+    /// Write raw segment data starting at the digit `address`
+    Segments { address: u8, data: [u8; MAX_SEGMENTS], len: u8 },
+}
+
+impl Command {
+    /// Raw segment data, anything beyond `MAX_SEGMENTS` bytes is ignored
+    pub fn segments(address: u8, segments: &[u8]) -> Self {
+        let len = segments.len().min(MAX_SEGMENTS);
+        let mut data = [0; MAX_SEGMENTS];
+        data[..len].copy_from_slice(&segments[..len]);
+        Command::Segments { address, data, len: len as u8 }
+    }
+
+    /// Hexadecimal digits (values 0-15) converted to segment patterns
+    pub fn hex_digits(address: u8, digits: &[u8]) -> Self {
+        let len = digits.len().min(MAX_SEGMENTS);
+        let mut data = [0; MAX_SEGMENTS];
+
+        for (segments, digit) in data.iter_mut().zip(digits.iter()) {
+            *segments = DIGITS[(digit & 0x0f) as usize];
+        }
+
+        Command::Segments { address, data, len: len as u8 }
+    }
+
+    /// Bytes sent on the wire, returns the buffer and the number of bytes used
+    pub(crate) fn encode(&self) -> ([u8; MAX_BYTES], usize) {
+        let mut bytes = [0; MAX_BYTES];
+
+        let len = match *self {
+            Command::Init => {
+                bytes[0] = ADDRESS_AUTO_INCREMENT_1_MODE;
+                1
+            },
+            Command::Brightness(level) => {
+                bytes[0] = DISPLAY_CONTROL_BRIGHTNESS_BITS | (level & DISPLAY_CONTROL_BRIGHTNESS_MASK);
+                1
+            },
+            Command::DisplayOff => {
+                bytes[0] = DISPLAY_CONTROL_OFF;
+                1
+            },
+            Command::Segments { address, data, len } => {
+                let len = (len as usize).min(MAX_SEGMENTS);
+                bytes[0] = ADDRESS_COMMAND_BITS | (address & ADDRESS_COMMAND_MASK);
+                bytes[1..=len].copy_from_slice(&data[..len]);
+                len + 1
+            },
+        };
+
+        (bytes, len)
+    }
+}
@@ -0,0 +1,11 @@
+#![no_std]
+
+//! Non-blocking TM1637 transport driven by a periodic timer interrupt
+
+mod command;
+mod queue;
+mod transport;
+
+pub use command::{Command, DIGITS, MAX_SEGMENTS};
+pub use queue::QUEUE_CAPACITY;
+pub use transport::{Error, Tm1637Transport};
@@ -0,0 +1,65 @@
+use crate::command::Command;
+
+/// Number of commands that can wait for transmission
+pub const QUEUE_CAPACITY: usize = 4;
+
+/// Fixed size FIFO of pending commands
+pub(crate) struct CommandQueue {
+    items: [Command; QUEUE_CAPACITY],
+    head: usize,
+    len: usize,
+}
+
+impl CommandQueue {
+    pub const fn new() -> Self {
+        Self { items: [Command::Init; QUEUE_CAPACITY], head: 0, len: 0 }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn push(&mut self, command: Command) -> Result<(), Command> {
+        if self.len == QUEUE_CAPACITY {
+            return Err(command);
+        }
+
+        self.items[(self.head + self.len) % QUEUE_CAPACITY] = command;
+        self.len += 1;
+        Ok(())
+    }
+
+    pub fn pop(&mut self) -> Option<Command> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let command = self.items[self.head];
+        self.head = (self.head + 1) % QUEUE_CAPACITY;
+        self.len -= 1;
+        Some(command)
+    }
+
+    /// Replace a pending segment write to the same address, so only
+    /// the most recent frame is transmitted when the app is faster than
+    /// the display. Returns the command back if nothing was replaced.
+    pub fn replace_segments(&mut self, command: Command) -> Result<(), Command> {
+        let address = match command {
+            Command::Segments { address, .. } => address,
+            _ => return Err(command),
+        };
+
+        for offset in 0..self.len {
+            let item = &mut self.items[(self.head + offset) % QUEUE_CAPACITY];
+
+            if let Command::Segments { address: pending, .. } = *item {
+                if pending == address {
+                    *item = command;
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(command)
+    }
+}
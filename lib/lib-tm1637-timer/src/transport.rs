@@ -0,0 +1,174 @@
+use embedded_hal::digital::v2::{InputPin, OutputPin};
+
+use crate::command::{Command, MAX_BYTES};
+use crate::queue::CommandQueue;
+
+/// Bit index of the acknowledge clock pulse following the eight data bits
+const ACK_BIT: u8 = 8;
+
+#[derive(Debug)]
+pub enum Error<E> {
+    /// Command queue is full, the rejected command is returned back
+    QueueFull(Command),
+    IO(E),
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(err: E) -> Error<E> {
+        Error::IO(err)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum State {
+    Idle,
+    /// Clock is pulled low and the data bit (or released line for ack) is set
+    ClockLow { bit: u8 },
+    /// Clock is released high, the display latches the data bit
+    ClockHigh { bit: u8 },
+    /// Clock is still high, display pulls data low to acknowledge the byte
+    SampleAck,
+    StopClockLow,
+    StopClockHigh,
+    StopRelease,
+}
+
+/// TM1637 transport clocking the protocol out one half-period per `tick`
+///
+/// `tick` is meant to be called from a periodic timer interrupt, the clock
+/// frequency on the wire is half of the tick frequency. Apps only submit
+/// commands into a small queue and continue with their work while the
+/// transport sends them in the background.
+pub struct Tm1637Transport<CLK, DIO> {
+    clk: CLK,
+    dio: DIO,
+    queue: CommandQueue,
+    state: State,
+    bytes: [u8; MAX_BYTES],
+    len: usize,
+    position: usize,
+    ack_errors: u32,
+}
+
+impl<CLK, DIO, E> Tm1637Transport<CLK, DIO>
+where
+    CLK: OutputPin<Error = E>,
+    DIO: InputPin<Error = E> + OutputPin<Error = E>,
+{
+    /// Both pins are expected to be open drain outputs
+    pub fn new(clk: CLK, dio: DIO) -> Self {
+        Self {
+            clk,
+            dio,
+            queue: CommandQueue::new(),
+            state: State::Idle,
+            bytes: [0; MAX_BYTES],
+            len: 0,
+            position: 0,
+            ack_errors: 0,
+        }
+    }
+
+    /// Queue a command for transmission
+    pub fn submit(&mut self, command: Command) -> Result<(), Error<E>> {
+        self.queue.push(command).map_err(Error::QueueFull)
+    }
+
+    /// Queue a segment write, replacing a pending write to the same address
+    /// that has not been started yet, so the display always ends up showing
+    /// the most recent frame
+    pub fn submit_frame(&mut self, command: Command) -> Result<(), Error<E>> {
+        match self.queue.replace_segments(command) {
+            Ok(()) => Ok(()),
+            Err(command) => self.submit(command),
+        }
+    }
+
+    /// No transaction is running and no command is waiting
+    pub fn is_idle(&self) -> bool {
+        self.state == State::Idle && self.queue.len() == 0
+    }
+
+    /// Number of commands waiting for transmission
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Number of bytes not acknowledged by the display so far
+    pub fn ack_errors(&self) -> u32 {
+        self.ack_errors
+    }
+
+    /// Advance the protocol by a single step
+    pub fn tick(&mut self) -> Result<(), E> {
+        self.state = match self.state {
+            State::Idle => match self.queue.pop() {
+                Some(command) => {
+                    let (bytes, len) = command.encode();
+                    self.bytes = bytes;
+                    self.len = len;
+                    self.position = 0;
+                    // Start condition, data goes low while the clock is high
+                    self.clk.set_high()?;
+                    self.dio.set_low()?;
+                    State::ClockLow { bit: 0 }
+                },
+                None => State::Idle,
+            },
+            State::ClockLow { bit } => {
+                self.clk.set_low()?;
+
+                if bit == ACK_BIT || self.bytes[self.position] & (1 << bit) != 0 {
+                    self.dio.set_high()?;
+                } else {
+                    self.dio.set_low()?;
+                }
+
+                State::ClockHigh { bit }
+            },
+            State::ClockHigh { bit } => {
+                self.clk.set_high()?;
+
+                if bit == ACK_BIT {
+                    State::SampleAck
+                } else {
+                    State::ClockLow { bit: bit + 1 }
+                }
+            },
+            State::SampleAck => {
+                if self.dio.is_high()? {
+                    self.ack_errors = self.ack_errors.wrapping_add(1);
+                }
+
+                self.position += 1;
+
+                if self.position < self.len {
+                    State::ClockLow { bit: 0 }
+                } else {
+                    State::StopClockLow
+                }
+            },
+            State::StopClockLow => {
+                self.clk.set_low()?;
+                self.dio.set_low()?;
+                State::StopClockHigh
+            },
+            State::StopClockHigh => {
+                self.clk.set_high()?;
+                State::StopRelease
+            },
+            State::StopRelease => {
+                // Stop condition, data goes high while the clock is high
+                self.dio.set_high()?;
+                State::Idle
+            },
+        };
+
+        Ok(())
+    }
+
+    /// Give back the pins, any pending commands are dropped
+    pub fn release(self) -> (CLK, DIO) {
+        (self.clk, self.dio)
+    }
+}
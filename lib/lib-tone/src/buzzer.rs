@@ -0,0 +1,72 @@
+use embedded_hal::Pwm;
+use fugit::HertzU32;
+
+use crate::player::Output;
+
+/// Lowest frequency played, lower ones are clamped
+pub const MIN_FREQUENCY_HZ: u32 = 20;
+
+/// Highest frequency played, higher ones are clamped
+pub const MAX_FREQUENCY_HZ: u32 = 20_000;
+
+/// Piezo buzzer on a PWM channel, tones are square waves with 50 % duty
+///
+/// The buzzer changes the period of the whole timer, other channels
+/// of the same timer change their frequency as well.
+pub struct Buzzer<P: Pwm> {
+    pwm: P,
+    channel: P::Channel,
+    frequency_hz: u32,
+}
+
+impl<P> Buzzer<P>
+where
+    P: Pwm<Duty = u16>,
+    P::Channel: Copy,
+    P::Time: From<HertzU32>,
+{
+    /// The buzzer starts silent
+    pub fn new(pwm: P, channel: P::Channel) -> Self {
+        let mut buzzer = Self { pwm, channel, frequency_hz: 0 };
+        buzzer.silence();
+        buzzer
+    }
+
+    /// Play a tone until changed or silenced
+    pub fn tone(&mut self, frequency_hz: u32) {
+        let frequency_hz = frequency_hz.clamp(MIN_FREQUENCY_HZ, MAX_FREQUENCY_HZ);
+
+        if frequency_hz != self.frequency_hz {
+            self.pwm.set_period(HertzU32::from_raw(frequency_hz));
+            self.frequency_hz = frequency_hz;
+        }
+
+        // Maximum duty changes with the period
+        let duty = self.pwm.get_max_duty()/2;
+        self.pwm.set_duty(self.channel, duty);
+        self.pwm.enable(self.channel);
+    }
+
+    pub fn silence(&mut self) {
+        self.pwm.set_duty(self.channel, 0);
+        self.pwm.disable(self.channel);
+    }
+
+    /// Apply the output requested by a `Player`
+    pub fn apply(&mut self, output: Output) {
+        match output {
+            Output::Tone(frequency_hz) => self.tone(frequency_hz),
+            Output::Silence => self.silence(),
+        }
+    }
+
+    /// Frequency of the last tone played
+    pub fn frequency_hz(&self) -> u32 {
+        self.frequency_hz
+    }
+
+    pub fn release(mut self) -> P {
+        self.silence();
+        self.pwm
+    }
+}
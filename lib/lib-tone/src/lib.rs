@@ -0,0 +1,16 @@
+#![no_std]
+
+//! Piezo buzzer tones and RTTTL (ringtone text) melodies
+//!
+//! `Player` only tracks timing of the melody, it is advanced from a timer
+//! interrupt and tells when the `Buzzer` should change its tone.
+
+mod buzzer;
+mod note;
+mod player;
+mod rtttl;
+
+pub use buzzer::{Buzzer, MAX_FREQUENCY_HZ, MIN_FREQUENCY_HZ};
+pub use note::{frequency, Note, Pitch};
+pub use player::{Output, Player};
+pub use rtttl::{Error, Melody, Notes, Settings};
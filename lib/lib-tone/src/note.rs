@@ -0,0 +1,46 @@
+/// Note names of a single octave
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pitch {
+    C, CSharp, D, DSharp, E, F, FSharp, G, GSharp, A, ASharp, B,
+}
+
+/// Frequencies of the fourth octave in hundredths of Hz (A4 = 440 Hz)
+const OCTAVE_4: [u32; 12] = [
+    26163, 27718, 29366, 31113, 32963, 34923, 36999, 39200, 41530, 44000, 46616, 49388,
+];
+
+/// Frequency of a note in Hz, each octave up doubles the frequency,
+/// octaves are clamped to 0 - 9
+pub fn frequency(pitch: Pitch, octave: u8) -> u32 {
+    let base = OCTAVE_4[pitch as usize];
+    let octave = octave.min(9);
+
+    let centihertz = match octave >= 4 {
+        true => base << (octave - 4),
+        false => base >> (4 - octave),
+    };
+
+    (centihertz + 50)/100
+}
+
+/// A tone or a pause of a melody
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Note {
+    /// Tone frequency, zero for a pause
+    pub frequency_hz: u32,
+    pub duration_ms: u32,
+}
+
+impl Note {
+    pub const fn new(frequency_hz: u32, duration_ms: u32) -> Self {
+        Self { frequency_hz, duration_ms }
+    }
+
+    pub const fn pause(duration_ms: u32) -> Self {
+        Self::new(0, duration_ms)
+    }
+
+    pub fn is_pause(&self) -> bool {
+        self.frequency_hz == 0
+    }
+}
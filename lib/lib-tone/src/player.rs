@@ -0,0 +1,140 @@
+use crate::rtttl::{Melody, Notes};
+
+/// Longest silence inserted at the end of each note, so that repeated
+/// notes of the same pitch can be told apart
+const ARTICULATION_MS: u32 = 20;
+
+/// Change of the buzzer output requested by the player
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Output {
+    Tone(u32),
+    Silence,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Phase {
+    Idle,
+    Note,
+    Gap,
+}
+
+/// Non-blocking melody player advanced by `update`, typically called
+/// from a periodic timer interrupt
+pub struct Player<'a> {
+    melody: Option<Melody<'a>>,
+    notes: Option<Notes<'a>>,
+    looping: bool,
+    phase: Phase,
+    remaining_ms: u32,
+    gap_ms: u32,
+    pending: Option<Output>,
+    output: Output,
+}
+
+impl<'a> Player<'a> {
+    pub const fn new() -> Self {
+        Self {
+            melody: None,
+            notes: None,
+            looping: false,
+            phase: Phase::Idle,
+            remaining_ms: 0,
+            gap_ms: 0,
+            pending: None,
+            output: Output::Silence,
+        }
+    }
+
+    /// Play `melody` once from the beginning, replacing the current one
+    pub fn play(&mut self, melody: Melody<'a>) {
+        self.start(melody, false);
+    }
+
+    /// Play `melody` repeatedly until stopped
+    pub fn play_looped(&mut self, melody: Melody<'a>) {
+        self.start(melody, true);
+    }
+
+    /// Stop playing, the next `update` silences the buzzer
+    pub fn stop(&mut self) {
+        if self.phase != Phase::Idle {
+            self.phase = Phase::Idle;
+            self.pending = Some(Output::Silence);
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.phase != Phase::Idle
+    }
+
+    /// Advance by `elapsed_ms`, returns the new output if it should change
+    pub fn update(&mut self, elapsed_ms: u32) -> Option<Output> {
+        let mut elapsed_ms = elapsed_ms;
+
+        while self.phase != Phase::Idle {
+            if self.remaining_ms > elapsed_ms {
+                self.remaining_ms -= elapsed_ms;
+                break;
+            }
+
+            elapsed_ms -= self.remaining_ms;
+
+            if self.phase == Phase::Note && self.gap_ms > 0 {
+                self.phase = Phase::Gap;
+                self.remaining_ms = self.gap_ms;
+                self.pending = Some(Output::Silence);
+            } else {
+                self.next_note();
+            }
+        }
+
+        // Report only real changes, a pause after a gap keeps the silence
+        match self.pending.take() {
+            Some(output) if output != self.output => {
+                self.output = output;
+                Some(output)
+            }
+            _ => None,
+        }
+    }
+
+    fn start(&mut self, melody: Melody<'a>, looping: bool) {
+        self.melody = Some(melody);
+        self.notes = Some(melody.notes());
+        self.looping = looping;
+        self.phase = Phase::Gap;
+        self.remaining_ms = 0;
+        self.gap_ms = 0;
+    }
+
+    fn next_note(&mut self) {
+        let mut note = self.notes.as_mut().and_then(|notes| notes.next());
+
+        if note.is_none() && self.looping {
+            self.notes = self.melody.map(|melody| melody.notes());
+            note = self.notes.as_mut().and_then(|notes| notes.next());
+        }
+
+        match note {
+            Some(note) => {
+                self.phase = Phase::Note;
+                self.gap_ms = ARTICULATION_MS.min(note.duration_ms/4);
+                self.remaining_ms = note.duration_ms - self.gap_ms;
+                self.pending = Some(match note.is_pause() {
+                    true => Output::Silence,
+                    false => Output::Tone(note.frequency_hz),
+                });
+            }
+            None => {
+                self.phase = Phase::Idle;
+                self.pending = Some(Output::Silence);
+            }
+        }
+    }
+}
+
+impl<'a> Default for Player<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
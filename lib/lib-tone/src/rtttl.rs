@@ -0,0 +1,278 @@
+use crate::note::{frequency, Note, Pitch};
+
+/// Note durations allowed by RTTTL, 1 is a whole note
+const DURATIONS: [u8; 6] = [1, 2, 4, 8, 16, 32];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Text does not consist of name, settings and notes separated by `:`
+    Format,
+    /// Unknown setting or invalid setting value
+    Setting,
+    /// Note duration other than 1, 2, 4, 8, 16 or 32
+    Duration,
+    /// Unknown note name or unexpected characters in a note
+    Note,
+    Octave,
+    /// Melody contains no notes
+    Empty,
+}
+
+/// Default duration and octave of notes and tempo of the melody
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Settings {
+    pub duration: u8,
+    pub octave: u8,
+    /// Beats (quarter notes) per minute
+    pub bpm: u16,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self { duration: 4, octave: 6, bpm: 63 }
+    }
+}
+
+/// Melody in the RTTTL format, for example
+/// `alarm:d=8,o=5,b=180:c6,p,c6,p,c6,4p`
+///
+/// The text is validated when parsed and notes are decoded on the fly,
+/// so the melody takes no memory beyond the borrowed text.
+#[derive(Clone, Copy, Debug)]
+pub struct Melody<'a> {
+    name: &'a str,
+    settings: Settings,
+    notes: &'a str,
+}
+
+impl<'a> Melody<'a> {
+    pub fn parse(text: &'a str) -> Result<Self, Error> {
+        let mut sections = text.splitn(3, ':');
+
+        let name = sections.next().ok_or(Error::Format)?.trim();
+        let settings = parse_settings(sections.next().ok_or(Error::Format)?)?;
+        let notes = sections.next().ok_or(Error::Format)?;
+
+        let mut count = 0;
+
+        for token in tokens(notes) {
+            parse_note(token, &settings)?;
+            count += 1;
+        }
+
+        match count {
+            0 => Err(Error::Empty),
+            _ => Ok(Self { name, settings, notes }),
+        }
+    }
+
+    pub fn name(&self) -> &'a str {
+        self.name
+    }
+
+    pub fn settings(&self) -> &Settings {
+        &self.settings
+    }
+
+    pub fn notes(&self) -> Notes<'a> {
+        Notes { tokens: self.notes.split(','), settings: self.settings }
+    }
+
+    /// Total length of the melody
+    pub fn duration_ms(&self) -> u32 {
+        self.notes().map(|note| note.duration_ms).sum()
+    }
+}
+
+/// Iterator over notes of a melody
+#[derive(Clone, Debug)]
+pub struct Notes<'a> {
+    tokens: core::str::Split<'a, char>,
+    settings: Settings,
+}
+
+impl<'a> Iterator for Notes<'a> {
+    type Item = Note;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Notes were validated by `Melody::parse`, so no note is skipped
+        // except empty ones
+        loop {
+            let token = self.tokens.next()?.trim();
+
+            if token.is_empty() {
+                continue;
+            }
+
+            if let Ok(note) = parse_note(token, &self.settings) {
+                return Some(note);
+            }
+        }
+    }
+}
+
+fn tokens(notes: &str) -> impl Iterator<Item = &str> {
+    notes.split(',').map(|token| token.trim()).filter(|token| !token.is_empty())
+}
+
+fn parse_settings(text: &str) -> Result<Settings, Error> {
+    let mut settings = Settings::default();
+
+    for item in tokens(text) {
+        let (key, value) = item.split_once('=').ok_or(Error::Setting)?;
+        let value: u16 = value.trim().parse().map_err(|_| Error::Setting)?;
+
+        match key.trim() {
+            "d" | "D" => settings.duration = parse_duration(value)?,
+            "o" | "O" => settings.octave = parse_octave(value)?,
+            "b" | "B" if value > 0 => settings.bpm = value,
+            _ => return Err(Error::Setting),
+        }
+    }
+
+    Ok(settings)
+}
+
+fn parse_note(token: &str, settings: &Settings) -> Result<Note, Error> {
+    let bytes = token.as_bytes();
+    let mut position = 0;
+
+    let duration = match take_number(bytes, &mut position) {
+        Some(value) => parse_duration(value)?,
+        None => settings.duration,
+    };
+
+    let name = bytes.get(position).ok_or(Error::Note)?.to_ascii_lowercase();
+    position += 1;
+
+    let sharp = take(bytes, &mut position, b'#');
+    let mut dotted = take(bytes, &mut position, b'.');
+
+    let octave = match take_number(bytes, &mut position) {
+        Some(value) => parse_octave(value)?,
+        None => settings.octave,
+    };
+
+    // Dot is placed after the octave by many melodies
+    dotted |= take(bytes, &mut position, b'.');
+
+    if position != bytes.len() {
+        return Err(Error::Note);
+    }
+
+    let frequency_hz = match name {
+        b'p' => 0,
+        _ => {
+            let (pitch, octave_up) = pitch(name, sharp)?;
+            frequency(pitch, octave + octave_up)
+        },
+    };
+
+    let mut duration_ms = 240_000/(settings.bpm as u32*duration as u32);
+
+    if dotted {
+        duration_ms += duration_ms/2;
+    }
+
+    Ok(Note::new(frequency_hz, duration_ms.max(1)))
+}
+
+/// Pitch of the note and the number of octaves it is above the octave
+/// written with it, `b#` is `c` of the next octave
+fn pitch(name: u8, sharp: bool) -> Result<(Pitch, u8), Error> {
+    Ok(match (name, sharp) {
+        (b'c', false) => (Pitch::C, 0),
+        (b'c', true) => (Pitch::CSharp, 0),
+        (b'd', false) => (Pitch::D, 0),
+        (b'd', true) => (Pitch::DSharp, 0),
+        (b'e', false) => (Pitch::E, 0),
+        (b'e', true) => (Pitch::F, 0),
+        (b'f', false) => (Pitch::F, 0),
+        (b'f', true) => (Pitch::FSharp, 0),
+        (b'g', false) => (Pitch::G, 0),
+        (b'g', true) => (Pitch::GSharp, 0),
+        (b'a', false) => (Pitch::A, 0),
+        (b'a', true) => (Pitch::ASharp, 0),
+        // Some melodies use the German `h` for `b`
+        (b'b' | b'h', false) => (Pitch::B, 0),
+        (b'b' | b'h', true) => (Pitch::C, 1),
+        _ => return Err(Error::Note),
+    })
+}
+
+fn parse_duration(value: u16) -> Result<u8, Error> {
+    DURATIONS.iter().copied().find(|&duration| duration as u16 == value).ok_or(Error::Duration)
+}
+
+fn parse_octave(value: u16) -> Result<u8, Error> {
+    match value {
+        0..=9 => Ok(value as u8),
+        _ => Err(Error::Octave),
+    }
+}
+
+fn take(bytes: &[u8], position: &mut usize, expected: u8) -> bool {
+    match bytes.get(*position) == Some(&expected) {
+        true => {
+            *position += 1;
+            true
+        }
+        false => false,
+    }
+}
+
+fn take_number(bytes: &[u8], position: &mut usize) -> Option<u16> {
+    let start = *position;
+    let mut value: u16 = 0;
+
+    while let Some(digit) = bytes.get(*position).filter(|byte| byte.is_ascii_digit()) {
+        value = value.saturating_mul(10).saturating_add((digit - b'0') as u16);
+        *position += 1;
+    }
+
+    match *position > start {
+        true => Some(value),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use std::vec::Vec;
+
+    use super::*;
+
+    fn frequencies(text: &str) -> Vec<u32> {
+        Melody::parse(text).unwrap().notes().map(|note| note.frequency_hz).collect()
+    }
+
+    #[test]
+    fn sharps_without_a_black_key() {
+        assert_eq!(frequencies("x:d=4,o=5,b=120:e#,f,b#,c6"), [698, 698, 1047, 1047]);
+        assert_eq!(frequencies("x:d=4,o=5,b=120:e#4,b#4,h#4"), [349, 523, 523]);
+    }
+
+    #[test]
+    fn notes_and_durations() {
+        let melody = Melody::parse("alarm:d=8,o=5,b=180:c6,p,a,4c#.").unwrap();
+        let notes: Vec<Note> = melody.notes().collect();
+
+        assert_eq!(melody.name(), "alarm");
+        assert_eq!(notes, [
+            Note::new(1047, 166),
+            Note::pause(166),
+            Note::new(880, 166),
+            Note::new(554, 499),
+        ]);
+    }
+
+    #[test]
+    fn invalid_notes() {
+        assert_eq!(Melody::parse("x:d=4,o=5,b=120:x").unwrap_err(), Error::Note);
+        assert_eq!(Melody::parse("x:d=4,o=5,b=120:c##").unwrap_err(), Error::Note);
+        assert_eq!(Melody::parse("x:d=4,o=5,b=120:3c").unwrap_err(), Error::Duration);
+        assert_eq!(Melody::parse("x:d=4,o=5,b=120:").unwrap_err(), Error::Empty);
+    }
+}
@@ -0,0 +1,87 @@
+use lib_rgb_led::Rgb;
+
+/// SPI clock the bit patterns are designed for
+pub const SPI_FREQUENCY_HZ: u32 = 2_250_000;
+
+/// Three color bytes, each encoded into three SPI bytes
+pub const BYTES_PER_LED: usize = 9;
+
+/// Low bytes at the end of the frame, 80 bytes at 2.25 MHz take 284 us,
+/// which satisfies the reset time of both the older (50 us) and newer
+/// (280 us) WS2812 revisions
+pub const RESET_BYTES: usize = 80;
+
+/// Size of the SPI buffer needed for `leds` pixels
+pub const fn frame_size(leds: usize) -> usize {
+    leds*BYTES_PER_LED + RESET_BYTES
+}
+
+/// SPI bit stream of a LED strip stored in a borrowed buffer
+pub struct Frame<'a> {
+    data: &'a mut [u8],
+}
+
+impl<'a> Frame<'a> {
+    /// Wrap a buffer created with `frame_size`, the reset gap is cleared,
+    /// pixel data are kept as they are
+    pub fn new(data: &'a mut [u8]) -> Self {
+        let leds = data.len().saturating_sub(RESET_BYTES)/BYTES_PER_LED;
+        data[leds*BYTES_PER_LED..].iter_mut().for_each(|byte| *byte = 0);
+        Self { data }
+    }
+
+    /// Number of pixels fitting into the buffer
+    pub fn len(&self) -> usize {
+        self.data.len().saturating_sub(RESET_BYTES)/BYTES_PER_LED
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Set the colour of a pixel, pixels outside of the strip are ignored
+    pub fn set(&mut self, index: usize, color: Rgb) {
+        if index >= self.len() {
+            return;
+        }
+
+        let offset = index*BYTES_PER_LED;
+        let pixel = &mut self.data[offset..offset + BYTES_PER_LED];
+
+        // WS2812 expects the green component first
+        for (bytes, component) in pixel.chunks_mut(3).zip([color.g, color.r, color.b]) {
+            bytes.copy_from_slice(&encode(component));
+        }
+    }
+
+    pub fn fill(&mut self, color: Rgb) {
+        for index in 0..self.len() {
+            self.set(index, color);
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.fill(Rgb::new(0, 0, 0));
+    }
+
+    /// Whole bit stream including the reset gap
+    pub fn as_bytes(&self) -> &[u8] {
+        self.data
+    }
+}
+
+/// Expand eight bits into 24 SPI bits, most significant bit first
+fn encode(value: u8) -> [u8; 3] {
+    let mut bits: u32 = 0;
+
+    for bit in (0..8).rev() {
+        let pattern = match value & (1 << bit) != 0 {
+            true => 0b110,
+            false => 0b100,
+        };
+
+        bits = (bits << 3) | pattern;
+    }
+
+    [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8]
+}
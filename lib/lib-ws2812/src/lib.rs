@@ -0,0 +1,12 @@
+#![no_std]
+
+//! WS2812 (NeoPixel) pixel data encoded as a SPI bit stream
+//!
+//! Every WS2812 bit is sent as three SPI bits, `100` for zero and `110`
+//! for one, so with SPI clocked at 2.25 MHz the high pulses take 444 ns
+//! and 889 ns and the whole bit takes 1.33 us. The frame ends with low
+//! SPI bytes forming the reset gap, so it can be streamed by DMA at once.
+
+mod frame;
+
+pub use frame::{frame_size, Frame, BYTES_PER_LED, RESET_BYTES, SPI_FREQUENCY_HZ};